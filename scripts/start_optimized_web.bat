@@ -52,7 +52,7 @@ if "%count%"=="0" (
 echo.
 
 echo 正在編譯優化版 Web 查看器...
-cargo build --bin simple_web_server --release
+cargo build --bin web_server --release
 if %errorlevel% neq 0 (
     echo [ERROR] 編譯失敗
     pause
@@ -66,8 +66,8 @@ echo ========================================
 echo 🚀 優化版 Web 查看器啟動中...
 echo 📍 位址: http://127.0.0.1:3000
 echo 🔗 請在瀏覽器中開啟上述網址
-echo 🔌 API: http://127.0.0.1:3000/api/announcements
-echo 📊 統計: http://127.0.0.1:3000/api/stats
+echo 🔌 API: http://127.0.0.1:3000/api/v1/announcements
+echo 📊 統計: http://127.0.0.1:3000/api/v1/stats
 echo ⏹️  按 Ctrl+C 停止服務器
 echo ========================================
 echo.
@@ -80,7 +80,7 @@ echo   - 複製和匯出功能
 echo   - 優化的卡片式設計
 echo.
 
-.\target\release\simple_web_server.exe
+.\target\release\web_server.exe
 
 echo.
 echo Web 查看器已停止
@@ -1,6 +1,6 @@
 @echo off
 echo ========================================
-echo 台灣證交所重大訊息簡化 Web 查看器
+echo 台灣證交所重大訊息 Web 查看器
 echo ========================================
 echo.
 
@@ -51,19 +51,19 @@ if "%count%"=="0" (
 
 echo.
 
-echo 正在啟動簡化 Web 查看器...
+echo 正在啟動 Web 查看器...
 echo.
 echo ========================================
 echo 🚀 Web 查看器啟動中...
 echo 📍 位址: http://127.0.0.1:3000
 echo 🔗 請在瀏覽器中開啟上述網址
-echo 🔌 API: http://127.0.0.1:3000/api/announcements
-echo 📊 統計: http://127.0.0.1:3000/api/stats
+echo 🔌 API: http://127.0.0.1:3000/api/v1/announcements
+echo 📊 統計: http://127.0.0.1:3000/api/v1/stats
 echo ⏹️  按 Ctrl+C 停止服務器
 echo ========================================
 echo.
 
-cargo run --bin simple_web_server
+cargo run --bin web_server
 
 echo.
 echo Web 查看器已停止
@@ -5,7 +5,7 @@ echo ========================================
 echo.
 
 echo 正在啟動 Web 服務器...
-start /B .\target\release\simple_web_server.exe
+start /B .\target\release\web_server.exe
 
 echo 等待服務器啟動...
 timeout /t 3 /nobreak >nul
@@ -15,19 +15,19 @@ echo 測試 API 端點:
 echo.
 
 echo 1. 檢查調試資訊:
-echo    http://127.0.0.1:3000/api/debug
+echo    http://127.0.0.1:3000/api/v1/diagnostics
 echo.
 
 echo 2. 測試基本查詢:
-echo    http://127.0.0.1:3000/api/announcements?limit=5
+echo    http://127.0.0.1:3000/api/v1/announcements?limit=5
 echo.
 
 echo 3. 測試日期範圍查詢:
-echo    http://127.0.0.1:3000/api/announcements?start_date=2025-08-14^&end_date=2025-08-15^&limit=10
+echo    http://127.0.0.1:3000/api/v1/announcements?start_date=2025-08-14^&end_date=2025-08-15^&limit=10
 echo.
 
 echo 4. 測試統計資訊:
-echo    http://127.0.0.1:3000/api/stats
+echo    http://127.0.0.1:3000/api/v1/stats
 echo.
 
 echo 5. 開啟 Web 介面:
@@ -48,5 +48,5 @@ echo 按任意鍵結束測試...
 pause >nul
 
 echo 正在停止服務器...
-taskkill /f /im simple_web_server.exe >nul 2>&1
+taskkill /f /im web_server.exe >nul 2>&1
 echo 測試完成
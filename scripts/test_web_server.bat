@@ -16,7 +16,7 @@ echo 正在啟動 Web 服務器...
 echo 位址: http://127.0.0.1:3000
 echo.
 
-.\target\release\simple_web_server.exe
+.\target\release\web_server.exe
 echo.
 echo Web 服務器已停止
 pause
@@ -0,0 +1,313 @@
+//! 原始 HTML 回應的壓縮封存。
+//!
+//! 每次向 MOPS 擷取的回應 (含失敗與限流頁面) 以 SHA-256 內容定址、gzip 壓縮後存於
+//! `<封存目錄>/objects/<雜湊前兩碼>/<雜湊>.html.gz`，相同內容只存一份；
+//! 每次擷取另在 `<封存目錄>/manifest.jsonl` 追加一筆 [`ArchiveEntry`]，同一天的多次擷取都會保留。
+//!
+//! 指定 GridFS 時，壓縮後超過門檻的內容改存於 MongoDB GridFS，清單仍寫在本機。
+
+use anyhow::{Context, Result};
+use bson::doc;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use mongodb::gridfs::GridFsBucket;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::date::RocDate;
+
+/// archive 子命令未指定 `--archive-dir` 時讀取的封存目錄
+pub const DEFAULT_ARCHIVE_DIR: &str = "archive";
+
+/// 擷取清單的檔名
+pub const MANIFEST_FILE: &str = "manifest.jsonl";
+
+/// GridFS bucket 名稱
+pub const GRIDFS_BUCKET: &str = "html_archive";
+
+/// 擷取的頁面類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveKind {
+    /// 單日重大訊息列表 (t05st02)
+    #[default]
+    List,
+    /// 公司歷史查詢 (t05st01)
+    History,
+    /// 單筆明細頁 (t05st01 step 2)
+    Detail,
+}
+
+impl FromStr for ArchiveKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "list" => Ok(Self::List),
+            "history" => Ok(Self::History),
+            "detail" => Ok(Self::Detail),
+            _ => Err(format!("不支援的頁面類型: {}。支援的類型: list, history, detail", s)),
+        }
+    }
+}
+
+impl fmt::Display for ArchiveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::List => "list",
+            Self::History => "history",
+            Self::Detail => "detail",
+        })
+    }
+}
+
+/// 壓縮內容的存放位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveStorage {
+    File,
+    GridFs,
+}
+
+/// 一次擷取的查詢內容，由用戶端在送出請求時提供
+#[derive(Debug, Clone, Default)]
+pub struct FetchInfo {
+    pub kind: ArchiveKind,
+    /// 查詢日期；歷史查詢為該年度的 1 月 1 日
+    pub date: Option<RocDate>,
+    pub market: Option<String>,
+    pub company_code: Option<String>,
+}
+
+/// 擷取清單中的一筆紀錄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub fetched_at: DateTime<Utc>,
+    pub kind: ArchiveKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<RocDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_code: Option<String>,
+    pub url: String,
+    pub http_status: u16,
+    /// 原始內容的 SHA-256
+    pub sha256: String,
+    pub size: u64,
+    pub compressed_size: u64,
+    pub storage: ArchiveStorage,
+}
+
+impl ArchiveEntry {
+    /// 壓縮內容的檔名 (GridFS 亦使用此檔名)
+    pub fn object_name(&self) -> String {
+        format!("{}.html.gz", self.sha256)
+    }
+}
+
+/// 列出或匯出時的篩選條件，未指定的欄位不限制
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveFilter {
+    pub kind: Option<ArchiveKind>,
+    pub date: Option<RocDate>,
+    pub market: Option<String>,
+    pub company_code: Option<String>,
+}
+
+impl ArchiveFilter {
+    /// 是否符合條件
+    pub fn matches(&self, entry: &ArchiveEntry) -> bool {
+        self.kind.is_none_or(|kind| entry.kind == kind)
+            && self.date.is_none_or(|date| entry.date == Some(date))
+            && self.market.as_ref().is_none_or(|market| entry.market.as_ref() == Some(market))
+            && self.company_code.as_ref().is_none_or(|code| entry.company_code.as_ref() == Some(code))
+    }
+}
+
+/// 原始 HTML 封存
+pub struct HtmlArchive {
+    root: PathBuf,
+    gridfs: Option<(GridFsBucket, usize)>,
+    // 已確認存於 GridFS 的檔名；檢查與上傳在鎖內進行，同時擷取到相同內容時只上傳一次
+    gridfs_uploaded: tokio::sync::Mutex<HashSet<String>>,
+    // 多個明細頁同時完成時，避免清單寫入交錯
+    manifest_lock: Mutex<()>,
+}
+
+impl HtmlArchive {
+    /// 開啟 (或建立) 封存目錄
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(root.join("objects")).with_context(|| format!("無法建立封存目錄 {}", root.display()))?;
+        Ok(Self { root, gridfs: None, gridfs_uploaded: Default::default(), manifest_lock: Mutex::new(()) })
+    }
+
+    /// 壓縮後達到 `min_size` 位元組的內容改存於 GridFS
+    pub fn with_gridfs(mut self, bucket: GridFsBucket, min_size: usize) -> Self {
+        self.gridfs = Some((bucket, min_size));
+        self
+    }
+
+    /// 封存目錄
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn object_path(&self, sha256: &str) -> PathBuf {
+        self.root.join("objects").join(&sha256[..2]).join(format!("{}.html.gz", sha256))
+    }
+
+    /// 封存一次擷取的回應並追加清單紀錄
+    pub async fn store(&self, info: &FetchInfo, url: &str, http_status: u16, html: &str) -> Result<ArchiveEntry> {
+        let sha256 = sha256_hex(html);
+        let compressed = compress(html.as_bytes())?;
+
+        let storage = match &self.gridfs {
+            Some((bucket, min_size)) if compressed.len() >= *min_size => {
+                self.store_gridfs(bucket, &format!("{}.html.gz", sha256), &compressed).await?;
+                ArchiveStorage::GridFs
+            }
+            _ => {
+                self.store_file(&sha256, &compressed)?;
+                ArchiveStorage::File
+            }
+        };
+
+        let entry = ArchiveEntry {
+            fetched_at: Utc::now(),
+            kind: info.kind,
+            date: info.date,
+            market: info.market.clone(),
+            company_code: info.company_code.clone(),
+            url: url.to_string(),
+            http_status,
+            sha256,
+            size: html.len() as u64,
+            compressed_size: compressed.len() as u64,
+            storage,
+        };
+        self.append_manifest(&entry)?;
+        Ok(entry)
+    }
+
+    // 與 store_file 相同，相同內容已上傳時略過
+    async fn store_gridfs(&self, bucket: &GridFsBucket, filename: &str, compressed: &[u8]) -> Result<()> {
+        let mut uploaded = self.gridfs_uploaded.lock().await;
+        if uploaded.contains(filename) {
+            return Ok(());
+        }
+        if !gridfs_contains(bucket, filename).await? {
+            bucket.upload_from_futures_0_3_reader(filename, compressed, None).await?;
+        }
+        uploaded.insert(filename.to_string());
+        Ok(())
+    }
+
+    // 相同內容已存在時不重寫；先寫入暫存檔再更名，中斷時不會留下不完整的檔案
+    fn store_file(&self, sha256: &str, compressed: &[u8]) -> Result<()> {
+        let path = self.object_path(sha256);
+        if path.exists() {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temp, compressed)?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    fn append_manifest(&self, entry: &ArchiveEntry) -> Result<()> {
+        let _guard = self.manifest_lock.lock().map_err(|_| anyhow::anyhow!("封存清單鎖定失敗"))?;
+        let mut file = OpenOptions::new().create(true).append(true).open(self.root.join(MANIFEST_FILE))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// 讀取清單中符合條件的紀錄，依擷取時間由舊到新排序
+    pub fn entries(&self, filter: &ArchiveFilter) -> Result<Vec<ArchiveEntry>> {
+        let path = self.root.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for (line_number, line) in fs::read_to_string(&path)?.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: ArchiveEntry = serde_json::from_str(line)
+                .with_context(|| format!("{} 第 {} 行格式錯誤", path.display(), line_number + 1))?;
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(|entry| entry.fetched_at);
+        Ok(entries)
+    }
+
+    /// 讀取並解壓一筆紀錄的原始 HTML
+    pub async fn load(&self, entry: &ArchiveEntry) -> Result<String> {
+        let compressed = match entry.storage {
+            ArchiveStorage::File => fs::read(self.object_path(&entry.sha256))
+                .with_context(|| format!("找不到封存內容 {}", entry.object_name()))?,
+            ArchiveStorage::GridFs => {
+                let Some((bucket, _)) = &self.gridfs else {
+                    anyhow::bail!("{} 存於 GridFS，請加上 --archive-gridfs", entry.object_name());
+                };
+                let mut compressed = Vec::new();
+                bucket.download_to_futures_0_3_writer_by_name(entry.object_name(), &mut compressed, None).await?;
+                compressed
+            }
+        };
+
+        let html = decompress(&compressed)?;
+        if sha256_hex(&html) != entry.sha256 {
+            anyhow::bail!("封存內容 {} 雜湊不符，檔案可能已損毀", entry.object_name());
+        }
+        Ok(html)
+    }
+}
+
+fn sha256_hex(html: &str) -> String {
+    Sha256::digest(html.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(data: &[u8]) -> Result<String> {
+    let mut html = String::new();
+    GzDecoder::new(data).read_to_string(&mut html)?;
+    Ok(html)
+}
+
+/// 連線 MongoDB 並取得封存使用的 GridFS bucket
+pub async fn gridfs_bucket(mongodb_uri: &str, database_name: &str) -> Result<GridFsBucket> {
+    let client = mongodb::Client::with_uri_str(mongodb_uri).await?;
+    let options = mongodb::options::GridFsBucketOptions::builder()
+        .bucket_name(GRIDFS_BUCKET.to_string())
+        .build();
+    Ok(client.database(database_name).gridfs_bucket(options))
+}
+
+// GridFS 中是否已有此檔名
+async fn gridfs_contains(bucket: &GridFsBucket, filename: &str) -> Result<bool> {
+    let options = mongodb::options::GridFsFindOptions::builder().limit(1).build();
+    let mut existing = bucket.find(doc! { "filename": filename }, options).await?;
+    Ok(existing.advance().await?)
+}
@@ -0,0 +1,73 @@
+//! 逐日回補的進度檔：記錄已完成的最後日期與失敗日期，中斷後可從此處續跑並重試失敗日期。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::date::RocDate;
+
+/// 回補進度檔，日期皆為西元 `YYYY-MM-DD` 字串
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct BackfillCheckpoint {
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_completed: Option<String>,
+    #[serde(default)]
+    pub failed_dates: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl BackfillCheckpoint {
+    pub fn new(start_date: RocDate, end_date: RocDate) -> Self {
+        Self {
+            start_date: start_date.to_gregorian_string(),
+            end_date: end_date.to_gregorian_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 讀取進度檔；不存在、格式錯誤或範圍不同時回傳 None，避免誤用其他回補工作的進度
+    pub fn load(path: &str, start_date: RocDate, end_date: RocDate) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        let checkpoint: Self = serde_json::from_str(&content).ok()?;
+        (checkpoint.start_date == start_date.to_gregorian_string()
+            && checkpoint.end_date == end_date.to_gregorian_string())
+            .then_some(checkpoint)
+    }
+
+    pub fn save(&mut self, path: &str) -> Result<()> {
+        self.updated_at = Some(chrono::Utc::now());
+        // 先寫入暫存檔再改名，避免中斷時留下損毀的進度檔
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// 是否需要查詢該日：上次完成日期之後的日期，以及先前失敗的日期
+    pub fn is_pending(&self, date: RocDate) -> bool {
+        let date_str = date.to_gregorian_string();
+        let after_last = self.last_completed.as_deref()
+            .and_then(RocDate::parse)
+            .is_none_or(|last| date > last);
+        after_last || self.failed_dates.contains(&date_str)
+    }
+
+    /// 記錄單日結果：成功時從失敗清單移除，失敗時加入；上次完成日期只會往後推進
+    pub fn record(&mut self, date: RocDate, succeeded: bool) {
+        let date_str = date.to_gregorian_string();
+        if succeeded {
+            self.failed_dates.retain(|d| d != &date_str);
+        } else if !self.failed_dates.contains(&date_str) {
+            self.failed_dates.push(date_str.clone());
+            self.failed_dates.sort();
+        }
+
+        let last = self.last_completed.as_deref().and_then(RocDate::parse);
+        if last.is_none_or(|last| date > last) {
+            self.last_completed = Some(date_str);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use clap::Parser;
+use std::net::SocketAddr;
+use std::time::Duration;
+use twse_announcements::mock::{Fault, MockMops};
+
+#[derive(Parser)]
+#[command(name = "twse-mock-mops")]
+#[command(about = "本機模擬的 MOPS 伺服器，供端對端測試使用")]
+struct Args {
+    /// 回應檔目錄 (list[_<市場別>]_YYYYMMDD.html、history_<公司代號>_<民國年>.html、detail_<公司代號>_<發言序號>.html)
+    #[arg(long)]
+    fixtures_dir: Option<String>,
+
+    /// 啟動時排入的故障，依序套用在前幾個請求 (throttle, timeout, server_error, layout_change)
+    #[arg(long)]
+    fault: Vec<Fault>,
+
+    /// 逾時故障的延遲秒數
+    #[arg(long, default_value = "120")]
+    timeout_delay_secs: u64,
+
+    /// 監聽位址
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// 監聽埠號
+    #[arg(long, default_value = "3100")]
+    port: u16,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    println!("🚀 啟動模擬 MOPS 伺服器");
+    let mock = MockMops::new();
+    if let Some(dir) = &args.fixtures_dir {
+        let loaded = mock.load_dir(dir)?;
+        println!("📁 已載入 {} 個回應檔: {}", loaded, dir);
+    }
+    if !args.fault.is_empty() {
+        let faults: Vec<String> = args.fault.iter().map(Fault::to_string).collect();
+        println!("💥 排入故障: {}", faults.join(", "));
+        mock.push_faults(args.fault.iter().copied());
+    }
+    mock.set_timeout_delay(Duration::from_secs(args.timeout_delay_secs));
+
+    let addr = SocketAddr::new(args.host.parse()?, args.port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("✅ 查詢網址: http://{}/mops/web", addr);
+    println!("   twse-announcements --mops-base-url http://{}/mops/web --date 2025-08-15", addr);
+    println!("   排入故障: curl -X POST -H 'Content-Type: application/json' -d '[\"throttle\"]' http://{}/__mock/faults", addr);
+    println!("   收到的請求: http://{}/__mock/requests", addr);
+    println!("⏹️  按 Ctrl+C 停止伺服器");
+
+    axum::serve(listener, mock.router()).await?;
+    Ok(())
+}
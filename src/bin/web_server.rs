@@ -1,14 +1,19 @@
 use clap::Parser;
 use std::net::SocketAddr;
-use tokio;
-
-// 直接包含 web_viewer 模組的內容
-include!("../web_viewer.rs");
+use std::sync::Arc;
+use std::time::Duration;
+use twse_announcements::search::SearchIndex;
+use twse_announcements::store::{open_store, AnnouncementQuery};
+use twse_announcements::web::create_app_with_search;
 
 #[derive(Parser)]
 #[command(name = "twse-web-viewer")]
 #[command(about = "台灣證交所重大訊息 Web 查看器")]
 struct Args {
+    /// 儲存連線字串 (mongodb://...、sqlite:///path.db，或由 JSON 檔載入記憶體的 memory://path.json)，未指定時使用 --mongodb-uri
+    #[arg(long)]
+    store: Option<String>,
+
     /// MongoDB 連接字串
     #[arg(long, default_value = "mongodb://localhost:27017")]
     mongodb_uri: String,
@@ -28,6 +33,14 @@ struct Args {
     /// Web 服務器監聽埠號
     #[arg(long, default_value = "3000")]
     port: u16,
+
+    /// 全文索引目錄，重新啟動後只需同步有變動的公告；未指定時索引建立於記憶體中
+    #[arg(long)]
+    search_index: Option<String>,
+
+    /// 全文索引與儲存後端同步的間隔 (秒)
+    #[arg(long, default_value = "60")]
+    search_refresh_secs: u64,
 }
 
 #[tokio::main]
@@ -35,42 +48,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
     println!("🚀 啟動台灣證交所重大訊息 Web 查看器");
-    println!("📊 MongoDB URI: {}", args.mongodb_uri);
-    println!("🗄️  資料庫: {}", args.mongodb_database);
-    println!("📁 集合: {}", args.mongodb_collection);
+    let store_uri = args.store.as_deref().unwrap_or(&args.mongodb_uri);
+    println!("📊 儲存位置: {}", store_uri);
+    if store_uri.starts_with("mongodb") {
+        println!("🗄️  資料庫: {}", args.mongodb_database);
+        println!("📁 集合: {}", args.mongodb_collection);
+    }
     
-    // 測試 MongoDB 連接
-    println!("🔗 正在連接 MongoDB...");
-    match mongodb::Client::with_uri_str(&args.mongodb_uri).await {
-        Ok(client) => {
-            // 測試連接
-            match client.database(&args.mongodb_database)
-                .run_command(mongodb::bson::doc! {"ping": 1}, None).await {
-                Ok(_) => println!("✅ MongoDB 連接成功"),
-                Err(e) => {
-                    eprintln!("❌ MongoDB 連接測試失敗: {}", e);
-                    eprintln!("請確認:");
-                    eprintln!("1. MongoDB 服務正在運行");
-                    eprintln!("2. 連接字串正確: {}", args.mongodb_uri);
-                    eprintln!("3. 資料庫存在且有資料");
-                    return Err(e.into());
-                }
-            }
+    // 測試資料庫連接
+    println!("🔗 正在連接資料庫...");
+    let store = match open_store(store_uri, &args.mongodb_database, &args.mongodb_collection).await {
+        Ok(store) => {
+            println!("✅ {} 連接成功", store.backend());
+            store
         }
         Err(e) => {
-            eprintln!("❌ 無法連接到 MongoDB: {}", e);
-            eprintln!("請確認 MongoDB 服務正在運行，連接字串: {}", args.mongodb_uri);
+            eprintln!("❌ 無法連接到資料庫: {}", e);
+            eprintln!("請確認:");
+            eprintln!("1. MongoDB 服務正在運行，或改用 --store sqlite:///path.db / memory://path.json");
+            eprintln!("2. 連接字串正確: {}", store_uri);
+            eprintln!("3. 資料庫存在且有資料");
             return Err(e.into());
         }
+    };
+    
+    // 檢查資料
+    let count = store.count(&AnnouncementQuery::default()).await?;
+    println!("📊 找到 {} 筆重大訊息資料", count);
+    
+    if count == 0 {
+        println!("⚠️  資料庫中沒有資料，請先使用 CLI 工具收集資料:");
+        println!("   cargo run -- --date 2025-08-15 --save-mongodb");
     }
     
+    // 建立全文索引
+    let search = match &args.search_index {
+        Some(path) => {
+            println!("🔎 全文索引目錄: {}", path);
+            SearchIndex::open_in_dir(path)?
+        }
+        None => SearchIndex::in_memory(),
+    }
+    .with_refresh_interval(Duration::from_secs(args.search_refresh_secs));
+    let summary = search.refresh(store.as_ref()).await?;
+    println!("🔎 全文索引: {} 筆 (本次寫入 {} 筆，移除 {} 筆)", summary.total, summary.indexed, summary.removed);
+
     // 創建 Web 應用程式
     println!("🌐 正在建立 Web 應用程式...");
-    let app = create_app(
-        &args.mongodb_uri,
-        &args.mongodb_database,
-        &args.mongodb_collection,
-    ).await?;
+    let app = create_app_with_search(store, Arc::new(search));
     
     // 設定監聽位址
     let addr = SocketAddr::new(
@@ -81,15 +106,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎯 Web 服務器啟動成功！");
     println!("📍 位址: http://{}:{}", args.host, args.port);
     println!("🔗 主頁: http://{}:{}/", args.host, args.port);
+    println!("🔎 進階搜尋: http://{}:{}/search", args.host, args.port);
     println!("📊 統計: http://{}:{}/stats", args.host, args.port);
-    println!("🔌 API: http://{}:{}/api/announcements", args.host, args.port);
+    println!("🔌 API: http://{}:{}/api/v1/announcements", args.host, args.port);
+    println!("🔎 全文檢索: http://{}:{}/api/v1/search?q=私募", args.host, args.port);
+    println!("📘 OpenAPI: http://{}:{}/api/openapi.json", args.host, args.port);
     println!();
     println!("💡 使用說明:");
     println!("   - 瀏覽器開啟 http://{}:{} 查看重大訊息", args.host, args.port);
-    println!("   - 可以按公司代號、日期篩選");
-    println!("   - 支援關鍵字搜尋");
+    println!("   - 可以按公司代號、市場別、日期或日期範圍篩選");
+    println!("   - 支援關鍵字搜尋，/api/v1/search 全文檢索標題與內容");
     println!("   - 點擊公告可查看詳細內容");
     println!("   - /stats 頁面提供統計分析");
+    println!("   - /search 為單頁式搜尋介面，可匯出 CSV");
+    println!("   - /api/v1 提供 JSON 資料");
     println!();
     println!("⏹️  按 Ctrl+C 停止服務器");
     
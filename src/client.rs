@@ -0,0 +1,456 @@
+//! MOPS 公開資訊觀測站的 HTTP 用戶端，包含逾時、重試退避與速率限制。
+
+use anyhow::Result;
+use futures_util::stream::{self, StreamExt};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::archive::{ArchiveKind, FetchInfo, HtmlArchive};
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementDetail};
+use crate::parser;
+
+/// MOPS 查詢頁面所在的網址 (ajax_t05st01、ajax_t05st02 等)
+pub const DEFAULT_BASE_URL: &str = "https://mopsov.twse.com.tw/mops/web";
+
+/// 可個別查詢的市場別；MOPS 的 `all` 回應不標示公告所屬市場，因此 `all` 依序查詢這些市場
+pub const MARKETS: [&str; 4] = ["sii", "otc", "rotc", "pub"];
+
+/// HTTP 連線設定：查詢網址、逾時、重試退避與每分鐘請求上限
+#[derive(Debug, Clone)]
+pub struct TwseClientConfig {
+    /// 查詢頁面的網址前綴，測試時可指向本機模擬伺服器
+    pub base_url: String,
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub max_retries: u32,
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    pub throttle_cooldown: Duration,
+    pub requests_per_minute: u32,
+    /// `all` 分市場查詢時，各市場請求之間的最短間隔；不限制每分鐘請求數時仍然適用
+    pub market_interval: Duration,
+}
+
+impl Default for TwseClientConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            max_retries: 3,
+            backoff_base: Duration::from_secs(2),
+            backoff_max: Duration::from_secs(60),
+            throttle_cooldown: Duration::from_secs(90),
+            requests_per_minute: 20,
+            market_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// 請求已達重試上限仍失敗；重試與退避已在用戶端完成，呼叫端不需再重試
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetriesExhausted {
+    pub retries: u32,
+}
+
+impl std::fmt::Display for RetriesExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "已達重試上限 ({} 次)", self.retries)
+    }
+}
+
+// 單次請求失敗的分類，決定是否重試以及等待多久
+enum FetchError {
+    Retryable(anyhow::Error),
+    Throttled,
+    Fatal(anyhow::Error),
+}
+
+/// MOPS 公開資訊觀測站的查詢用戶端
+pub struct TwseClient {
+    client: Client,
+    config: TwseClientConfig,
+    // 下一次允許發送請求的時間點，所有查詢共用同一個速率限制
+    next_request_at: Arc<tokio::sync::Mutex<Instant>>,
+    // 指定時封存每一次收到的回應
+    archive: Option<Arc<HtmlArchive>>,
+}
+
+impl TwseClient {
+    pub fn new() -> Self {
+        Self::with_config(TwseClientConfig::default())
+    }
+
+    pub fn with_config(config: TwseClientConfig) -> Self {
+        let client = Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/139.0.0.0 Safari/537.36")
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            config,
+            next_request_at: Arc::new(tokio::sync::Mutex::new(Instant::now())),
+            archive: None,
+        }
+    }
+
+    /// 封存每一次收到的回應 (含重試前的失敗與限流頁面)
+    pub fn with_archive(mut self, archive: HtmlArchive) -> Self {
+        self.archive = Some(Arc::new(archive));
+        self
+    }
+
+    // 封存失敗不影響查詢，只輸出警告
+    async fn archive_response(&self, info: &FetchInfo, url: &str, status: u16, text: &str) {
+        if let Some(archive) = &self.archive {
+            if let Err(e) = archive.store(info, url, status, text).await {
+                println!("⚠️  封存原始回應失敗: {}", e);
+            }
+        }
+    }
+
+    // 查詢頁面的完整網址
+    fn endpoint(&self, page: &str) -> String {
+        format!("{}/{}", self.config.base_url.trim_end_matches('/'), page)
+    }
+
+    // 等待限流冷卻期結束，並依每分鐘請求上限確保請求之間保持固定間隔；
+    // 不限制請求數 (0) 時仍需等待冷卻期
+    async fn wait_for_rate_limit(&self) {
+        let mut next_request_at = self.next_request_at.lock().await;
+        let now = Instant::now();
+        if *next_request_at > now {
+            tokio::time::sleep(*next_request_at - now).await;
+        }
+        if self.config.requests_per_minute > 0 {
+            *next_request_at = Instant::now() + Duration::from_secs(60) / self.config.requests_per_minute;
+        }
+    }
+
+    // 下一個請求至少等待 interval，與每分鐘請求上限取較晚者
+    async fn delay_next_request(&self, interval: Duration) {
+        let mut next_request_at = self.next_request_at.lock().await;
+        *next_request_at = (*next_request_at).max(Instant::now() + interval);
+    }
+
+    // 指數退避加上隨機抖動，避免多個工作同時重試
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponential = self.config.backoff_base.saturating_mul(1 << attempt.min(16));
+        let capped = exponential.min(self.config.backoff_max);
+        let jitter_range = (capped.as_millis() as u64 / 2).max(1);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0);
+        capped + Duration::from_millis(nanos % jitter_range)
+    }
+
+    // 送出表單請求並回傳內容；連線錯誤、逾時、5xx 與限流 (429 或限流頁面) 會自動重試
+    async fn post_form(&self, url: &str, referer: &str, form_data: &HashMap<&str, &str>, info: &FetchInfo) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_rate_limit().await;
+
+            // 限流時不另外計算退避時間，改以冷卻期暫停所有查詢
+            let (error, backoff) = match self.try_post_form(url, referer, form_data, info).await {
+                Ok(text) => return Ok(text),
+                Err(FetchError::Fatal(e)) => return Err(e),
+                Err(FetchError::Throttled) => (anyhow::anyhow!("MOPS 回應查詢過於頻繁"), None),
+                Err(FetchError::Retryable(e)) => (e, Some(self.backoff_delay(attempt))),
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(error.context(RetriesExhausted { retries: self.config.max_retries }));
+            }
+            attempt += 1;
+
+            match backoff {
+                Some(delay) => {
+                    println!("⚠️  請求失敗: {}，{:.1} 秒後重試 ({}/{})",
+                             error, delay.as_secs_f64(), attempt, self.config.max_retries);
+                    tokio::time::sleep(delay).await;
+                }
+                None => {
+                    println!("⏳ {}，冷卻 {} 秒後重試 ({}/{})",
+                             error, self.config.throttle_cooldown.as_secs(), attempt, self.config.max_retries);
+                    // 延後共用的下次請求時間，其他查詢也一併暫停，下一輪的 wait_for_rate_limit 等待冷卻期結束
+                    let mut next_request_at = self.next_request_at.lock().await;
+                    *next_request_at = (*next_request_at).max(Instant::now() + self.config.throttle_cooldown);
+                }
+            }
+        }
+    }
+
+    async fn try_post_form(
+        &self,
+        url: &str,
+        referer: &str,
+        form_data: &HashMap<&str, &str>,
+        info: &FetchInfo,
+    ) -> std::result::Result<String, FetchError> {
+        let origin = reqwest::Url::parse(url)
+            .map(|url| url.origin().ascii_serialization())
+            .map_err(|e| FetchError::Fatal(anyhow::anyhow!("無效的查詢網址 {}: {}", url, e)))?;
+        let response = self.client
+            .post(url)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Origin", origin)
+            .header("Referer", referer)
+            .form(form_data)
+            .send()
+            .await
+            .map_err(transport_error)?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(transport_error)?;
+        self.archive_response(info, url, status.as_u16(), &text).await;
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(FetchError::Throttled);
+        }
+        if status.is_server_error() {
+            return Err(FetchError::Retryable(anyhow::anyhow!("HTTP request failed: {}", status)));
+        }
+        if !status.is_success() {
+            return Err(FetchError::Fatal(anyhow::anyhow!("HTTP request failed: {}", status)));
+        }
+
+        if parser::is_throttled(&text) {
+            return Err(FetchError::Throttled);
+        }
+
+        Ok(text)
+    }
+
+    /// 查詢指定日期與市場別的重大訊息，回傳標記市場別的解析結果與各市場的 (市場別, 原始 HTML)
+    ///
+    /// `all` 逐一查詢 [`MARKETS`]，任一市場失敗即回傳錯誤，不會只回傳部分市場的結果。
+    /// 列表頁的每一列不含市場別，`TYPEK=all` 的單一回應無法標記，因此分成四個請求；
+    /// 每個請求都計入 `requests_per_minute`，並且彼此至少間隔 `market_interval`。
+    pub async fn fetch_announcements(&self, date: RocDate, market: &str) -> Result<(Vec<Announcement>, Vec<(&'static str, String)>)> {
+        let markets = match parse_market(market)? {
+            "all" => MARKETS.to_vec(),
+            market => vec![market],
+        };
+
+        let mut announcements = Vec::new();
+        let mut pages = Vec::new();
+        for (index, market) in markets.into_iter().enumerate() {
+            if index > 0 {
+                self.delay_next_request(self.config.market_interval).await;
+            }
+            let (mut listed, text) = self.fetch_market_list(date, market).await?;
+            tag_market(&mut listed, market);
+            announcements.extend(listed);
+            pages.push((market, text));
+        }
+
+        Ok((announcements, pages))
+    }
+
+    // 查詢單一市場別的當日列表 (ajax_t05st02)
+    async fn fetch_market_list(&self, date: RocDate, market: &'static str) -> Result<(Vec<Announcement>, String)> {
+        let url = self.endpoint("ajax_t05st02");
+
+        // 創建字串變數以避免生命週期問題
+        let year_str = date.roc_year().to_string(); // 民國年
+        let month_str = format!("{:02}", date.month());
+        let day_str = format!("{:02}", date.day());
+
+        let mut form_data = HashMap::new();
+        form_data.insert("encodeURIComponent", "1");
+        form_data.insert("step", "1");
+        form_data.insert("step00", "0");
+        form_data.insert("firstin", "1");
+        form_data.insert("off", "1");
+        form_data.insert("TYPEK", market);
+        form_data.insert("year", year_str.as_str());
+        form_data.insert("month", month_str.as_str());
+        form_data.insert("day", day_str.as_str());
+
+        // println!("發送請求到: {}", url);
+        // println!("表單資料: {:?}", form_data);
+
+        let info = FetchInfo {
+            kind: ArchiveKind::List,
+            date: Some(date),
+            market: Some(market.to_string()),
+            company_code: None,
+        };
+        let text = self.post_form(&url, &self.endpoint("t05st02"), &form_data, &info).await?;
+
+        // 解析回應並轉換為結構化資料，網站結構變更時回傳 LayoutChangedError
+        let outcome = parser::parse_response(&text);
+        outcome.print();
+        let announcements = outcome.into_announcements()?;
+
+        Ok((announcements, text))
+    }
+
+    /// 查詢單一公司在日期區間內的歷史重大訊息 (t05st01)
+    ///
+    /// MOPS 以民國年度為查詢單位，區間跨年時逐年查詢後依日期篩選。
+    /// 歷史頁的每一列不含市場別，只有指定單一市場別 (`TYPEK`) 查詢時才標記；`all` 的結果保持空白。
+    /// 回傳依發言日期時間排序的結果，以及每個年度的 (民國年, 原始 HTML)。
+    pub async fn fetch_company_history(
+        &self,
+        company_code: &str,
+        market: &str,
+        from: RocDate,
+        to: RocDate,
+    ) -> Result<(Vec<Announcement>, Vec<(i32, String)>)> {
+        if from > to {
+            anyhow::bail!("起始日期 {} 晚於結束日期 {}", from, to);
+        }
+
+        let url = self.endpoint("ajax_t05st01");
+        let mut announcements = Vec::new();
+        let mut pages = Vec::new();
+
+        for roc_year in from.roc_year()..=to.roc_year() {
+            let year_str = roc_year.to_string();
+
+            let mut form_data = HashMap::new();
+            form_data.insert("encodeURIComponent", "1");
+            form_data.insert("step", "1");
+            form_data.insert("firstin", "1");
+            form_data.insert("off", "1");
+            form_data.insert("queryName", "co_id");
+            form_data.insert("inpuType", "co_id");
+            form_data.insert("TYPEK", market);
+            form_data.insert("co_id", company_code);
+            form_data.insert("year", year_str.as_str());
+
+            let info = FetchInfo {
+                kind: ArchiveKind::History,
+                date: RocDate::from_roc_ymd(roc_year, 1, 1),
+                market: (market != "all").then(|| market.to_string()),
+                company_code: Some(company_code.to_string()),
+            };
+            let text = self.post_form(&url, &self.endpoint("t05st01"), &form_data, &info).await?;
+
+            let outcome = parser::parse_company_history(&text, company_code);
+            outcome.print();
+            let mut yearly = outcome.into_announcements()?;
+            yearly.retain(|a| a.date >= from && a.date <= to);
+            tag_market(&mut yearly, market);
+            println!("📅 民國 {} 年: {} 筆", roc_year, yearly.len());
+
+            announcements.extend(yearly);
+            pages.push((roc_year, text));
+        }
+
+        announcements.sort_by(|a, b| (a.date, &a.time).cmp(&(b.date, &b.time)));
+
+        Ok((announcements, pages))
+    }
+
+    /// 查詢單筆重大訊息的明細頁 (t05st01)，回傳結構化資料與原始 HTML
+    pub async fn fetch_detail(&self, announcement: &Announcement) -> Result<(AnnouncementDetail, String)> {
+        let url = self.endpoint("ajax_t05st01");
+
+        let sequence_number = announcement.sequence_number.as_deref()
+            .ok_or_else(|| anyhow::anyhow!("{} 缺少發言序號，無法查詢明細", announcement.company_code))?;
+
+        let date = announcement.date;
+        let year_str = date.roc_year().to_string();
+        let month_str = format!("{:02}", date.month());
+        let day_str = format!("{:02}", date.day());
+        let spoke_date = date.to_compact_string(); // 發言日期 YYYYMMDD
+        let spoke_time: String = announcement.time.chars().filter(|c| c.is_ascii_digit()).collect(); // 發言時間 HHMMSS
+        let market = announcement.market.as_deref().unwrap_or("all");
+
+        let mut form_data = HashMap::new();
+        form_data.insert("encodeURIComponent", "1");
+        form_data.insert("step", "2");
+        form_data.insert("firstin", "1");
+        form_data.insert("off", "1");
+        form_data.insert("colorchg", "1");
+        form_data.insert("TYPEK", market);
+        form_data.insert("co_id", announcement.company_code.as_str());
+        form_data.insert("year", year_str.as_str());
+        form_data.insert("month", month_str.as_str());
+        form_data.insert("day", day_str.as_str());
+        form_data.insert("spoke_date", spoke_date.as_str());
+        form_data.insert("spoke_time", spoke_time.as_str());
+        form_data.insert("seq_no", sequence_number);
+
+        let info = FetchInfo {
+            kind: ArchiveKind::Detail,
+            date: Some(date),
+            market: announcement.market.clone(),
+            company_code: Some(announcement.company_code.clone()),
+        };
+        let text = self.post_form(&url, &self.endpoint("t05st01"), &form_data, &info).await?;
+        let detail = parser::parse_detail_page(&text)?;
+
+        Ok((detail, text))
+    }
+
+    /// 以有限併發查詢多筆明細頁，成功者寫入 `detail` 欄位
+    ///
+    /// 回傳每筆的索引與結果（成功時為明細頁原始 HTML），所有請求仍受共用速率限制約束。
+    pub async fn fetch_details(&self, announcements: &mut [Announcement], concurrency: usize) -> Vec<(usize, Result<String>)> {
+        let results: Vec<_> = stream::iter(announcements.iter().enumerate())
+            .map(|(index, announcement)| async move { (index, self.fetch_detail(announcement).await) })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut outcomes: Vec<(usize, Result<String>)> = results
+            .into_iter()
+            .map(|(index, result)| {
+                let html = result.map(|(detail, html)| {
+                    announcements[index].detail = Some(detail);
+                    html
+                });
+                (index, html)
+            })
+            .collect();
+        outcomes.sort_by_key(|(index, _)| *index);
+        outcomes
+    }
+}
+
+// 只有連線失敗與逾時值得重試，其他錯誤 (無效的請求、重新導向過多、解碼失敗等) 重試也不會成功
+fn transport_error(e: reqwest::Error) -> FetchError {
+    if e.is_timeout() || e.is_connect() {
+        FetchError::Retryable(e.into())
+    } else {
+        FetchError::Fatal(e.into())
+    }
+}
+
+impl Default for TwseClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 標記市場別；`all` 的回應無法分辨所屬市場，保持空白 (例如舊版 `all` 存檔重新解析時)
+pub fn tag_market(announcements: &mut [Announcement], market: &str) {
+    if market != "all" {
+        for announcement in announcements {
+            announcement.market = Some(market.to_string());
+        }
+    }
+}
+
+/// 將市場別參數轉換為 MOPS 表單的 TYPEK 值
+pub fn parse_market(market: &str) -> Result<&'static str> {
+    match market.to_lowercase().as_str() {
+        "all" => Ok("all"),
+        "sii" | "listed" => Ok("sii"),
+        "otc" => Ok("otc"),
+        "rotc" | "emerging" => Ok("rotc"),
+        "pub" | "public" => Ok("pub"),
+        _ => Err(anyhow::anyhow!("不支援的市場別: {}。支援的市場別: all, sii(listed), otc, rotc(emerging), pub(public)", market)),
+    }
+}
@@ -0,0 +1,386 @@
+//! 民國／西元日期型別。
+//!
+//! MOPS 的日期以民國年呈現 (`114/08/17`)，事實發生日隱藏欄位為 `YYYYMMDD`，
+//! 查詢參數則使用西元 `YYYY-MM-DD`。[`RocDate`] 統一處理這些格式：
+//!
+//! - 解析：接受民國或西元、`/`、`-`、`.`、`年月日` 分隔及 7/8 位數字
+//! - 序列化：JSON 等人類可讀格式輸出西元 `YYYY-MM-DD`，寫入 MongoDB 時輸出 BSON 日期
+//!   （UTC 午夜），範圍查詢只需單一索引比較
+//! - [`roc_format`]：JSON 改輸出民國 `YYY/MM/DD`，MongoDB 仍為 BSON 日期
+//! - [`timestamp_format`]：公告時間戳記，JSON 輸出 RFC 3339，MongoDB 存為 BSON 日期
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// 民國年與西元年的差距
+pub const ROC_YEAR_OFFSET: i32 = 1911;
+
+/// 台灣時間 (UTC+8)，MOPS 的公告時間以此時區表示
+pub fn taipei_offset() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).expect("UTC+8 為有效時區")
+}
+
+/// 不含時間的日期，可用民國或西元格式解析與輸出
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RocDate(NaiveDate);
+
+/// 無法辨識的日期字串
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRocDateError(String);
+
+impl fmt::Display for ParseRocDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "無法辨識的日期格式: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRocDateError {}
+
+impl RocDate {
+    /// 以西元年月日建立
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, month, day).map(Self)
+    }
+
+    /// 以民國年月日建立
+    pub fn from_roc_ymd(roc_year: i32, month: u32, day: u32) -> Option<Self> {
+        if roc_year <= 0 {
+            return None;
+        }
+        Self::from_ymd(roc_year + ROC_YEAR_OFFSET, month, day)
+    }
+
+    /// 本機時區的今日
+    pub fn today() -> Self {
+        Self(Local::now().date_naive())
+    }
+
+    /// 寬鬆解析常見的日期寫法，無法辨識時回傳 `None`
+    ///
+    /// 支援 `114/08/17`、`114/8/17`、`2025-08-17`、`2025/08/17`、`20250817`、
+    /// `1140817`、`民國114年8月17日`、`2025年8月17日`，字串後方的附註文字會被忽略。
+    pub fn parse(input: &str) -> Option<Self> {
+        let token = input.trim().trim_start_matches("民國").split_whitespace().next()?;
+
+        // 純數字：8 位為西元 YYYYMMDD，7 位為民國 YYYMMDD
+        if token.chars().all(|c| c.is_ascii_digit()) {
+            return match token.len() {
+                8 => Self::from_ymd(token[0..4].parse().ok()?, token[4..6].parse().ok()?, token[6..8].parse().ok()?),
+                7 => Self::from_roc_ymd(token[0..3].parse().ok()?, token[3..5].parse().ok()?, token[5..7].parse().ok()?),
+                _ => None,
+            };
+        }
+
+        let normalized = token.replace(['年', '月', '-', '.'], "/").replace('日', "");
+        let mut parts = normalized.split('/').filter(|p| !p.is_empty());
+        let year: i32 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        // 四位數以下的年份視為民國年
+        if year < 1000 {
+            Self::from_roc_ymd(year, month, day)
+        } else {
+            Self::from_ymd(year, month, day)
+        }
+    }
+
+    pub fn naive_date(&self) -> NaiveDate {
+        self.0
+    }
+
+    pub fn year(&self) -> i32 {
+        self.0.year()
+    }
+
+    pub fn month(&self) -> u32 {
+        self.0.month()
+    }
+
+    pub fn day(&self) -> u32 {
+        self.0.day()
+    }
+
+    pub fn roc_year(&self) -> i32 {
+        self.0.year() - ROC_YEAR_OFFSET
+    }
+
+    /// 民國格式 `114/08/17`（MOPS 列表使用的格式）
+    pub fn to_roc_string(&self) -> String {
+        format!("{}/{:02}/{:02}", self.roc_year(), self.month(), self.day())
+    }
+
+    /// 民國格式不補零 `114/8/17`
+    pub fn to_roc_string_short(&self) -> String {
+        format!("{}/{}/{}", self.roc_year(), self.month(), self.day())
+    }
+
+    /// 西元格式 `2025-08-17`
+    pub fn to_gregorian_string(&self) -> String {
+        self.0.format("%Y-%m-%d").to_string()
+    }
+
+    /// 西元緊湊格式 `20250817`（檔名使用）
+    pub fn to_compact_string(&self) -> String {
+        self.0.format("%Y%m%d").to_string()
+    }
+
+    /// 當日 UTC 午夜的 BSON 日期
+    pub fn to_bson(&self) -> bson::DateTime {
+        let midnight = self.0.and_hms_opt(0, 0, 0).expect("午夜必定有效");
+        bson::DateTime::from_chrono(Utc.from_utc_datetime(&midnight))
+    }
+
+    /// 由 BSON 日期取回日期部分（以 UTC 計算）
+    pub fn from_bson(datetime: bson::DateTime) -> Self {
+        Self(datetime.to_chrono().date_naive())
+    }
+
+    /// 結合公告時間 (`HH:MM:SS` 或 `HH:MM`，台灣時間) 轉為 UTC 時間戳記
+    pub fn with_time(&self, time: &str) -> Option<DateTime<Utc>> {
+        let time = time.trim();
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .ok()?;
+        taipei_offset()
+            .from_local_datetime(&self.0.and_time(time))
+            .single()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// 下一日
+    pub fn succ(&self) -> Option<Self> {
+        self.0.succ_opt().map(Self)
+    }
+}
+
+impl From<NaiveDate> for RocDate {
+    fn from(date: NaiveDate) -> Self {
+        Self(date)
+    }
+}
+
+impl From<RocDate> for NaiveDate {
+    fn from(date: RocDate) -> Self {
+        date.0
+    }
+}
+
+impl From<RocDate> for bson::Bson {
+    fn from(date: RocDate) -> Self {
+        bson::Bson::DateTime(date.to_bson())
+    }
+}
+
+impl FromStr for RocDate {
+    type Err = ParseRocDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).ok_or_else(|| ParseRocDateError(s.to_string()))
+    }
+}
+
+/// 顯示為西元 `YYYY-MM-DD`
+impl fmt::Display for RocDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.to_gregorian_string())
+    }
+}
+
+impl Serialize for RocDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_gregorian_string())
+        } else {
+            self.to_bson().serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RocDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RocDateVisitor)
+    }
+}
+
+// 接受任何可解析的日期字串，以及 BSON 日期（延伸 JSON `{"$date": ...}` 或毫秒數）
+struct RocDateVisitor;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExtendedDateBody {
+    Millis(i64),
+    Text(String),
+    NumberLong {
+        #[serde(rename = "$numberLong")]
+        number_long: String,
+    },
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    bson::DateTime::from_millis(millis).to_chrono()
+}
+
+// 讀取延伸 JSON 日期物件 `{"$date": ...}` 的內容
+fn read_extended_date<'de, A: MapAccess<'de>>(mut map: A) -> Result<DateTime<Utc>, A::Error> {
+    let key: String = map.next_key()?.ok_or_else(|| de::Error::custom("空的日期物件"))?;
+    if key != "$date" {
+        return Err(de::Error::unknown_field(&key, &["$date"]));
+    }
+
+    match map.next_value::<ExtendedDateBody>()? {
+        ExtendedDateBody::Millis(millis) => Ok(from_millis(millis)),
+        ExtendedDateBody::NumberLong { number_long } => {
+            Ok(from_millis(number_long.parse().map_err(de::Error::custom)?))
+        }
+        ExtendedDateBody::Text(text) => match text.parse::<i64>() {
+            Ok(millis) => Ok(from_millis(millis)),
+            // 寬鬆延伸 JSON 以 ISO-8601 字串表示
+            Err(_) => DateTime::parse_from_rfc3339(&text)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(de::Error::custom),
+        },
+    }
+}
+
+impl<'de> Visitor<'de> for RocDateVisitor {
+    type Value = RocDate;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("民國或西元日期字串，或 BSON 日期")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<RocDate, E> {
+        RocDate::parse(value).ok_or_else(|| E::custom(ParseRocDateError(value.to_string())))
+    }
+
+    fn visit_i64<E: de::Error>(self, millis: i64) -> Result<RocDate, E> {
+        Ok(RocDate(from_millis(millis).date_naive()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<RocDate, A::Error> {
+        read_extended_date(map).map(|dt| RocDate(dt.date_naive()))
+    }
+}
+
+/// 寬鬆反序列化選填日期：無法辨識的字串（例如舊資料中的自由文字）視為 `None`，
+/// 避免單一欄位讓整筆資料讀取失敗
+pub fn deserialize_lenient<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<RocDate>, D::Error> {
+    deserializer.deserialize_option(LenientVisitor)
+}
+
+struct LenientVisitor;
+
+impl<'de> Visitor<'de> for LenientVisitor {
+    type Value = Option<RocDate>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        RocDateVisitor.expecting(formatter)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(LenientVisitor)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(RocDate::parse(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, millis: i64) -> Result<Self::Value, E> {
+        RocDateVisitor.visit_i64(millis).map(Some)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        RocDateVisitor.visit_map(map).map(Some)
+    }
+}
+
+/// 搭配 `#[serde(with = "roc_format")]` 使用：人類可讀格式輸出民國 `YYY/MM/DD`
+pub mod roc_format {
+    use super::RocDate;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &RocDate, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&date.to_roc_string())
+        } else {
+            date.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RocDate, D::Error> {
+        RocDate::deserialize(deserializer)
+    }
+}
+
+/// 搭配 `#[serde(with = "timestamp_format")]` 用於 `Option<DateTime<Utc>>`：
+/// 人類可讀格式輸出 RFC 3339，MongoDB 存為 BSON 日期以便排序與範圍查詢
+pub mod timestamp_format {
+    use super::{from_millis, read_extended_date};
+    use chrono::{DateTime, Utc};
+    use serde::de::{self, MapAccess, Visitor};
+    use serde::{Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(timestamp: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(dt) if serializer.is_human_readable() => dt.serialize(serializer),
+            Some(dt) => bson::DateTime::from_chrono(*dt).serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        deserializer.deserialize_option(TimestampVisitor)
+    }
+
+    struct TimestampVisitor;
+
+    impl<'de> Visitor<'de> for TimestampVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("RFC 3339 時間字串或 BSON 日期")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(TimestampVisitor)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            DateTime::parse_from_rfc3339(value)
+                .map(|dt| Some(dt.with_timezone(&Utc)))
+                .map_err(E::custom)
+        }
+
+        fn visit_i64<E: de::Error>(self, millis: i64) -> Result<Self::Value, E> {
+            Ok(Some(from_millis(millis)))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            read_extended_date(map).map(Some)
+        }
+    }
+}
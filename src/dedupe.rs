@@ -0,0 +1,262 @@
+//! 合併 MongoDB 既有的重複公告並建立識別鍵唯一索引。
+//!
+//! 早期版本沒有唯一索引，同時執行多個查詢或使用 `replace` 模式都可能寫入重複公告。
+//! 此模組找出識別鍵 (公司代號 + 日期 + 時間 + 標題) 相同的文件，保留最新的一筆、
+//! 以其他文件補齊缺少的欄位，內容不同的舊版本存入版本紀錄，最後建立唯一索引。
+//!
+//! 日期仍為舊版字串格式的文件不會與 BSON 日期的文件視為重複，建議先執行 `migrate --apply`。
+
+use anyhow::Result;
+use bson::{doc, Bson, Document};
+use mongodb::options::{AggregateOptions, FindOneOptions};
+use mongodb::{Client as MongoClient, Collection};
+use std::cmp::Reverse;
+
+use crate::model::{Announcement, AnnouncementRevision};
+use crate::store::mongo::{identity_index, IDENTITY_INDEX_NAME, REVISIONS_COLLECTION};
+
+// 報告中保留的範例筆數
+const MAX_SAMPLES: usize = 10;
+
+/// 一組重複文件的合併計畫
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    /// 保留的文件 `_id`
+    pub keep: Bson,
+    /// 合併後取代保留文件的內容
+    pub merged: Document,
+    /// 要刪除的重複文件 `_id`
+    pub remove: Vec<Bson>,
+    /// 由重複文件補上的欄位
+    pub filled_fields: Vec<String>,
+    /// 內容不同的重複文件，保存為舊版本
+    pub revisions: Vec<AnnouncementRevision>,
+}
+
+/// 合併結果報告（試算與實際執行共用）
+#[derive(Debug, Default)]
+pub struct DedupeReport {
+    pub scanned: u64,
+    pub duplicate_groups: u64,
+    pub to_remove: u64,
+    pub removed: u64,
+    pub filled_fields: u64,
+    pub revisions: u64,
+    pub index_created: bool,
+    pub samples: Vec<String>,
+    pub write_errors: Vec<String>,
+}
+
+impl DedupeReport {
+    /// 輸出報告
+    pub fn print(&self, applied: bool) {
+        println!();
+        println!("{}", if applied { "📋 合併報告" } else { "📋 合併試算報告 (dry-run，未寫入任何資料)" });
+        println!("  掃描文件: {} 筆", self.scanned);
+        println!("  重複的公告: {} 則", self.duplicate_groups);
+        println!("  需要刪除的重複文件: {} 筆", self.to_remove);
+        if applied {
+            println!("  已刪除: {} 筆", self.removed);
+        }
+        println!("  補上的欄位: {} 個", self.filled_fields);
+        println!("  內容不同而保存為舊版本: {} 筆", self.revisions);
+        if applied {
+            println!("  唯一索引 {}: {}", IDENTITY_INDEX_NAME, if self.index_created { "已建立" } else { "未建立" });
+        }
+
+        if !self.samples.is_empty() {
+            println!();
+            println!("  合併範例:");
+            for sample in &self.samples {
+                println!("    {}", sample);
+            }
+        }
+        if !self.write_errors.is_empty() {
+            println!();
+            println!("  ❌ 寫入錯誤 {} 筆:", self.write_errors.len());
+            for error in self.write_errors.iter().take(MAX_SAMPLES) {
+                println!("    {}", error);
+            }
+        }
+    }
+}
+
+/// 計算一組重複文件的合併結果
+///
+/// 保留版本號最大、其次建立時間最新的文件，缺少或為 null 的欄位由其他文件依新到舊補上；
+/// `detail_content` 與保留文件不同的重複文件存為舊版本，版本號接在 `archived_max` 之後。
+pub fn plan_merge(documents: Vec<Document>, archived_max: u32) -> Result<MergePlan> {
+    let mut entries = documents
+        .into_iter()
+        .map(|document| {
+            let mut announcement: Announcement = bson::from_document(document.clone())?;
+            announcement.ensure_identity();
+            Ok((document, announcement))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by_key(|(document, announcement)| {
+        Reverse((announcement.revision, announcement.created_at, document.get_object_id("_id").ok()))
+    });
+
+    let mut entries = entries.into_iter();
+    let Some((mut merged, mut current)) = entries.next() else {
+        anyhow::bail!("沒有可合併的文件");
+    };
+    let keep = merged.get("_id").cloned().unwrap_or(Bson::Null);
+
+    let mut remove = Vec::new();
+    let mut filled_fields = Vec::new();
+    let mut superseded = Vec::new();
+    for (document, announcement) in entries {
+        for (field, value) in &document {
+            if field == "_id" || matches!(value, Bson::Null) {
+                continue;
+            }
+            if matches!(merged.get(field), None | Some(Bson::Null)) {
+                merged.insert(field.clone(), value.clone());
+                filled_fields.push(field.clone());
+            }
+        }
+        remove.push(document.get("_id").cloned().unwrap_or(Bson::Null));
+
+        if announcement.content_hash != current.content_hash
+            && !superseded.iter().any(|a: &Announcement| a.content_hash == announcement.content_hash)
+        {
+            superseded.push(announcement);
+        }
+    }
+
+    // 舊版本依建立時間由舊到新編號，保留的文件成為最新版本
+    superseded.sort_by_key(|a| a.created_at);
+    let mut next = archived_max.max(current.revision.unwrap_or(1).saturating_sub(1)) + 1;
+    let now = chrono::Utc::now();
+    let revisions: Vec<AnnouncementRevision> = superseded
+        .iter_mut()
+        .map(|announcement| {
+            announcement.announcement_id = current.announcement_id.clone();
+            announcement.revision = Some(next);
+            next += 1;
+            AnnouncementRevision::from_superseded(announcement, now)
+        })
+        .collect();
+
+    if !revisions.is_empty() {
+        current.revision = Some(next);
+    }
+    current.revision.get_or_insert(1);
+    merged.insert("announcement_id", current.announcement_id.clone());
+    merged.insert("content_hash", current.content_hash.clone());
+    merged.insert("revision", current.revision.map(i64::from));
+
+    Ok(MergePlan { keep, merged, remove, filled_fields, revisions })
+}
+
+// 已保存的最大版本號
+async fn archived_max(revisions: &Collection<AnnouncementRevision>, announcement_id: Option<&str>) -> Result<u32> {
+    let Some(announcement_id) = announcement_id else {
+        return Ok(0);
+    };
+    let options = FindOneOptions::builder().sort(doc! { "revision": -1 }).build();
+    let latest = revisions.find_one(doc! { "announcement_id": announcement_id }, options).await?;
+    Ok(latest.map_or(0, |r| r.revision))
+}
+
+/// 找出並合併重複公告；`apply` 為 true 時寫入並建立唯一索引，否則只產生試算報告
+pub async fn run_dedupe(
+    mongodb_uri: &str,
+    database_name: &str,
+    collection_name: &str,
+    apply: bool,
+) -> Result<DedupeReport> {
+    let client = MongoClient::with_uri_str(mongodb_uri).await?;
+    let database = client.database(database_name);
+    let collection: Collection<Document> = database.collection(collection_name);
+    let revisions: Collection<AnnouncementRevision> = database.collection(REVISIONS_COLLECTION);
+
+    let mut report = DedupeReport {
+        scanned: collection.count_documents(doc! {}, None).await?,
+        ..Default::default()
+    };
+
+    // 由伺服器依識別鍵分組，只取回重複的 _id
+    let pipeline = vec![
+        doc! { "$group": {
+            "_id": { "company_code": "$company_code", "date": "$date", "time": "$time", "title": "$title" },
+            "ids": { "$push": "$_id" },
+            "count": { "$sum": 1 },
+        } },
+        doc! { "$match": { "count": { "$gt": 1 } } },
+    ];
+    let options = AggregateOptions::builder().allow_disk_use(true).build();
+    let mut groups = Vec::new();
+    let mut cursor = collection.aggregate(pipeline, options).await?;
+    while cursor.advance().await? {
+        let group: Document = cursor.deserialize_current()?;
+        groups.push(group.get_array("ids").cloned().unwrap_or_default());
+    }
+
+    for ids in groups {
+        let mut documents = Vec::new();
+        let mut cursor = collection.find(doc! { "_id": { "$in": ids } }, None).await?;
+        while cursor.advance().await? {
+            documents.push(cursor.deserialize_current()?);
+        }
+
+        let announcement_id = documents
+            .first()
+            .and_then(|document| bson::from_document::<Announcement>(document.clone()).ok())
+            .map(|announcement| announcement.stable_id());
+        let archived = archived_max(&revisions, announcement_id.as_deref()).await?;
+        let plan = plan_merge(documents, archived)?;
+
+        report.duplicate_groups += 1;
+        report.to_remove += plan.remove.len() as u64;
+        report.filled_fields += plan.filled_fields.len() as u64;
+        report.revisions += plan.revisions.len() as u64;
+        if report.samples.len() < MAX_SAMPLES {
+            report.samples.push(format!(
+                "{} {} {} {}: 保留 {}，刪除 {} 筆{}",
+                plan.merged.get_str("company_code").unwrap_or(""),
+                plan.merged.get("date").map(|d| d.to_string()).unwrap_or_default(),
+                plan.merged.get_str("time").unwrap_or(""),
+                plan.merged.get_str("title").unwrap_or(""),
+                plan.keep,
+                plan.remove.len(),
+                if plan.filled_fields.is_empty() {
+                    String::new()
+                } else {
+                    format!("，補上 {}", plan.filled_fields.join(", "))
+                }
+            ));
+        }
+
+        if !apply {
+            continue;
+        }
+
+        // 先保存舊版本並更新保留的文件，最後才刪除重複文件
+        let result = async {
+            if !plan.revisions.is_empty() {
+                revisions.insert_many(&plan.revisions, None).await?;
+            }
+            collection.replace_one(doc! { "_id": plan.keep.clone() }, &plan.merged, None).await?;
+            let deleted = collection.delete_many(doc! { "_id": { "$in": plan.remove.clone() } }, None).await?;
+            anyhow::Ok(deleted.deleted_count)
+        }
+        .await;
+        match result {
+            Ok(deleted) => report.removed += deleted,
+            Err(e) => report.write_errors.push(format!("{}: {}", plan.keep, e)),
+        }
+    }
+
+    // 全部合併成功才建立唯一索引
+    if apply && report.write_errors.is_empty() {
+        match collection.create_index(identity_index(), None).await {
+            Ok(_) => report.index_created = true,
+            Err(e) => report.write_errors.push(format!("建立唯一索引失敗: {}", e)),
+        }
+    }
+
+    Ok(report)
+}
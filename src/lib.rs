@@ -0,0 +1,43 @@
+//! 台灣證交所（MOPS 公開資訊觀測站）重大訊息擷取函式庫。
+//!
+//! - [`archive`]：原始 HTML 回應的壓縮封存與擷取清單
+//! - [`backfill`]：逐日回補的進度檔
+//! - [`client`]：查詢 MOPS 的 HTTP 用戶端
+//! - [`date`]：民國／西元日期型別
+//! - [`dedupe`]：合併 MongoDB 既有的重複公告並建立唯一索引
+//! - [`migration`]：既有 MongoDB 資料的日期欄位正規化
+//! - [`mock`]：本機模擬的 MOPS 伺服器 (端對端測試)
+//! - [`model`]：重大訊息與條款代號資料結構
+//! - [`parser`]：解析 MOPS 回應的 HTML
+//! - [`search`]：公告標題與內容的全文檢索
+//! - [`storage`]：本機檔案輸出
+//! - [`store`]：資料庫儲存介面與 MongoDB、SQLite 實作
+//! - [`watch`]：監看模式的新公告判斷
+//! - [`web`]：Web 查看器與 API
+//!
+//! ```no_run
+//! use twse_announcements::client::TwseClient;
+//! use twse_announcements::date::RocDate;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let client = TwseClient::new();
+//! let (announcements, _pages) = client.fetch_announcements(RocDate::from_ymd(2025, 8, 15).unwrap(), "all").await?;
+//! println!("{} 筆重大訊息", announcements.len());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod archive;
+pub mod backfill;
+pub mod client;
+pub mod date;
+pub mod dedupe;
+pub mod migration;
+pub mod mock;
+pub mod model;
+pub mod parser;
+pub mod search;
+pub mod storage;
+pub mod store;
+pub mod watch;
+pub mod web;
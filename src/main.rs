@@ -1,30 +1,43 @@
 use anyhow::Result;
-use bson::{doc, Document};
-use chrono::{Datelike, Local, NaiveDate};
-use clap::Parser;
-use mongodb::{Client as MongoClient, Collection};
-use reqwest::Client;
-use scraper::{Html, Selector};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
-
-// pub mod web_viewer; // 暫時禁用，因為模板語法問題
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use clap::{Parser, Subcommand};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use twse_announcements::archive::{gridfs_bucket, ArchiveEntry, ArchiveFilter, ArchiveKind, HtmlArchive, DEFAULT_ARCHIVE_DIR};
+use twse_announcements::backfill::BackfillCheckpoint;
+use twse_announcements::client::{parse_market, tag_market, RetriesExhausted, TwseClient, TwseClientConfig, DEFAULT_BASE_URL};
+use twse_announcements::date::{taipei_offset, RocDate};
+use twse_announcements::dedupe::run_dedupe;
+use twse_announcements::migration::run_migration;
+use twse_announcements::model::{Announcement, AnnouncementKey};
+use twse_announcements::parser::{self, LayoutChangedError};
+use twse_announcements::storage::{save_html, save_json, save_txt};
+use twse_announcements::store::{open_store, AnnouncementStore, DuplicateMode, ReplaceScope};
+use twse_announcements::watch::SeenAnnouncements;
 
 #[derive(Parser)]
 #[command(name = "twse-announcements")]
 #[command(about = "查詢台灣證交所重大訊息")]
 struct Args {
-    /// 查詢日期 (格式: YYYY-MM-DD)，預設為今日
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// 查詢日期 (格式: YYYY-MM-DD 或民國 114/08/15)，預設為今日
     #[arg(short, long)]
-    date: Option<String>,
+    date: Option<RocDate>,
     
     /// 公司代號
-    #[arg(short, long)]
+    #[arg(global = true, short, long)]
     company: Option<String>,
     
     /// 輸出格式 (json, table, html, txt)
-    #[arg(short, long, default_value = "table")]
+    #[arg(global = true, short, long, default_value = "table")]
     format: String,
 
     /// 儲存原始 HTML 回應到檔案（已自動啟用，此參數保留向後相容）
@@ -32,382 +45,197 @@ struct Args {
     save_html: bool,
 
     /// 輸出檔案前綴名稱
-    #[arg(short, long, default_value = "twse_announcements")]
+    #[arg(global = true, short, long, default_value = "twse_announcements")]
     output: String,
 
-    /// 儲存到 MongoDB
-    #[arg(long)]
+    /// 儲存到 MongoDB（指定 --store 時改存到該位置）
+    #[arg(global = true, long)]
     save_mongodb: bool,
 
+    /// 儲存連線字串 (mongodb://...、sqlite:///path.db 或 memory://[path.json])，指定後即啟用儲存，未指定時使用 --mongodb-uri
+    #[arg(global = true, long)]
+    store: Option<String>,
+
     /// MongoDB 連接字串
-    #[arg(long, default_value = "mongodb://localhost:27017")]
+    #[arg(global = true, long, default_value = "mongodb://localhost:27017")]
     mongodb_uri: String,
 
     /// MongoDB 資料庫名稱
-    #[arg(long, default_value = "twse_db")]
+    #[arg(global = true, long, default_value = "twse_db")]
     mongodb_database: String,
 
     /// MongoDB 集合名稱
-    #[arg(long, default_value = "announcements")]
+    #[arg(global = true, long, default_value = "announcements")]
     mongodb_collection: String,
 
     /// 防重複模式：upsert(預設), replace, skip
-    #[arg(long, default_value = "upsert")]
-    duplicate_mode: String,
-}
+    #[arg(global = true, long, default_value = "upsert")]
+    duplicate_mode: DuplicateMode,
 
-#[derive(Debug, Deserialize)]
-struct AnnouncementResponse {
-    #[serde(flatten)]
-    data: HashMap<String, serde_json::Value>,
-}
+    /// 市場別：all(預設), sii/listed(上市), otc(上櫃), rotc/emerging(興櫃), pub/public(公開發行)
+    #[arg(short, long, default_value = "all")]
+    market: String,
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Announcement {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
-    id: Option<bson::oid::ObjectId>,
-    company_code: String,
-    company_name: String,
-    title: String,
-    date: String,
-    time: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    detail_content: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    announcement_type: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    fact_date: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    fact_occurrence_date: Option<String>, // 新增事實發生日欄位 (從 h07 擷取)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    clause_code: Option<String>, // 條款代號欄位
-    #[serde(skip_serializing_if = "Option::is_none")]
-    raw_html: Option<String>, // 新增原始 HTML 資料欄位
-    #[serde(skip_serializing_if = "Option::is_none")]
-    created_at: Option<chrono::DateTime<chrono::Utc>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    query_date: Option<String>,
-}
+    /// 回補起始日期 (格式: YYYY-MM-DD 或民國 114/08/15)，與 --end-date 搭配逐日查詢
+    #[arg(long)]
+    start_date: Option<RocDate>,
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct ClauseCode {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
-    id: Option<bson::oid::ObjectId>,
-    code: String,
-    description: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
+    /// 回補結束日期 (格式: YYYY-MM-DD 或民國 114/08/15)，預設為今日
+    #[arg(long)]
+    end_date: Option<RocDate>,
 
-struct TwseClient {
-    client: Client,
-}
+    /// 休市日清單檔案（每行一個 YYYY-MM-DD，# 開頭為註解），回補與監看時跳過
+    #[arg(global = true, long)]
+    holidays_file: Option<String>,
 
-impl TwseClient {
-    fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/139.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
-        
-        Self { client }
-    }
-
-    async fn fetch_announcements(&self, year: u32, month: u32, day: u32) -> Result<(Vec<Announcement>, String)> {
-        let url = "https://mopsov.twse.com.tw/mops/web/ajax_t05st02";
-
-        // 創建字串變數以避免生命週期問題
-        let year_str = (year - 1911).to_string(); // 民國年
-        let month_str = format!("{:02}", month);
-        let day_str = format!("{:02}", day);
-
-        let mut form_data = HashMap::new();
-        form_data.insert("encodeURIComponent", "1");
-        form_data.insert("step", "1");
-        form_data.insert("step00", "0");
-        form_data.insert("firstin", "1");
-        form_data.insert("off", "1");
-        form_data.insert("TYPEK", "all");
-        form_data.insert("year", year_str.as_str());
-        form_data.insert("month", month_str.as_str());
-        form_data.insert("day", day_str.as_str());
-
-        // println!("發送請求到: {}", url);
-        // println!("表單資料: {:?}", form_data);
-
-        let response = self.client
-            .post(url)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .header("Origin", "https://mopsov.twse.com.tw")
-            .header("Referer", "https://mopsov.twse.com.tw/mops/web/t05st02")
-            .form(&form_data)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("HTTP request failed: {}", response.status());
-        }
+    /// 回補與監看時不跳過週六、週日（補行上班日使用）
+    #[arg(global = true, long)]
+    include_weekends: bool,
 
-        let text = response.text().await?;
-
-        // 解析回應並轉換為結構化資料
-        let announcements = self.parse_response(&text)?;
-        Ok((announcements, text))
-    }
-
-    fn parse_response(&self, html: &str) -> Result<Vec<Announcement>> {
-        let document = Html::parse_document(html);
-        let mut announcements = Vec::new();
-
-        // 嘗試解析表格資料 - 尋找包含 tblHead 的表格
-        let table_selector = Selector::parse("table").unwrap();
-        let row_selector = Selector::parse("tr").unwrap();
-        let cell_selector = Selector::parse("td").unwrap();
-        let header_selector = Selector::parse("th.tblHead").unwrap();
-        let input_selector = Selector::parse("input[type='hidden']").unwrap();
-
-        for table in document.select(&table_selector) {
-            // 檢查是否包含表頭
-            if table.select(&header_selector).next().is_some() {
-                // 這是資料表格，解析每一行
-                for row in table.select(&row_selector) {
-                    let cells: Vec<String> = row
-                        .select(&cell_selector)
-                        .map(|cell| cell.text().collect::<String>().trim().to_string())
-                        .collect();
-
-                    // 跳過空行或只有少於5個欄位的行
-                    if cells.len() >= 5 && !cells[0].is_empty() {
-                        let date = cells[0].trim().to_string();
-                        let time = cells[1].trim().to_string();
-                        let company_code = cells[2].trim().to_string();
-                        let company_name = cells[3].trim().to_string();
-                        let title = cells[4].trim().to_string();
-
-                        // 提取詳細資料
-                        let (detail_content, announcement_type, fact_date, clause_code, fact_occurrence_date, raw_html) = self.extract_detail_info(&row, &input_selector);
-
-                        // 過濾掉無效的資料
-                        if !date.is_empty() && !company_code.is_empty() && !title.is_empty() {
-                            // 移除標題中的換行符號，用空格取代
-                            let clean_title = title.replace('\n', " ").replace('\r', " ");
-
-                            let announcement = Announcement {
-                                id: None,
-                                company_code,
-                                company_name,
-                                title: clean_title,
-                                date,
-                                time,
-                                detail_content,
-                                announcement_type,
-                                fact_date,
-                                fact_occurrence_date,
-                                clause_code,
-                                raw_html: Some(raw_html),
-                                created_at: Some(chrono::Utc::now()),
-                                query_date: None, // 將在 main 函數中設定
-                            };
-                            announcements.push(announcement);
-                        }
-                    }
-                }
-                break; // 找到資料表格後就停止
-            }
-        }
+    /// 回補單日處理失敗 (例如寫入資料庫失敗) 時的最大重試次數；
+    /// HTTP 請求已在 --http-retries 內重試，用盡後不再以整日重試
+    #[arg(long, default_value = "3")]
+    max_retries: u32,
 
-        // 如果沒有找到表格資料，檢查是否有"沒有找到重大訊息"的訊息
-        if announcements.is_empty() {
-            // 檢查完整的 HTML 內容
-            let html_lower = html.to_lowercase();
-            if html.contains("沒有找到重大訊息") || html.contains("無重大訊息") ||
-               html.contains("沒有找到") || html_lower.contains("no data") {
-                println!("今日無重大訊息公告");
-            } else {
-                println!("未找到表格資料，可能的原因：");
-                println!("1. 今日確實無重大訊息");
-                println!("2. 網站結構已變更");
-                println!("3. 請求參數需要調整");
-                println!("\n如需除錯，請檢查原始回應內容。");
-            }
-        }
+    /// 回補進度檔路徑，預設為 <output>_backfill_checkpoint.json
+    #[arg(long)]
+    checkpoint_file: Option<String>,
 
-        Ok(announcements)
-    }
-
-    fn extract_detail_info(&self, row: &scraper::ElementRef, input_selector: &Selector) -> (Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, String) {
-        let mut detail_content = None;
-        let mut announcement_type = None;
-        let mut fact_date = None;
-        let mut clause_code = None;
-        let mut fact_occurrence_date = None;
-
-        // 保存原始 HTML
-        let raw_html = row.html();
-
-        // 在這一行中尋找隱藏的 input 欄位
-        for input in row.select(input_selector) {
-            if let Some(name) = input.value().attr("name") {
-                if let Some(value) = input.value().attr("value") {
-                    println!("🔍 發現 input 欄位: name='{}', value='{}'", name, value);
-
-                    match name {
-                        name if name.ends_with("6") => { // h06, h16, h26 等包含條款代號
-                            if !value.trim().is_empty() {
-                                clause_code = Some(value.trim().to_string());
-                                println!("📋 擷取條款代號: {} = {}", name, value);
-                            }
-                        }
-                        name if name.ends_with("7") => { // h07, h17, h27 等包含事實發生日
-                            if !value.trim().is_empty() {
-                                // 將 YYYYMMDD 格式轉換為 YYYY-MM-DD 格式
-                                let formatted_date = format_fact_occurrence_date(value);
-                                fact_occurrence_date = Some(formatted_date.clone());
-                                println!("📅 擷取事實發生日: {} = {} -> {}", name, value, formatted_date);
-                            }
-                        }
-                        name if name.ends_with("8") => { // h08, h18, h28 等包含詳細內容
-                            if !value.trim().is_empty() {
-                                detail_content = Some(value.trim().to_string());
-
-                                // 嘗試提取事實發生日（從詳細內容中）
-                                if let Some(fact_line) = value.lines().find(|line| line.contains("事實發生日")) {
-                                    if let Some(date_part) = fact_line.split('：').nth(1) {
-                                        fact_date = Some(date_part.trim().to_string());
-                                    }
-                                }
-
-                                // 嘗試提取公告類型
-                                if let Some(type_line) = value.lines().find(|line| line.contains("符合條款")) {
-                                    announcement_type = Some(type_line.trim().to_string());
-                                }
-                            }
-                        }
-                        _ => {
-                            // 記錄所有其他的 input 欄位以供調試
-                            if !value.trim().is_empty() {
-                                println!("📝 其他欄位: {} = {}", name, value);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    /// MOPS 查詢網址前綴，可指向本機模擬伺服器 (mock_mops_server)
+    #[arg(global = true, long, default_value = DEFAULT_BASE_URL)]
+    mops_base_url: String,
 
-        (detail_content, announcement_type, fact_date, clause_code, fact_occurrence_date, raw_html)
-    }
+    /// HTTP 請求逾時秒數
+    #[arg(long, default_value = "30")]
+    timeout_secs: u64,
 
-    fn parse_text_content(&self, content: &str) -> Result<Vec<Announcement>> {
-        let mut announcements = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    /// HTTP 請求遇到連線錯誤、5xx 或限流時的重試次數
+    #[arg(long, default_value = "3")]
+    http_retries: u32,
 
-        // 找到資料開始的行（跳過標題行）
-        let mut data_started = false;
+    /// 每分鐘最多發送的請求數，0 表示不限制
+    #[arg(long, default_value = "20")]
+    requests_per_minute: u32,
 
-        for line in lines {
-            let line = line.trim();
+    /// 遇到「查詢過於頻繁」限流頁面時的冷卻秒數
+    #[arg(long, default_value = "90")]
+    throttle_cooldown_secs: u64,
 
-            // 跳過空行和標題行
-            if line.is_empty() || line.contains("公司當日重大訊息之詳細內容") ||
-               line.contains("代號") || line.contains("----") {
-                if line.contains("----") {
-                    data_started = true;
-                }
-                continue;
-            }
+    /// 逐筆查詢明細頁，擷取發言人、更正註記與說明項目，並保存明細頁原始 HTML
+    #[arg(global = true, long)]
+    with_details: bool,
 
-            if !data_started {
-                continue;
-            }
+    /// 明細頁查詢的併發數
+    #[arg(global = true, long, default_value = "2")]
+    detail_concurrency: usize,
 
-            // 解析資料行
-            // 格式看起來是: 日期 時間 公司名稱 標題 代號
-            if let Some(announcement) = self.parse_announcement_line(line) {
-                announcements.push(announcement);
-            }
-        }
+    /// 原始回應封存目錄 (預設 archive)；每次擷取都以壓縮、內容定址方式保存並記錄於 manifest.jsonl
+    #[arg(global = true, long)]
+    archive_dir: Option<String>,
 
-        Ok(announcements)
-    }
-
-    fn parse_announcement_line(&self, line: &str) -> Option<Announcement> {
-        // 使用正則表達式或字串分割來解析行
-        // 格式: 114/08/17 07:00:03 世界健身-KY 公告本公司名稱... 2762
-
-        // 先嘗試找到日期模式 (114/08/17)
-        if let Some(date_end) = line.find(' ') {
-            let date = &line[..date_end];
-            let rest = &line[date_end..].trim_start();
-
-            // 找到時間模式 (07:00:03)
-            if let Some(time_end) = rest.find(' ') {
-                let time = &rest[..time_end];
-                let rest = &rest[time_end..].trim_start();
-
-                // 找到最後的數字（公司代號）
-                if let Some(last_space) = rest.rfind(' ') {
-                    let company_code = &rest[last_space..].trim();
-                    let content = &rest[..last_space].trim();
-
-                    // 找到公司名稱和標題的分界點
-                    // 假設公司名稱不會太長，通常在前20個字元內
-                    let mut company_name = String::new();
-                    let mut title = String::new();
-
-                    // 簡單的分割邏輯：找到第一個中文標點或特定關鍵字
-                    if let Some(split_pos) = content.find("公告") {
-                        if split_pos > 0 {
-                            company_name = content[..split_pos].trim().to_string();
-                            title = content[split_pos..].trim().to_string();
-                        } else {
-                            // 如果找不到合適的分割點，使用前面的部分作為公司名稱
-                            let words: Vec<&str> = content.split_whitespace().collect();
-                            if !words.is_empty() {
-                                company_name = words[0].to_string();
-                                title = words[1..].join(" ");
-                            }
-                        }
-                    } else {
-                        // 備用分割邏輯
-                        let words: Vec<&str> = content.split_whitespace().collect();
-                        if !words.is_empty() {
-                            company_name = words[0].to_string();
-                            title = words[1..].join(" ");
-                        }
-                    }
+    /// 將壓縮後較大的回應存於 MongoDB GridFS (使用 --mongodb-uri 與 --mongodb-database)
+    #[arg(global = true, long)]
+    archive_gridfs: bool,
 
-                    return Some(Announcement {
-                        id: None,
-                        company_code: company_code.to_string(),
-                        company_name,
-                        title,
-                        date: date.to_string(),
-                        time: time.to_string(),
-                        detail_content: None,
-                        announcement_type: None,
-                        fact_date: None,
-                        fact_occurrence_date: None,
-                        clause_code: None,
-                        raw_html: None,
-                        created_at: Some(chrono::Utc::now()),
-                        query_date: None,
-                    });
-                }
-            }
-        }
+    /// 存入 GridFS 的壓縮後大小門檻 (KB)
+    #[arg(global = true, long, default_value = "256")]
+    archive_gridfs_min_kb: usize,
+}
 
-        None
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// 重新解析已存檔的原始 HTML（不連線 MOPS），重新輸出 JSON/TXT 或寫入 MongoDB
+    Reparse {
+        /// 原始 HTML 檔案或目錄（目錄會掃描其中的 *.html）
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// 查詢單一公司在日期區間內的歷史重大訊息
+    History {
+        /// 公司代號，例如 2330
+        code: String,
+
+        /// 起始日期 (格式: YYYY-MM-DD 或民國 114/01/01)
+        #[arg(long)]
+        from: RocDate,
+
+        /// 結束日期，預設為今日
+        #[arg(long)]
+        to: Option<RocDate>,
+    },
+    /// 盤中持續監看今日重大訊息，只輸出並保存新出現的公告
+    Watch {
+        /// 查詢間隔秒數
+        #[arg(long, default_value = "60")]
+        interval_secs: u64,
+
+        /// 每日開始監看時間 (HH:MM，台灣時間)
+        #[arg(long, default_value = "07:00", value_parser = parse_clock)]
+        start_time: NaiveTime,
+
+        /// 每日結束監看時間 (HH:MM，台灣時間)
+        #[arg(long, default_value = "22:00", value_parser = parse_clock)]
+        end_time: NaiveTime,
+    },
+    /// 將 MongoDB 既有資料的日期欄位轉為 BSON 日期並補上 announced_at（預設僅試算）
+    Migrate {
+        /// 實際寫入轉換結果；未指定時只輸出試算報告
+        #[arg(long)]
+        apply: bool,
+
+        /// 每批掃描的文件數
+        #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+        batch_size: u32,
+    },
+    /// 合併 MongoDB 既有的重複公告並建立識別鍵唯一索引（預設僅試算）
+    Dedupe {
+        /// 實際合併並建立唯一索引；未指定時只輸出試算報告
+        #[arg(long)]
+        apply: bool,
+    },
+    /// 列出或匯出封存的原始回應
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveAction,
+    },
 }
 
-fn parse_date(date_str: &str) -> Result<(u32, u32, u32)> {
-    let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")?;
-    Ok((date.year() as u32, date.month(), date.day()))
+#[derive(Subcommand)]
+enum ArchiveAction {
+    /// 列出擷取紀錄（--format json 輸出 JSON）
+    List {
+        #[command(flatten)]
+        query: ArchiveQuery,
+    },
+    /// 解壓匯出為 HTML 檔，單日列表的檔名可直接交給 reparse 重新解析
+    Export {
+        #[command(flatten)]
+        query: ArchiveQuery,
+
+        /// 匯出目錄
+        #[arg(long, default_value = "archive_export")]
+        out: String,
+
+        /// 同一頁面 (類型、日期、市場別、公司) 只匯出最後一次成功的擷取
+        #[arg(long)]
+        latest: bool,
+    },
 }
 
-fn get_today() -> (u32, u32, u32) {
-    let today = Local::now().date_naive();
-    (today.year() as u32, today.month(), today.day())
+/// 封存紀錄的篩選條件，公司代號使用 --company
+#[derive(clap::Args)]
+struct ArchiveQuery {
+    /// 頁面類型：list(單日列表), history(公司歷史), detail(明細頁)
+    #[arg(long)]
+    kind: Option<ArchiveKind>,
+
+    /// 查詢日期 (格式: YYYY-MM-DD 或民國 114/08/15)
+    #[arg(long)]
+    date: Option<RocDate>,
+
+    /// 市場別 (all, sii, otc, rotc, pub)
+    #[arg(long)]
+    market: Option<String>,
 }
 
 fn print_table(announcements: &[Announcement]) {
@@ -416,410 +244,871 @@ fn print_table(announcements: &[Announcement]) {
         return;
     }
 
-    println!("{:<8} {:<20} {:<10} {:<8} {}",
-             "代號", "公司名稱", "日期", "時間", "標題");
+    println!("{:<8} {:<20} {:<10} {:<8} 標題",
+             "代號", "公司名稱", "日期", "時間");
     println!("{}", "-".repeat(80));
 
     for announcement in announcements {
         // 移除標題中的換行符號，用空格取代
-        let clean_title = announcement.title.replace('\n', " ").replace('\r', " ");
+        let clean_title = announcement.title.replace(['\n', '\r'], " ");
         println!("{:<8} {:<20} {:<10} {:<8} {}",
                  announcement.company_code,
                  announcement.company_name,
-                 announcement.date,
+                 announcement.date.to_roc_string(),
                  announcement.time,
                  clean_title);
     }
 }
 
-fn save_html(html_content: &str, filename: &str) -> Result<()> {
-    fs::write(format!("{}.html", filename), html_content)?;
-    println!("✅ 原始 HTML 檔案已自動儲存: {}.html", filename);
-    Ok(())
+// 讀取休市日清單檔案
+fn load_holidays(path: &str) -> Result<HashSet<RocDate>> {
+    let content = fs::read_to_string(path)?;
+    let mut holidays = HashSet::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // 允許行尾附註，例如 "2025-10-10 國慶日"
+        let date_part = line.split_whitespace().next().unwrap_or(line);
+        let date: RocDate = date_part.parse()
+            .map_err(|e| anyhow::anyhow!("休市日清單格式錯誤 '{}': {}", line, e))?;
+        holidays.insert(date);
+    }
+
+    Ok(holidays)
 }
 
-fn save_json(announcements: &[Announcement], filename: &str) -> Result<()> {
-    // 創建不包含 raw_html 的版本以減少檔案大小
-    let announcements_for_json: Vec<_> = announcements.iter().map(|announcement| {
-        let mut json_announcement = announcement.clone();
-        json_announcement.raw_html = None; // 移除 raw_html 欄位
-        json_announcement
-    }).collect();
-
-    let json_content = serde_json::to_string_pretty(&announcements_for_json)?;
-    fs::write(format!("{}.json", filename), json_content)?;
-    println!("JSON 檔案已儲存: {}.json (不包含原始HTML以減少檔案大小)", filename);
-    Ok(())
+// 依 --save-mongodb / --store 開啟儲存後端，並建立索引、初始化條款代號對照表；未要求儲存時回傳 None
+async fn prepare_store(args: &Args) -> Result<Option<Arc<dyn AnnouncementStore>>> {
+    if !args.save_mongodb && args.store.is_none() {
+        return Ok(None);
+    }
+
+    let uri = args.store.as_deref().unwrap_or(&args.mongodb_uri);
+    println!("正在連接資料庫: {}", uri);
+    let store = open_store(uri, &args.mongodb_database, &args.mongodb_collection).await?;
+
+    if let Err(e) = store.prepare().await {
+        println!("警告：初始化 {} 失敗: {}", store.backend(), e);
+    }
+
+    Ok(Some(store))
 }
 
-fn save_txt(announcements: &[Announcement], filename: &str) -> Result<()> {
-    let mut content = String::new();
-    content.push_str("台灣證交所重大訊息\n");
-    content.push_str("==================\n\n");
+// 查詢單日重大訊息並依參數輸出、儲存
+async fn process_date(client: &TwseClient, args: &Args, store: Option<&dyn AnnouncementStore>, date: RocDate) -> Result<()> {
+    println!("查詢日期: {} (民國 {})", date, date.to_roc_string());
 
+    let market = parse_market(&args.market)?;
+    let (mut announcements, pages) = client.fetch_announcements(date, market).await?;
+
+    // 生成檔案名稱（包含日期）
+    // 指定市場別時加入檔名，避免不同市場的查詢結果互相覆蓋
+    let filename = if market == "all" {
+        format!("{}_{}", args.output, date.to_compact_string())
+    } else {
+        format!("{}_{}_{}", args.output, market, date.to_compact_string())
+    };
+
+    // 自動儲存原始 HTML（每次查詢都會保存），每個市場一個檔案，重新解析時可由檔名判斷市場別
+    for (page_market, html) in &pages {
+        save_html(html, &format!("{}_{}_{}", args.output, page_market, date.to_compact_string()))?;
+    }
+    let html_content = pages.into_iter().map(|(_, html)| html).collect::<Vec<_>>().join("\n");
+
+    if args.with_details {
+        fetch_announcement_details(client, args, &mut announcements, &filename).await?;
+    }
+
+    set_query_date(&mut announcements, date);
+    output_announcements(args, store, announcements, &html_content, &filename, ReplaceScope::day(date, market)).await
+}
+
+// 單日查詢的結果以查詢日期標記
+fn set_query_date(announcements: &mut [Announcement], date: RocDate) {
+    for announcement in announcements {
+        announcement.query_date = Some(date);
+    }
+}
+
+// 查詢每筆公告的明細頁，原始 HTML 存於 <filename>_details/ 目錄
+async fn fetch_announcement_details(
+    client: &TwseClient,
+    args: &Args,
+    announcements: &mut Vec<Announcement>,
+    filename: &str,
+) -> Result<()> {
+    // 指定公司代號時先篩選，避免查詢不需要的明細頁
+    if let Some(company_code) = &args.company {
+        announcements.retain(|a| a.company_code.contains(company_code.as_str()));
+    }
     if announcements.is_empty() {
-        content.push_str("沒有找到重大訊息\n");
+        return Ok(());
+    }
+
+    let detail_dir = format!("{}_details", filename);
+    fs::create_dir_all(&detail_dir)?;
+
+    println!("🔎 查詢 {} 筆明細頁 (併發數 {})...", announcements.len(), args.detail_concurrency);
+    let outcomes = client.fetch_details(announcements, args.detail_concurrency).await;
+
+    let mut succeeded = 0;
+    let mut failed = 0;
+    for (index, outcome) in outcomes {
+        let announcement = &announcements[index];
+        match outcome {
+            Ok(html) => {
+                let time: String = announcement.time.chars().filter(|c| c.is_ascii_digit()).collect();
+                let detail_file = format!("{}/{}_{}_{}", detail_dir, announcement.company_code, time,
+                                          announcement.sequence_number.as_deref().unwrap_or("0"));
+                save_html(&html, &detail_file)?;
+                succeeded += 1;
+            }
+            Err(e) => {
+                println!("⚠️  {} {} 明細查詢失敗: {}", announcement.company_code, announcement.title, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("✅ 明細查詢完成: 成功 {} 筆，失敗 {} 筆", succeeded, failed);
+    Ok(())
+}
+
+// 依公司代號篩選後寫入資料庫並輸出指定格式，線上查詢與離線重新解析共用
+async fn output_announcements(
+    args: &Args,
+    store: Option<&dyn AnnouncementStore>,
+    announcements: Vec<Announcement>,
+    html_content: &str,
+    filename: &str,
+    replace_scope: ReplaceScope,
+) -> Result<()> {
+    let filtered_announcements: Vec<Announcement> = if let Some(company_code) = &args.company {
+        announcements.into_iter()
+            .filter(|a| a.company_code.contains(company_code.as_str()))
+            .collect()
     } else {
-        content.push_str(&format!("{:<8} {:<20} {:<10} {:<8} {}\n",
-                                 "代號", "公司名稱", "日期", "時間", "標題"));
-        content.push_str(&format!("{}\n", "-".repeat(80)));
-
-        for announcement in announcements {
-            // 移除標題中的換行符號，用空格取代
-            let clean_title = announcement.title.replace('\n', " ").replace('\r', " ");
-            content.push_str(&format!("{:<8} {:<20} {:<10} {:<8} {}\n",
-                                     announcement.company_code,
-                                     announcement.company_name,
-                                     announcement.date,
-                                     announcement.time,
-                                     clean_title));
+        announcements
+    };
+
+    // 儲存到資料庫（如果要求）
+    if let Some(store) = store {
+        save_to_store(store, &filtered_announcements, &replace_scope, args.duplicate_mode).await?;
+    }
+
+    // 根據格式輸出或儲存
+    match args.format.as_str() {
+        "json" => {
+            let json_output = serde_json::to_string_pretty(&filtered_announcements)?;
+            println!("{}", json_output);
+            save_json(&filtered_announcements, filename)?;
+        }
+        "table" => {
+            print_table(&filtered_announcements);
+            save_txt(&filtered_announcements, filename)?;
+        }
+        "html" => {
+            println!("原始 HTML 內容:");
+            println!("{}", html_content);
+            save_html(html_content, filename)?;
+        }
+        "txt" => {
+            save_txt(&filtered_announcements, filename)?;
+            println!("資料已儲存到 TXT 檔案");
+        }
+        _ => {
+            anyhow::bail!("不支援的輸出格式: {}。支援的格式: json, table, html, txt", args.format);
         }
     }
 
-    fs::write(format!("{}.txt", filename), content)?;
-    println!("TXT 檔案已儲存: {}.txt", filename);
     Ok(())
 }
 
-async fn save_to_mongodb(
+// 依防重複模式寫入資料庫並輸出結果
+async fn save_to_store(
+    store: &dyn AnnouncementStore,
     announcements: &[Announcement],
-    mongodb_uri: &str,
-    database_name: &str,
-    collection_name: &str,
-    query_date: &str,
-    duplicate_mode: &str
+    replace_scope: &ReplaceScope,
+    duplicate_mode: DuplicateMode,
 ) -> Result<()> {
-    // 連接到 MongoDB
-    let client = MongoClient::with_uri_str(mongodb_uri).await?;
-    let database = client.database(database_name);
-    let collection: Collection<Announcement> = database.collection(collection_name);
-
     if announcements.is_empty() {
-        println!("沒有資料需要儲存到 MongoDB");
+        println!("沒有資料需要儲存到 {}", store.backend());
         return Ok(());
     }
 
-    // 準備要插入的資料，加入查詢日期並移除 raw_html
-    let mut docs_to_insert = Vec::new();
-    for announcement in announcements {
-        let mut doc = announcement.clone();
-        doc.query_date = Some(query_date.to_string());
-        doc.id = None; // 讓 MongoDB 自動生成 ObjectId
-        doc.raw_html = None; // 移除 raw_html 欄位以減少資料庫大小
-        docs_to_insert.push(doc);
+    match duplicate_mode {
+        DuplicateMode::Upsert => println!("使用 Upsert 模式：自動更新重複資料..."),
+        DuplicateMode::Replace => println!("使用 Replace 模式：刪除舊資料後重新插入..."),
+        DuplicateMode::Skip => println!("使用 Skip 模式：跳過重複資料..."),
     }
+    let summary = store.save(announcements, replace_scope, duplicate_mode).await?;
+    summary.print(duplicate_mode);
+    Ok(())
+}
 
-    // 根據防重複模式處理資料
-    match duplicate_mode {
-        "upsert" => {
-            println!("使用 Upsert 模式：自動更新重複資料...");
-            let mut inserted_count = 0;
-            let mut updated_count = 0;
-
-            for doc in docs_to_insert {
-                // 建立唯一識別條件：公司代號 + 日期 + 時間 + 標題
-                let filter = doc! {
-                    "company_code": &doc.company_code,
-                    "date": &doc.date,
-                    "time": &doc.time,
-                    "title": &doc.title
-                };
-
-                // 使用 replace_one 進行 upsert
-                let options = mongodb::options::ReplaceOptions::builder()
-                    .upsert(true)
-                    .build();
-
-                let result = collection
-                    .replace_one(filter, &doc, options)
-                    .await?;
-
-                if result.upserted_id.is_some() {
-                    inserted_count += 1;
-                } else if result.modified_count > 0 {
-                    updated_count += 1;
+// 從存檔檔名 <prefix>[_<market>]_YYYYMMDD.html 推回查詢日期與市場別
+fn parse_archive_filename(path: &Path) -> Option<(RocDate, &'static str)> {
+    let file_name = path.file_name()?.to_str()?;
+    let re = Regex::new(r"(?:_(sii|otc|rotc|pub))?_(\d{8})\.html$").ok()?;
+    let caps = re.captures(file_name)?;
+    let date = RocDate::parse(caps.get(2)?.as_str())?;
+    let market = caps.get(1).map_or(Ok("all"), |m| parse_market(m.as_str())).ok()?;
+    Some((date, market))
+}
+
+// 展開重新解析的輸入路徑，目錄會掃描其中的 *.html 並依檔名排序
+fn collect_archive_files(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            let mut dir_files: Vec<PathBuf> = fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "html"))
+                .collect();
+            dir_files.sort();
+            files.extend(dir_files);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+// 離線重新解析已存檔的原始 HTML，不會連線 MOPS
+async fn run_reparse(args: &Args, paths: &[String]) -> Result<()> {
+    let files = collect_archive_files(paths)?;
+    if files.is_empty() {
+        anyhow::bail!("找不到任何可重新解析的 HTML 檔案");
+    }
+
+    println!("🔁 重新解析 {} 個原始 HTML 檔案", files.len());
+
+    let store = prepare_store(args).await?;
+
+    let mut parsed_files = 0;
+    let mut total_announcements = 0;
+    let mut failed_files = Vec::new();
+    let mut layout_changed = 0;
+
+    for file in &files {
+        let Some((date, market)) = parse_archive_filename(file) else {
+            println!("⚠️  無法從檔名判斷查詢日期，跳過: {}", file.display());
+            failed_files.push(file.display().to_string());
+            continue;
+        };
+
+        println!();
+        println!("📄 {} (查詢日期: {}, 市場別: {})", file.display(), date, market);
+
+        // 舊版 all 存檔無法判斷公告所屬市場，只輸出檔案，不寫入資料庫
+        let file_store = match market {
+            "all" if store.is_some() => {
+                println!("⚠️  {} 未標示市場別，不寫入資料庫；請以個別市場重新查詢", file.display());
+                None
+            }
+            _ => store.as_deref(),
+        };
+
+        let result = async {
+            let html_content = fs::read_to_string(file)?;
+            let outcome = parser::parse_response(&html_content);
+            outcome.print();
+            let mut announcements = outcome.into_announcements()?;
+            tag_market(&mut announcements, market);
+            let count = announcements.len();
+
+            // 輸出檔與原始 HTML 放在同一位置，檔名相同僅副檔名不同
+            let filename = file.with_extension("").to_string_lossy().into_owned();
+            set_query_date(&mut announcements, date);
+            output_announcements(args, file_store, announcements, &html_content, &filename, ReplaceScope::day(date, market)).await?;
+            anyhow::Ok(count)
+        }.await;
+
+        match result {
+            Ok(count) => {
+                parsed_files += 1;
+                total_announcements += count;
+            }
+            Err(e) => {
+                println!("❌ 重新解析失敗 {}: {}", file.display(), e);
+                if e.is::<LayoutChangedError>() {
+                    layout_changed += 1;
                 }
+                failed_files.push(file.display().to_string());
             }
+        }
+    }
 
-            println!("Upsert 操作完成 (不包含原始HTML以減少資料庫大小):");
-            println!("  新增: {} 筆資料", inserted_count);
-            println!("  更新: {} 筆資料", updated_count);
-            println!("  總計: {} 筆資料", inserted_count + updated_count);
-        },
+    println!();
+    println!("✅ 重新解析完成: {} 個檔案，共 {} 筆重大訊息", parsed_files, total_announcements);
+    if !failed_files.is_empty() {
+        println!("  失敗: {} 個檔案 ({})", failed_files.len(), failed_files.join(", "));
+    }
+    // 任何檔案失敗都以非零結束碼回報，排程工作才能發現
+    if layout_changed > 0 {
+        anyhow::bail!("{} 個檔案的頁面結構無法辨識，請檢查解析器是否需要更新", layout_changed);
+    }
+    if !failed_files.is_empty() {
+        anyhow::bail!("{} 個檔案重新解析失敗", failed_files.len());
+    }
+
+    Ok(())
+}
+
+// 查詢單一公司的歷史重大訊息，沿用單日查詢的輸出與資料庫防重複處理
+async fn run_history(client: &TwseClient, args: &Args, code: &str, from: RocDate, to: Option<RocDate>) -> Result<()> {
+    let to = to.unwrap_or_else(RocDate::today);
+    println!("🏢 查詢公司 {} 的歷史重大訊息: {} ~ {}", code, from, to);
+
+    let store = prepare_store(args).await?;
+
+    // 歷史頁不含市場別，以 --market 指定的市場別查詢並標記
+    let market = parse_market(&args.market)?;
+    if market == "all" {
+        println!("⚠️  未指定 --market，歷史資料無法標記市場別");
+    }
+    let (mut announcements, pages) = client.fetch_company_history(code, market, from, to).await?;
+
+    let filename = format!("{}_{}_{}_{}", args.output, code, from.to_compact_string(), to.to_compact_string());
+    for (roc_year, html) in &pages {
+        save_html(html, &format!("{}_{}", filename, roc_year))?;
+    }
 
-        "replace" => {
-            println!("使用 Replace 模式：刪除舊資料後重新插入...");
+    // 歷史查詢沒有單一查詢日期，以各筆的發言日期為查詢日期
+    for announcement in &mut announcements {
+        announcement.query_date = Some(announcement.date);
+    }
+
+    if args.with_details {
+        fetch_announcement_details(client, args, &mut announcements, &filename).await?;
+    }
+
+    println!("共 {} 筆重大訊息", announcements.len());
+    let html_content = pages.into_iter().map(|(_, html)| html).collect::<Vec<_>>().join("\n");
+    output_announcements(args, store.as_deref(), announcements, &html_content, &filename,
+                         ReplaceScope::company_range(code, from, to)).await
+}
+
+// 解析 HH:MM 格式的時間
+fn parse_clock(value: &str) -> std::result::Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|e| format!("時間格式應為 HH:MM: {}", e))
+}
+
+// 台灣時間的現在時刻
+fn taipei_now() -> chrono::DateTime<FixedOffset> {
+    Utc::now().with_timezone(&taipei_offset())
+}
+
+// 從現在到指定日期時刻 (台灣時間) 的等待時間
+fn duration_until(now: chrono::DateTime<FixedOffset>, date: NaiveDate, time: NaiveTime) -> Duration {
+    taipei_offset()
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .and_then(|target| (target - now).to_std().ok())
+        .unwrap_or(Duration::ZERO)
+}
+
+// 盤中監看：定時查詢今日列表，與已知公告比對後只輸出並保存新公告
+async fn run_watch(
+    client: &TwseClient,
+    args: &Args,
+    interval_secs: u64,
+    start_time: NaiveTime,
+    end_time: NaiveTime,
+) -> Result<()> {
+    if start_time >= end_time {
+        anyhow::bail!("開始時間 {} 必須早於結束時間 {}", start_time, end_time);
+    }
+
+    let market = parse_market(&args.market)?;
+    let holidays = match &args.holidays_file {
+        Some(path) => load_holidays(path)?,
+        None => HashSet::new(),
+    };
+
+    // 監看只寫入新公告，replace 模式會刪除當日其他資料，不適用
+    let duplicate_mode = if args.duplicate_mode == DuplicateMode::Replace {
+        println!("⚠️  監看模式只寫入新公告，replace 模式改用 upsert");
+        DuplicateMode::Upsert
+    } else {
+        args.duplicate_mode
+    };
+
+    let store = prepare_store(args).await?;
+
+    println!("👀 監看模式: 每 {} 秒查詢一次，時段 {} ~ {} (台灣時間)，按 Ctrl+C 結束",
+             interval_secs, start_time.format("%H:%M"), end_time.format("%H:%M"));
 
-            // 檢查是否已存在相同日期的資料
-            let existing_count = collection
-                .count_documents(doc! { "query_date": query_date }, None)
-                .await?;
+    let mut seen = SeenAnnouncements::new();
+    let mut current_day: Option<RocDate> = None;
+    let mut total_new = 0;
 
-            if existing_count > 0 {
-                println!("發現 {} 筆相同日期的資料，刪除後重新插入", existing_count);
+    loop {
+        let now = taipei_now();
+        let today = RocDate::from(now.date_naive());
 
-                // 刪除相同日期的舊資料
-                let delete_result = collection
-                    .delete_many(doc! { "query_date": query_date }, None)
-                    .await?;
-                println!("已刪除 {} 筆舊資料", delete_result.deleted_count);
+        // 換日時重新載入今日已知的公告
+        if current_day != Some(today) {
+            current_day = Some(today);
+            seen = SeenAnnouncements::new();
+            if let Some(store) = &store {
+                match store.known_keys(today).await {
+                    Ok(keys) => {
+                        println!("📚 資料庫中已有 {} 的公告 {} 筆", today, keys.len());
+                        seen = SeenAnnouncements::from_keys(keys);
+                    }
+                    Err(e) => println!("警告：讀取既有公告失敗: {}", e),
+                }
             }
+        }
 
-            // 插入新資料
-            let insert_result = collection.insert_many(docs_to_insert, None).await?;
-            println!("成功插入 {} 筆新資料 (不包含原始HTML以減少資料庫大小)", insert_result.inserted_ids.len());
-        },
+        let is_weekend = matches!(today.naive_date().weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun);
+        let wait = if (is_weekend && !args.include_weekends) || holidays.contains(&today) || now.time() >= end_time {
+            let tomorrow = today.succ().expect("日期超出範圍").naive_date();
+            let wait = duration_until(now, tomorrow, start_time);
+            println!("⏸️  非監看時段，{} {} 恢復監看", tomorrow, start_time.format("%H:%M"));
+            wait
+        } else if now.time() < start_time {
+            println!("⏸️  尚未到監看時段，{} 開始監看", start_time.format("%H:%M"));
+            duration_until(now, today.naive_date(), start_time)
+        } else {
+            total_new += poll_new_announcements(client, args, store.as_deref(), today, market, duplicate_mode, &mut seen).await?;
+            Duration::from_secs(interval_secs)
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                println!("🛑 停止監看，本次共發現 {} 筆新公告", total_new);
+                return Ok(());
+            }
+        }
+    }
+}
+
+// 查詢一次今日列表，保存並輸出新公告，回傳新公告筆數；網站結構變更時回傳錯誤以結束監看
+async fn poll_new_announcements(
+    client: &TwseClient,
+    args: &Args,
+    store: Option<&dyn AnnouncementStore>,
+    today: RocDate,
+    market: &str,
+    duplicate_mode: DuplicateMode,
+    seen: &mut SeenAnnouncements,
+) -> Result<usize> {
+    let checked_at = taipei_now().format("%H:%M:%S");
+
+    let fetched = match client.fetch_announcements(today, market).await {
+        Ok((announcements, _)) => announcements,
+        Err(e) if e.is::<LayoutChangedError>() => return Err(e),
+        Err(e) => {
+            println!("⚠️  [{}] 查詢失敗: {}", checked_at, e);
+            return Ok(0);
+        }
+    };
 
-        "skip" => {
-            println!("使用 Skip 模式：跳過重複資料...");
-
-            let mut inserted_count = 0;
-            let mut skipped_count = 0;
-
-            for doc in docs_to_insert {
-                // 檢查是否已存在相同的資料
-                let filter = doc! {
-                    "company_code": &doc.company_code,
-                    "date": &doc.date,
-                    "time": &doc.time,
-                    "title": &doc.title
-                };
-
-                let existing = collection.find_one(filter, None).await?;
-
-                if existing.is_none() {
-                    // 不存在，插入新資料
-                    collection.insert_one(&doc, None).await?;
-                    inserted_count += 1;
-                } else {
-                    // 已存在，跳過
-                    skipped_count += 1;
+    // 新公告與內容更正的公告
+    let mut announcements = seen.diff(fetched);
+    if let Some(company_code) = &args.company {
+        announcements.retain(|a| a.company_code.contains(company_code.as_str()));
+    }
+    if announcements.is_empty() {
+        println!("[{}] 無新公告 (今日已知 {} 筆)", checked_at, seen.len());
+        return Ok(0);
+    }
+    set_query_date(&mut announcements, today);
+
+    // 先寫入資料庫，失敗時不標記為已知，下次查詢會再重試
+    if let Some(store) = store {
+        match store.save(&announcements, &ReplaceScope::day(today, market), duplicate_mode).await {
+            Ok(summary) => {
+                // 逐筆寫入失敗的公告不標記為已知
+                let failed: HashSet<AnnouncementKey> = summary.failures.into_iter().map(|f| f.key).collect();
+                if !failed.is_empty() {
+                    println!("⚠️  [{}] {} 筆公告寫入 {} 失敗，下次查詢時重試", checked_at, failed.len(), store.backend());
+                    announcements.retain(|a| !failed.contains(&a.key()));
+                    if announcements.is_empty() {
+                        return Ok(0);
+                    }
                 }
             }
+            Err(e) => {
+                println!("⚠️  [{}] 寫入 {} 失敗，下次查詢時重試: {}", checked_at, store.backend(), e);
+                return Ok(0);
+            }
+        }
+    }
 
-            println!("Skip 操作完成 (不包含原始HTML以減少資料庫大小):");
-            println!("  新增: {} 筆資料", inserted_count);
-            println!("  跳過: {} 筆重複資料", skipped_count);
-            println!("  總計處理: {} 筆資料", inserted_count + skipped_count);
-        },
+    if let Err(e) = emit_new_announcements(args, &announcements, today, market) {
+        println!("⚠️  寫入監看紀錄檔失敗: {}", e);
+    }
 
-        _ => {
-            return Err(anyhow::anyhow!("不支援的防重複模式: {}。支援的模式: upsert, replace, skip", duplicate_mode));
+    seen.mark(&announcements);
+    Ok(announcements.len())
+}
+
+// 輸出新公告：json 格式為每行一筆 (NDJSON)，其他格式為單行摘要；同時附加到當日的 .jsonl 紀錄檔
+fn emit_new_announcements(args: &Args, announcements: &[Announcement], today: RocDate, market: &str) -> Result<()> {
+    let log_path = if market == "all" {
+        format!("{}_watch_{}.jsonl", args.output, today.to_compact_string())
+    } else {
+        format!("{}_{}_watch_{}.jsonl", args.output, market, today.to_compact_string())
+    };
+    let mut log_file = OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    for announcement in announcements {
+        let mut record = announcement.clone();
+        record.raw_html = None;
+        let line = serde_json::to_string(&record)?;
+
+        if args.format == "json" {
+            println!("{}", line);
+        } else {
+            println!("🆕 {} {} {} {}", announcement.time, announcement.company_code,
+                     announcement.company_name, announcement.title);
         }
+        writeln!(log_file, "{}", line)?;
     }
-    println!("資料庫: {}, 集合: {}", database_name, collection_name);
 
     Ok(())
 }
 
-async fn setup_mongodb_indexes(
-    mongodb_uri: &str,
-    database_name: &str,
-    collection_name: &str
-) -> Result<()> {
-    let client = MongoClient::with_uri_str(mongodb_uri).await?;
-    let database = client.database(database_name);
-    let collection: Collection<Document> = database.collection(collection_name);
-
-    // 建立索引以提升查詢效能
-    let indexes = vec![
-        mongodb::IndexModel::builder()
-            .keys(doc! { "company_code": 1 })
-            .build(),
-        mongodb::IndexModel::builder()
-            .keys(doc! { "query_date": 1 })
-            .build(),
-        mongodb::IndexModel::builder()
-            .keys(doc! { "date": 1, "time": 1 })
-            .build(),
-        mongodb::IndexModel::builder()
-            .keys(doc! { "created_at": 1 })
-            .build(),
-    ];
-
-    collection.create_indexes(indexes, None).await?;
-    println!("MongoDB 索引建立完成");
+// 正規化 MongoDB 既有資料的日期欄位，預設只輸出試算報告
+async fn run_migrate(args: &Args, apply: bool, batch_size: u32) -> Result<()> {
+    if args.store.as_deref().is_some_and(|uri| uri.starts_with("sqlite://")) {
+        anyhow::bail!("migrate 只適用於 MongoDB，SQLite 的日期欄位已是正規格式");
+    }
+    let mongodb_uri = args.store.as_deref().unwrap_or(&args.mongodb_uri);
+
+    println!("🔧 {} {}.{} 的日期欄位", if apply { "遷移" } else { "試算遷移" },
+             args.mongodb_database, args.mongodb_collection);
+
+    let report = run_migration(
+        mongodb_uri,
+        &args.mongodb_database,
+        &args.mongodb_collection,
+        batch_size,
+        apply,
+    ).await?;
+    report.print(apply);
+
+    println!();
+    if !apply {
+        println!("ℹ️  確認報告無誤後，加上 --apply 執行轉換");
+    } else if report.write_errors.is_empty() {
+        println!("✅ 遷移完成，schema 版本已更新");
+    } else {
+        anyhow::bail!("遷移過程有 {} 筆寫入錯誤，未更新 schema 版本", report.write_errors.len());
+    }
 
     Ok(())
 }
 
-// 初始化條款代號對照表
-async fn initialize_clause_codes(collection: &mongodb::Collection<ClauseCode>) -> Result<()> {
-    println!("📋 正在初始化條款代號對照表...");
-
-    let clause_codes = vec![
-        ("1", "信用異常或股票交易異動"),
-        ("2", "涉訟或主管違法"),
-        ("3", "停工、減產、資產處理"),
-        ("4", "公司法重大決議"),
-        ("5", "重整或破產程序"),
-        ("6", "高層人事異動或席次不足"),
-        ("7", "更換會計師或承銷商"),
-        ("8", "重要主管異動"),
-        ("9", "會計年度或政策變更"),
-        ("10", "重大契約、合作或新產品量產"),
-        ("11", "資本變動或合併收購"),
-        ("12", "說明會或未申報資訊發布"),
-        ("13", "財務預測差異重大"),
-        ("14", "股利政策異動或延遲"),
-        ("15", "大額投資計畫"),
-        ("16", "增資或債券計畫變動"),
-        ("17", "股東會召開通知"),
-        ("18", "股東會重要決議"),
-        ("19", "舞弊、掏空或主管遭羈押"),
-        ("20", "資產交易或衍生損失重大"),
-        ("21", "經理人或董事競業行為"),
-        ("22", "背書保證達標準"),
-        ("23", "資金貸與達標準"),
-        ("24", "私募證券交易"),
-        ("25", "主要客戶或供應商終止往來"),
-        ("26", "災難、罷工、資安等重大事件"),
-        ("27", "與銀行協商結果確定"),
-        ("28", "關係人或債務人信用異常"),
-        ("29", "內控聲明或審查報告"),
-        ("30", "財報錯誤或遭保留意見"),
-        ("31", "財報提報或自結資訊異動"),
-        ("32", "股票集中保管不足"),
-        ("33", "股權變動通知"),
-        ("34", "董監事遭停止職權"),
-        ("35", "公司買回股份"),
-        ("36", "減資或面額異動作業"),
-        ("37", "上市承諾未履行"),
-        ("38", "公開收購申報或通知"),
-        ("40", "暫停或恢復交易"),
-        ("41", "控股公司持股變動"),
-        ("42", "終止上市或改列申請"),
-        ("43", "重大捐贈"),
-        ("44", "委員會反對或董事會逾越建議"),
-        ("45", "增資由特定人認購"),
-        ("46", "子公司達終止上市標準或營收為零"),
-        ("47", "海外財報與台灣準則差異"),
-        ("48", "特定營業細則情事"),
-        ("49", "子公司控制力喪失或持股下降"),
-        ("50", "子公司海外掛牌相關事項"),
-        ("51", "其他重大決策或影響股價事件"),
-    ];
-
-    // 檢查是否已經初始化
-    let count = collection.count_documents(doc! {}, None).await?;
-    if count > 0 {
-        println!("📋 條款代號對照表已存在，跳過初始化");
-        return Ok(());
+// 開啟原始回應封存；--archive-gridfs 時較大的內容改存 MongoDB GridFS
+async fn open_archive(args: &Args, dir: &str) -> Result<HtmlArchive> {
+    let mut archive = HtmlArchive::open(dir)?;
+    if args.archive_gridfs {
+        let mongodb_uri = args.store.as_deref().filter(|uri| uri.starts_with("mongodb")).unwrap_or(&args.mongodb_uri);
+        let bucket = gridfs_bucket(mongodb_uri, &args.mongodb_database).await?;
+        archive = archive.with_gridfs(bucket, args.archive_gridfs_min_kb * 1024);
     }
+    Ok(archive)
+}
 
-    let mut documents = Vec::new();
-    for (code, description) in clause_codes {
-        documents.push(ClauseCode {
-            id: None,
-            code: code.to_string(),
-            description: description.to_string(),
-            created_at: Some(chrono::Utc::now()),
-        });
-    }
+// 列出或匯出封存的原始回應
+async fn run_archive(args: &Args, action: &ArchiveAction) -> Result<()> {
+    let (ArchiveAction::List { query } | ArchiveAction::Export { query, .. }) = action;
+    let dir = args.archive_dir.as_deref().unwrap_or(DEFAULT_ARCHIVE_DIR);
+    let archive = HtmlArchive::open(dir)?;
+    let filter = ArchiveFilter {
+        kind: query.kind,
+        date: query.date,
+        market: query.market.as_deref().map(parse_market).transpose()?.map(str::to_string),
+        company_code: args.company.clone(),
+    };
+    let entries = archive.entries(&filter)?;
 
-    collection.insert_many(documents, None).await?;
-    println!("✅ 條款代號對照表初始化完成，共 {} 筆資料", collection.count_documents(doc! {}, None).await?);
+    match action {
+        ArchiveAction::List { .. } => {
+            if args.format == "json" {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                print_archive_entries(&entries);
+            }
+        }
+        ArchiveAction::Export { out, latest, .. } => {
+            // 需要讀取 GridFS 時才連線
+            let archive = if args.archive_gridfs { open_archive(args, dir).await? } else { archive };
+            let entries = if *latest { latest_successful(entries) } else { entries };
+
+            fs::create_dir_all(out)?;
+            for entry in &entries {
+                let html = archive.load(entry).await?;
+                let path = Path::new(out).join(export_filename(&args.output, entry));
+                fs::write(&path, html)?;
+                println!("📄 {}", path.display());
+            }
+            println!("✅ 已匯出 {} 個檔案到 {}", entries.len(), out);
+        }
+    }
 
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    
-    let (year, month, day) = if let Some(date_str) = args.date {
-        parse_date(&date_str)?
-    } else {
-        get_today()
-    };
+fn print_archive_entries(entries: &[ArchiveEntry]) {
+    if entries.is_empty() {
+        println!("沒有符合條件的擷取紀錄");
+        return;
+    }
+
+    println!("{:<20} {:<8} {:<10} {:<5} {:<6} {:<5} {:>10} {:>10} {:<7} 雜湊",
+             "擷取時間", "類型", "日期", "市場", "公司", "狀態", "大小", "壓縮後", "位置");
+    println!("{}", "-".repeat(110));
+    for entry in entries {
+        println!("{:<20} {:<8} {:<10} {:<5} {:<6} {:<5} {:>10} {:>10} {:<7} {}",
+                 entry.fetched_at.with_timezone(&taipei_offset()).format("%Y-%m-%d %H:%M:%S"),
+                 entry.kind,
+                 entry.date.map(|d| d.to_string()).unwrap_or_default(),
+                 entry.market.as_deref().unwrap_or(""),
+                 entry.company_code.as_deref().unwrap_or(""),
+                 entry.http_status,
+                 entry.size,
+                 entry.compressed_size,
+                 format!("{:?}", entry.storage).to_lowercase(),
+                 &entry.sha256[..12]);
+    }
+    println!("共 {} 筆擷取紀錄", entries.len());
+}
 
-    println!("查詢日期: {}-{:02}-{:02}", year, month, day);
+// 每個頁面只保留最後一次成功 (2xx) 的擷取
+fn latest_successful(entries: Vec<ArchiveEntry>) -> Vec<ArchiveEntry> {
+    let mut latest: Vec<ArchiveEntry> = Vec::new();
+    for entry in entries.into_iter().filter(|e| (200..300).contains(&e.http_status)) {
+        let same_page = |e: &ArchiveEntry| {
+            (e.kind, e.date, &e.market, &e.company_code) == (entry.kind, entry.date, &entry.market, &entry.company_code)
+        };
+        latest.retain(|e| !same_page(e));
+        latest.push(entry);
+    }
+    latest
+}
 
-    let client = TwseClient::new();
-    let (announcements, html_content) = client.fetch_announcements(year, month, day).await?;
-    
-    let filtered_announcements = if let Some(company_code) = args.company {
-        announcements.into_iter()
-            .filter(|a| a.company_code.contains(&company_code))
-            .collect()
+// 匯出檔名含擷取時間與雜湊，同一天的多次擷取不會互相覆蓋；
+// 單日列表以 <前綴>_<擷取時間>_<雜湊>[_<市場別>]_YYYYMMDD.html 結尾，可直接交給 reparse
+fn export_filename(prefix: &str, entry: &ArchiveEntry) -> String {
+    let fetched_at = entry.fetched_at.with_timezone(&taipei_offset()).format("%Y%m%dT%H%M%S");
+    let hash = &entry.sha256[..8];
+    match (entry.kind, entry.date) {
+        (ArchiveKind::List, Some(date)) => match entry.market.as_deref() {
+            Some(market) if market != "all" => {
+                format!("{}_{}_{}_{}_{}.html", prefix, fetched_at, hash, market, date.to_compact_string())
+            }
+            _ => format!("{}_{}_{}_{}.html", prefix, fetched_at, hash, date.to_compact_string()),
+        },
+        (kind, _) => format!(
+            "{}_{}_{}_{}_{}.html",
+            prefix,
+            kind,
+            entry.company_code.as_deref().unwrap_or("all"),
+            fetched_at,
+            hash
+        ),
+    }
+}
+
+// 合併重複公告，完成後建立識別鍵唯一索引
+async fn run_dedupe_command(args: &Args, apply: bool) -> Result<()> {
+    if args.store.as_deref().is_some_and(|uri| !uri.starts_with("mongodb")) {
+        anyhow::bail!("dedupe 只適用於 MongoDB，SQLite 建立資料表時已有唯一索引");
+    }
+    let mongodb_uri = args.store.as_deref().unwrap_or(&args.mongodb_uri);
+
+    println!("🔧 {} {}.{} 的重複公告", if apply { "合併" } else { "試算合併" },
+             args.mongodb_database, args.mongodb_collection);
+
+    let report = run_dedupe(
+        mongodb_uri,
+        &args.mongodb_database,
+        &args.mongodb_collection,
+        apply,
+    ).await?;
+    report.print(apply);
+
+    println!();
+    if !apply {
+        println!("ℹ️  確認報告無誤後，加上 --apply 執行合併並建立唯一索引");
+    } else if report.write_errors.is_empty() {
+        println!("✅ 合併完成，已建立識別鍵唯一索引");
     } else {
-        announcements
-    };
+        anyhow::bail!("合併過程有 {} 筆寫入錯誤，未建立唯一索引", report.write_errors.len());
+    }
 
-    // 生成檔案名稱（包含日期）
-    let filename = format!("{}_{:04}{:02}{:02}", args.output, year, month, day);
-    let query_date_str = format!("{:04}-{:02}-{:02}", year, month, day);
+    Ok(())
+}
 
-    // 自動儲存原始 HTML（每次查詢都會保存）
-    save_html(&html_content, &filename)?;
+// 逐日回補指定日期範圍，跳過週末與休市日，並以進度檔支援中斷續跑
+async fn run_backfill(client: &TwseClient, args: &Args) -> Result<()> {
+    let Some(start_date) = args.start_date else {
+        anyhow::bail!("回補模式需要指定 --start-date");
+    };
+    let end_date = args.end_date.unwrap_or_else(RocDate::today);
 
-    // 儲存到 MongoDB（如果要求）
-    if args.save_mongodb {
-        println!("正在連接 MongoDB...");
+    if start_date > end_date {
+        anyhow::bail!("起始日期 {} 晚於結束日期 {}", start_date, end_date);
+    }
 
-        // 建立索引
-        if let Err(e) = setup_mongodb_indexes(&args.mongodb_uri, &args.mongodb_database, &args.mongodb_collection).await {
-            println!("警告：建立 MongoDB 索引失敗: {}", e);
+    let holidays = match &args.holidays_file {
+        Some(path) => {
+            let holidays = load_holidays(path)?;
+            println!("📅 已載入 {} 個休市日: {}", holidays.len(), path);
+            holidays
         }
+        None => HashSet::new(),
+    };
 
-        // 初始化條款代號對照表
-        let client = MongoClient::with_uri_str(&args.mongodb_uri).await?;
-        let database = client.database(&args.mongodb_database);
-        let clause_collection: Collection<ClauseCode> = database.collection("clause_codes");
-
-        if let Err(e) = initialize_clause_codes(&clause_collection).await {
-            println!("警告：初始化條款代號對照表失敗: {}", e);
+    let market = parse_market(&args.market)?;
+    let checkpoint_path = args.checkpoint_file.clone().unwrap_or_else(|| {
+        if market == "all" {
+            format!("{}_backfill_checkpoint.json", args.output)
+        } else {
+            format!("{}_{}_backfill_checkpoint.json", args.output, market)
         }
+    });
 
-        // 儲存資料
-        save_to_mongodb(
-            &filtered_announcements,
-            &args.mongodb_uri,
-            &args.mongodb_database,
-            &args.mongodb_collection,
-            &query_date_str,
-            &args.duplicate_mode
-        ).await?;
-    }
+    let start_str = start_date.to_gregorian_string();
+    let end_str = end_date.to_gregorian_string();
 
-    // 根據格式輸出或儲存
-    match args.format.as_str() {
-        "json" => {
-            let json_output = serde_json::to_string_pretty(&filtered_announcements)?;
-            println!("{}", json_output);
-            save_json(&filtered_announcements, &filename)?;
-        }
-        "table" => {
-            print_table(&filtered_announcements);
-            save_txt(&filtered_announcements, &filename)?;
+    let mut checkpoint = match BackfillCheckpoint::load(&checkpoint_path, start_date, end_date) {
+        Some(cp) => {
+            if let Some(last) = &cp.last_completed {
+                println!("♻️  從進度檔續跑，上次完成日期: {} ({})", last, checkpoint_path);
+            }
+            if !cp.failed_dates.is_empty() {
+                println!("♻️  重試先前失敗的 {} 天: {}", cp.failed_dates.len(), cp.failed_dates.join(", "));
+            }
+            cp
         }
-        "html" => {
-            println!("原始 HTML 內容:");
-            println!("{}", html_content);
-            save_html(&html_content, &filename)?;
+        None => BackfillCheckpoint::new(start_date, end_date),
+    };
+
+    println!("🔄 回補範圍: {} ~ {}", start_str, end_str);
+
+    let store = prepare_store(args).await?;
+
+    let mut processed_count = 0;
+    let mut skipped_count = 0;
+
+    let mut current = start_date;
+    while current <= end_date {
+        let date = current;
+        current = current.succ().expect("日期超出範圍");
+
+        if !checkpoint.is_pending(date) {
+            continue;
         }
-        "txt" => {
-            save_txt(&filtered_announcements, &filename)?;
-            println!("資料已儲存到 TXT 檔案");
+
+        let is_weekend = matches!(date.naive_date().weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun);
+        if (is_weekend && !args.include_weekends) || holidays.contains(&date) {
+            skipped_count += 1;
+            continue;
         }
-        _ => {
-            anyhow::bail!("不支援的輸出格式: {}。支援的格式: json, table, html, txt", args.format);
+
+        let date_str = date.to_gregorian_string();
+        let mut attempt = 0;
+        let succeeded = loop {
+            match process_date(client, args, store.as_deref(), date).await {
+                Ok(()) => break true,
+                // 網站結構變更時重試也無效，立即結束讓排程工作發出告警；進度檔保留，修正後可續跑
+                Err(e) if e.is::<LayoutChangedError>() => {
+                    println!("❌ {} 無法解析回應，停止回補", date_str);
+                    return Err(e);
+                }
+                // HTTP 請求已重試並退避，整日重試只會讓請求次數倍增
+                Err(e) if e.is::<RetriesExhausted>() => {
+                    println!("❌ {} 查詢失敗: {:#}", date_str, e);
+                    break false;
+                }
+                // 重試的請求同樣經過客戶端的速率限制，不另外等待
+                Err(e) if attempt < args.max_retries => {
+                    attempt += 1;
+                    println!("⚠️  {} 查詢失敗: {}，進行第 {} 次重試", date_str, e, attempt);
+                }
+                Err(e) => {
+                    println!("❌ {} 查詢失敗，已達重試上限: {}", date_str, e);
+                    break false;
+                }
+            }
+        };
+
+        if succeeded {
+            processed_count += 1;
         }
+        checkpoint.record(date, succeeded);
+        checkpoint.save(&checkpoint_path)?;
+    }
+
+    println!();
+    println!("✅ 回補完成: {} ~ {}", start_str, end_str);
+    println!("  已查詢: {} 天", processed_count);
+    println!("  跳過週末/休市日: {} 天", skipped_count);
+    if checkpoint.failed_dates.is_empty() {
+        println!("  失敗: 0 天");
+    } else {
+        println!("  失敗: {} 天 ({})", checkpoint.failed_dates.len(), checkpoint.failed_dates.join(", "));
+        println!("  失敗日期已記錄於進度檔: {}", checkpoint_path);
     }
 
     Ok(())
 }
 
-// 格式化事實發生日 (YYYYMMDD -> YYYY-MM-DD)
-fn format_fact_occurrence_date(date_str: &str) -> String {
-    if date_str.len() == 8 && date_str.chars().all(|c| c.is_ascii_digit()) {
-        // YYYYMMDD 格式
-        let year = &date_str[0..4];
-        let month = &date_str[4..6];
-        let day = &date_str[6..8];
-        format!("{}-{}-{}", year, month, day)
-    } else {
-        // 如果不是標準格式，直接返回原始值
-        date_str.to_string()
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    // 先檢查市場別，避免回補時每天都重試無效參數
+    parse_market(&args.market)?;
+
+    // 不需連線 MOPS 的指令
+    match &args.command {
+        Some(Command::Reparse { paths }) => return run_reparse(&args, paths).await,
+        Some(Command::Migrate { apply, batch_size }) => return run_migrate(&args, *apply, *batch_size).await,
+        Some(Command::Dedupe { apply }) => return run_dedupe_command(&args, *apply).await,
+        Some(Command::Archive { action }) => return run_archive(&args, action).await,
+        _ => {}
+    }
+
+    let mut client = TwseClient::with_config(TwseClientConfig {
+        base_url: args.mops_base_url.clone(),
+        timeout: Duration::from_secs(args.timeout_secs),
+        max_retries: args.http_retries,
+        requests_per_minute: args.requests_per_minute,
+        throttle_cooldown: Duration::from_secs(args.throttle_cooldown_secs),
+        ..Default::default()
+    });
+    // 每次擷取的原始回應都封存，之後可用 reparse 重新解析
+    let archive = open_archive(&args, args.archive_dir.as_deref().unwrap_or(DEFAULT_ARCHIVE_DIR)).await?;
+    client = client.with_archive(archive);
+
+    match &args.command {
+        Some(Command::History { code, from, to }) => return run_history(&client, &args, code, *from, *to).await,
+        Some(Command::Watch { interval_secs, start_time, end_time }) => {
+            return run_watch(&client, &args, *interval_secs, *start_time, *end_time).await
+        }
+        _ => {}
     }
+
+    // 指定日期範圍時進入回補模式
+    if args.start_date.is_some() || args.end_date.is_some() {
+        return run_backfill(&client, &args).await;
+    }
+
+    let date = args.date.unwrap_or_else(RocDate::today);
+
+    let store = prepare_store(&args).await?;
+    process_date(&client, &args, store.as_deref(), date).await
 }
@@ -0,0 +1,297 @@
+//! 既有 MongoDB 資料的日期欄位正規化。
+//!
+//! 早期版本以字串儲存日期（`114/08/17`、`2025-08-17`、自由文字等），
+//! 擷取時間 `created_at` 則為 RFC 3339 字串。此模組逐批掃描集合，將日期與擷取時間轉為 BSON 日期並補上 `announced_at`，
+//! 完成後於 `schema_info` 集合記錄 schema 版本。
+
+use anyhow::Result;
+use bson::{doc, Bson, Document};
+use chrono::{DateTime, Utc};
+use mongodb::{options::FindOptions, Client as MongoClient, Collection, Database};
+use std::collections::BTreeMap;
+
+use crate::date::RocDate;
+
+/// 目前的資料 schema 版本：日期欄位與 `created_at` 為 BSON 日期並包含 `announced_at`
+pub const SCHEMA_VERSION: i32 = 3;
+
+/// 記錄各集合 schema 版本的集合名稱
+pub const SCHEMA_COLLECTION: &str = "schema_info";
+
+// 需要正規化的日期欄位
+const DATE_FIELDS: [&str; 4] = ["date", "query_date", "fact_date", "fact_occurrence_date"];
+
+// 需要轉為 BSON 日期的時間戳記欄位，舊版以 RFC 3339 字串儲存
+const TIMESTAMP_FIELDS: [&str; 1] = ["created_at"];
+
+// 報告中保留的範例筆數
+const MAX_SAMPLES: usize = 10;
+
+/// 單一日期欄位的轉換統計
+#[derive(Debug, Default, Clone)]
+pub struct FieldStats {
+    /// 已是 BSON 日期
+    pub typed: u64,
+    /// 字串可轉換
+    pub converted: u64,
+    /// 無法辨識，保留原值
+    pub unparseable: u64,
+}
+
+/// 遷移結果報告（試算與實際執行共用）
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub previous_version: Option<i32>,
+    pub scanned: u64,
+    pub to_update: u64,
+    pub updated: u64,
+    pub write_errors: Vec<String>,
+    pub fields: BTreeMap<&'static str, FieldStats>,
+    pub announced_at_added: u64,
+    pub announced_at_missing: u64,
+    pub samples: Vec<String>,
+    pub unparseable_samples: Vec<String>,
+}
+
+impl MigrationReport {
+    /// 輸出報告
+    pub fn print(&self, applied: bool) {
+        println!();
+        println!("{}", if applied { "📋 遷移報告" } else { "📋 遷移試算報告 (dry-run，未寫入任何資料)" });
+        match self.previous_version {
+            Some(version) => println!("  目前 schema 版本: {} -> {}", version, SCHEMA_VERSION),
+            None => println!("  目前 schema 版本: 未記錄 -> {}", SCHEMA_VERSION),
+        }
+        println!("  掃描文件: {} 筆", self.scanned);
+        println!("  需要更新: {} 筆", self.to_update);
+        if applied {
+            println!("  已更新: {} 筆", self.updated);
+        }
+
+        println!();
+        println!("  {:<22} {:>10} {:>10} {:>10}", "欄位", "已為日期", "可轉換", "無法辨識");
+        for field in DATE_FIELDS.into_iter().chain(TIMESTAMP_FIELDS) {
+            let stats = self.fields.get(field).cloned().unwrap_or_default();
+            println!("  {:<22} {:>10} {:>10} {:>10}", field, stats.typed, stats.converted, stats.unparseable);
+        }
+        println!("  announced_at 新增: {} 筆，無法計算: {} 筆", self.announced_at_added, self.announced_at_missing);
+
+        if !self.samples.is_empty() {
+            println!();
+            println!("  轉換範例:");
+            for sample in &self.samples {
+                println!("    {}", sample);
+            }
+        }
+        if !self.unparseable_samples.is_empty() {
+            println!();
+            println!("  無法辨識的值 (保留原值):");
+            for sample in &self.unparseable_samples {
+                println!("    {}", sample);
+            }
+        }
+        if !self.write_errors.is_empty() {
+            println!();
+            println!("  ❌ 寫入錯誤 {} 筆:", self.write_errors.len());
+            for error in self.write_errors.iter().take(MAX_SAMPLES) {
+                println!("    {}", error);
+            }
+        }
+    }
+}
+
+/// 計算單一文件需要的 `$set` 更新，無需更新時回傳 `None`
+pub fn plan_document(document: &Document, report: &mut MigrationReport) -> Option<Document> {
+    let id = document.get("_id").map(|id| id.to_string()).unwrap_or_default();
+    let mut set = Document::new();
+    let mut announcement_date = None;
+
+    for field in DATE_FIELDS {
+        let stats = report.fields.entry(field).or_default();
+        match document.get(field) {
+            None | Some(Bson::Null) => {}
+            Some(Bson::DateTime(datetime)) => {
+                stats.typed += 1;
+                if field == "date" {
+                    announcement_date = Some(RocDate::from_bson(*datetime));
+                }
+            }
+            Some(Bson::String(text)) => match RocDate::parse(text) {
+                Some(date) => {
+                    stats.converted += 1;
+                    set.insert(field, date.to_bson());
+                    if field == "date" {
+                        announcement_date = Some(date);
+                    }
+                    if report.samples.len() < MAX_SAMPLES {
+                        report.samples.push(format!("{} {}: \"{}\" -> {}", id, field, text, date));
+                    }
+                }
+                None => {
+                    stats.unparseable += 1;
+                    if report.unparseable_samples.len() < MAX_SAMPLES {
+                        report.unparseable_samples.push(format!("{} {}: \"{}\"", id, field, text));
+                    }
+                }
+            },
+            Some(other) => {
+                stats.unparseable += 1;
+                if report.unparseable_samples.len() < MAX_SAMPLES {
+                    report.unparseable_samples.push(format!("{} {}: {}", id, field, other));
+                }
+            }
+        }
+    }
+
+    for field in TIMESTAMP_FIELDS {
+        let stats = report.fields.entry(field).or_default();
+        match document.get(field) {
+            None | Some(Bson::Null) => {}
+            Some(Bson::DateTime(_)) => stats.typed += 1,
+            Some(Bson::String(text)) => match DateTime::parse_from_rfc3339(text.trim()) {
+                Ok(timestamp) => {
+                    let timestamp = timestamp.with_timezone(&Utc);
+                    stats.converted += 1;
+                    set.insert(field, bson::DateTime::from_chrono(timestamp));
+                    if report.samples.len() < MAX_SAMPLES {
+                        report.samples.push(format!("{} {}: \"{}\" -> {}", id, field, text, timestamp.to_rfc3339()));
+                    }
+                }
+                Err(_) => {
+                    stats.unparseable += 1;
+                    if report.unparseable_samples.len() < MAX_SAMPLES {
+                        report.unparseable_samples.push(format!("{} {}: \"{}\"", id, field, text));
+                    }
+                }
+            },
+            Some(other) => {
+                stats.unparseable += 1;
+                if report.unparseable_samples.len() < MAX_SAMPLES {
+                    report.unparseable_samples.push(format!("{} {}: {}", id, field, other));
+                }
+            }
+        }
+    }
+
+    // 公告日期加時間 (台灣時間) 的時間戳記
+    if !matches!(document.get("announced_at"), Some(Bson::DateTime(_))) {
+        let announced_at = announcement_date
+            .zip(document.get_str("time").ok())
+            .and_then(|(date, time)| date.with_time(time));
+        match announced_at {
+            Some(timestamp) => {
+                set.insert("announced_at", bson::DateTime::from_chrono(timestamp));
+                report.announced_at_added += 1;
+            }
+            None => report.announced_at_missing += 1,
+        }
+    }
+
+    if set.is_empty() {
+        None
+    } else {
+        Some(doc! { "$set": set })
+    }
+}
+
+/// 讀取集合目前記錄的 schema 版本
+pub async fn get_schema_version(database: &Database, collection_name: &str) -> Result<Option<i32>> {
+    let schema: Collection<Document> = database.collection(SCHEMA_COLLECTION);
+    let marker = schema.find_one(doc! { "_id": collection_name }, None).await?;
+    Ok(marker.and_then(|m| m.get_i32("schema_version").ok()))
+}
+
+/// 逐批掃描集合並正規化日期欄位；`apply` 為 false 時只產生試算報告
+pub async fn run_migration(
+    mongodb_uri: &str,
+    database_name: &str,
+    collection_name: &str,
+    batch_size: u32,
+    apply: bool,
+) -> Result<MigrationReport> {
+    let client = MongoClient::with_uri_str(mongodb_uri).await?;
+    let database = client.database(database_name);
+    let collection: Collection<Document> = database.collection(collection_name);
+
+    let mut report = MigrationReport {
+        previous_version: get_schema_version(&database, collection_name).await?,
+        ..Default::default()
+    };
+
+    // 以 _id 遞增分批讀取，更新過程中也不會重複或遺漏文件
+    let mut last_id: Option<Bson> = None;
+    loop {
+        let filter = match &last_id {
+            Some(id) => doc! { "_id": { "$gt": id.clone() } },
+            None => doc! {},
+        };
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": 1 })
+            .limit(batch_size as i64)
+            .build();
+
+        let mut cursor = collection.find(filter, options).await?;
+        let mut updates = Vec::new();
+        let mut batch_count = 0;
+
+        while cursor.advance().await? {
+            let document: Document = cursor.deserialize_current()?;
+            batch_count += 1;
+            report.scanned += 1;
+            let id = document.get("_id").cloned().unwrap_or(Bson::Null);
+
+            if let Some(update) = plan_document(&document, &mut report) {
+                report.to_update += 1;
+                updates.push(doc! { "q": { "_id": id.clone() }, "u": update });
+            }
+            last_id = Some(id);
+        }
+
+        if batch_count == 0 {
+            break;
+        }
+
+        if apply && !updates.is_empty() {
+            // 以單一 update 命令送出整批更新，減少往返次數
+            let result = database
+                .run_command(doc! { "update": collection_name, "updates": updates, "ordered": false }, None)
+                .await?;
+            report.updated += result.get_i32("nModified").unwrap_or(0) as u64;
+            if let Ok(errors) = result.get_array("writeErrors") {
+                for error in errors.iter().filter_map(Bson::as_document) {
+                    report.write_errors.push(format!(
+                        "第 {} 筆: {}",
+                        error.get_i32("index").unwrap_or(-1),
+                        error.get_str("errmsg").unwrap_or("未知錯誤")
+                    ));
+                }
+            }
+        }
+
+        println!("📦 已掃描 {} 筆，需要更新 {} 筆", report.scanned, report.to_update);
+
+        if batch_count < batch_size as usize {
+            break;
+        }
+    }
+
+    // 全部寫入成功才記錄 schema 版本
+    if apply && report.write_errors.is_empty() {
+        let schema: Collection<Document> = database.collection(SCHEMA_COLLECTION);
+        let options = mongodb::options::ReplaceOptions::builder().upsert(true).build();
+        schema
+            .replace_one(
+                doc! { "_id": collection_name },
+                doc! {
+                    "_id": collection_name,
+                    "schema_version": SCHEMA_VERSION,
+                    "migrated_at": bson::DateTime::now(),
+                    "migrated_documents": report.updated as i64,
+                },
+                options,
+            )
+            .await?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,300 @@
+//! 本機模擬的 MOPS 伺服器，供端對端測試使用。
+//!
+//! 以 axum 回應 `ajax_t05st02` (單日列表) 與 `ajax_t05st01` (公司歷史、明細頁) 的表單查詢，
+//! 內容來自預先設定的 HTML；沒有設定的查詢回傳查無資料的頁面。
+//! 另可排入故障，依序套用在接下來的請求上，模擬限流、逾時、5xx 與網站結構變更：
+//!
+//! ```no_run
+//! use twse_announcements::client::{TwseClient, TwseClientConfig};
+//! use twse_announcements::mock::{Fault, MockMops};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mock = MockMops::new();
+//! mock.push_faults([Fault::Throttle]);
+//! let base_url = mock.spawn().await?;
+//! let client = TwseClient::with_config(TwseClientConfig { base_url, ..Default::default() });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 伺服器另提供 `POST /__mock/faults` (JSON 故障陣列) 與 `GET /__mock/requests`，
+//! 讓外部程序在執行期間排入故障並檢查收到的請求。
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::date::RocDate;
+
+// 查無資料時的頁面，與 MOPS 的提示文字相同
+const NO_DATA_PAGE: &str = "<html><body><center><font color='red'>沒有找到重大訊息</font></center></body></html>";
+const HISTORY_NO_DATA_PAGE: &str = "<html><body><center>查無所需資料！</center></body></html>";
+const THROTTLED_PAGE: &str = "<html><body><center><h3>查詢過於頻繁，請稍後再試！！</h3></center></body></html>";
+const CHANGED_LAYOUT_PAGE: &str =
+    "<html><body><div class='announcement-list'><div class='item'>2330 台積電 董事會決議</div></div></body></html>";
+
+/// 套用在下一個請求上的故障
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fault {
+    /// 回傳「查詢過於頻繁」的限流頁面
+    Throttle,
+    /// 延遲回應 (預設 120 秒)，讓用戶端逾時
+    Timeout,
+    /// 回傳 HTTP 503
+    ServerError,
+    /// 回傳結構不同的頁面
+    LayoutChange,
+}
+
+impl FromStr for Fault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "throttle" => Ok(Self::Throttle),
+            "timeout" => Ok(Self::Timeout),
+            "server_error" => Ok(Self::ServerError),
+            "layout_change" => Ok(Self::LayoutChange),
+            _ => Err(format!("不支援的故障類型: {}。支援的類型: throttle, timeout, server_error, layout_change", s)),
+        }
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Throttle => "throttle",
+            Self::Timeout => "timeout",
+            Self::ServerError => "server_error",
+            Self::LayoutChange => "layout_change",
+        })
+    }
+}
+
+/// 伺服器收到的一個請求
+#[derive(Debug, Clone, Serialize)]
+pub struct MockRequest {
+    /// 查詢頁面，例如 `ajax_t05st02`
+    pub page: String,
+    pub form: HashMap<String, String>,
+    /// 套用在此請求上的故障
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fault: Option<Fault>,
+}
+
+struct MockState {
+    lists: HashMap<(RocDate, String), String>,
+    histories: HashMap<(String, i32), String>,
+    details: HashMap<(String, String), String>,
+    faults: VecDeque<Fault>,
+    timeout_delay: Duration,
+    requests: Vec<MockRequest>,
+}
+
+/// 模擬的 MOPS 伺服器；複製後共用同一份設定與請求紀錄
+#[derive(Clone)]
+pub struct MockMops {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockMops {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockMops {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                lists: HashMap::new(),
+                histories: HashMap::new(),
+                details: HashMap::new(),
+                faults: VecDeque::new(),
+                timeout_delay: Duration::from_secs(120),
+                requests: Vec::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        // 處理請求時不會 panic，鎖不會因此失效
+        self.state.lock().expect("模擬伺服器狀態鎖定失敗")
+    }
+
+    /// 設定單日列表的回應；市場別 `all` 的頁面也用於沒有個別設定的市場
+    pub fn set_list(&self, date: RocDate, market: &str, html: impl Into<String>) {
+        self.state().lists.insert((date, market.to_string()), html.into());
+    }
+
+    /// 設定公司在某個民國年度的歷史查詢回應
+    pub fn set_history(&self, company_code: &str, roc_year: i32, html: impl Into<String>) {
+        self.state().histories.insert((company_code.to_string(), roc_year), html.into());
+    }
+
+    /// 設定明細頁回應，以公司代號與發言序號對應
+    pub fn set_detail(&self, company_code: &str, sequence_number: &str, html: impl Into<String>) {
+        self.state().details.insert((company_code.to_string(), sequence_number.to_string()), html.into());
+    }
+
+    /// 排入故障，每個請求依序取用一個
+    pub fn push_faults(&self, faults: impl IntoIterator<Item = Fault>) {
+        self.state().faults.extend(faults);
+    }
+
+    /// 逾時故障的延遲時間
+    pub fn set_timeout_delay(&self, delay: Duration) {
+        self.state().timeout_delay = delay;
+    }
+
+    /// 目前收到的所有請求
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    /// 由目錄載入回應檔，回傳載入的檔案數
+    ///
+    /// 檔名格式：`list[_<市場別>]_YYYYMMDD.html`、`history_<公司代號>_<民國年>.html`、
+    /// `detail_<公司代號>_<發言序號>.html`，其他檔案略過。
+    pub fn load_dir(&self, dir: impl AsRef<Path>) -> Result<usize> {
+        let dir = dir.as_ref();
+        let mut loaded = 0;
+        for entry in std::fs::read_dir(dir).with_context(|| format!("無法讀取回應目錄 {}", dir.display()))? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "html") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let parts: Vec<&str> = stem.split('_').collect();
+            let html = || std::fs::read_to_string(&path);
+            match parts.as_slice() {
+                ["list", date] | ["list", _, date] => {
+                    let Some(date) = RocDate::parse(date) else { continue };
+                    let market = if parts.len() == 3 { parts[1] } else { "all" };
+                    self.set_list(date, market, html()?);
+                }
+                ["history", code, year] => {
+                    let Ok(year) = year.parse() else { continue };
+                    self.set_history(code, year, html()?);
+                }
+                ["detail", code, seq] => self.set_detail(code, seq, html()?),
+                _ => continue,
+            }
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// 建立路由，查詢頁面位於 `/mops/web/` 之下
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/mops/web/ajax_t05st02", post(list_handler))
+            .route("/mops/web/ajax_t05st01", post(t05st01_handler))
+            .route("/__mock/faults", post(faults_handler))
+            .route("/__mock/requests", get(requests_handler))
+            .with_state(self.clone())
+    }
+
+    /// 在背景啟動伺服器，回傳可設為 `TwseClientConfig::base_url` 的網址
+    pub async fn spawn(&self) -> Result<String> {
+        self.spawn_on(SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    /// 在指定位址背景啟動伺服器 (埠號 0 為自動選擇)
+    pub async fn spawn_on(&self, addr: SocketAddr) -> Result<String> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let app = self.router();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                println!("⚠️  模擬 MOPS 伺服器停止: {}", e);
+            }
+        });
+        Ok(format!("http://{}/mops/web", addr))
+    }
+
+    // 記錄請求並取出要套用的故障
+    fn record(&self, page: &str, form: HashMap<String, String>) -> (Option<Fault>, Duration) {
+        let mut state = self.state();
+        let fault = state.faults.pop_front();
+        let delay = state.timeout_delay;
+        state.requests.push(MockRequest { page: page.to_string(), form, fault });
+        (fault, delay)
+    }
+
+    // 套用故障；沒有故障時回傳正常頁面
+    async fn respond(&self, page: &str, form: HashMap<String, String>, lookup: impl FnOnce(&MockState) -> String) -> Response {
+        let (fault, delay) = self.record(page, form);
+        match fault {
+            Some(Fault::Throttle) => Html(THROTTLED_PAGE).into_response(),
+            Some(Fault::ServerError) => (StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable").into_response(),
+            Some(Fault::LayoutChange) => Html(CHANGED_LAYOUT_PAGE).into_response(),
+            Some(Fault::Timeout) => {
+                tokio::time::sleep(delay).await;
+                Html(lookup(&self.state())).into_response()
+            }
+            None => Html(lookup(&self.state())).into_response(),
+        }
+    }
+}
+
+// 單日列表：依 year (民國)、month、day 與 TYPEK 查詢
+async fn list_handler(State(mock): State<MockMops>, Form(form): Form<HashMap<String, String>>) -> Response {
+    let date = form_date(&form);
+    let market = form.get("TYPEK").cloned().unwrap_or_else(|| "all".to_string());
+    mock.respond("ajax_t05st02", form, |state| {
+        date.and_then(|date| {
+            state.lists.get(&(date, market)).or_else(|| state.lists.get(&(date, "all".to_string())))
+        })
+        .cloned()
+        .unwrap_or_else(|| NO_DATA_PAGE.to_string())
+    })
+    .await
+}
+
+// t05st01：step 1 為公司歷史查詢，step 2 為明細頁
+async fn t05st01_handler(State(mock): State<MockMops>, Form(form): Form<HashMap<String, String>>) -> Response {
+    let company_code = form.get("co_id").cloned().unwrap_or_default();
+    let year = form.get("year").and_then(|y| y.parse::<i32>().ok());
+    let sequence_number = form.get("seq_no").cloned();
+    let is_detail = form.get("step").is_some_and(|step| step == "2");
+
+    mock.respond("ajax_t05st01", form, |state| {
+        let page = if is_detail {
+            sequence_number.and_then(|seq| state.details.get(&(company_code, seq)))
+        } else {
+            year.and_then(|year| state.histories.get(&(company_code, year)))
+        };
+        page.cloned().unwrap_or_else(|| HISTORY_NO_DATA_PAGE.to_string())
+    })
+    .await
+}
+
+async fn faults_handler(State(mock): State<MockMops>, Json(faults): Json<Vec<Fault>>) -> StatusCode {
+    mock.push_faults(faults);
+    StatusCode::NO_CONTENT
+}
+
+async fn requests_handler(State(mock): State<MockMops>) -> Json<Vec<MockRequest>> {
+    Json(mock.requests())
+}
+
+fn form_date(form: &HashMap<String, String>) -> Option<RocDate> {
+    let field = |name: &str| form.get(name).and_then(|v| v.parse().ok());
+    RocDate::from_roc_ymd(field("year")?, field("month")? as u32, field("day")? as u32)
+}
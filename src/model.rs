@@ -0,0 +1,238 @@
+//! 重大訊息與條款代號的資料結構，CLI、儲存層與 Web 查看器共用。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use crate::date::RocDate;
+
+/// 一筆重大訊息公告
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Announcement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<bson::oid::ObjectId>, // MongoDB 延伸 JSON 格式 {"$oid": "..."}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announcement_id: Option<String>, // 由識別鍵計算的穩定識別碼，寫入資料庫時補上
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>, // detail_content 的 SHA-256，用於判斷內容是否更正
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>, // 目前的版本號，首次寫入為 1，內容每變更一次加 1
+    pub company_code: String,
+    pub company_name: String,
+    pub title: String,
+    #[serde(with = "crate::date::roc_format")]
+    #[schema(value_type = String, example = "114/08/15")]
+    pub date: RocDate, // 公告日期，JSON 以民國格式輸出，MongoDB 存為 BSON 日期
+    pub time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::date::timestamp_format")]
+    pub announced_at: Option<DateTime<Utc>>, // 公告日期加時間 (台灣時間) 的時間戳記
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcement_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "crate::date::deserialize_lenient")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub fact_date: Option<RocDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "crate::date::deserialize_lenient")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub fact_occurrence_date: Option<RocDate>, // 新增事實發生日欄位 (從 h07 擷取)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clause_code: Option<String>, // 條款代號欄位
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_html: Option<String>, // 新增原始 HTML 資料欄位
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::date::timestamp_format")]
+    pub created_at: Option<DateTime<Utc>>, // 擷取時間，MongoDB 存為 BSON 日期
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "crate::date::deserialize_lenient")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub query_date: Option<RocDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>, // 市場別 (sii, otc, rotc, pub)，查詢全部市場時為空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<String>, // 當日發言序號，查詢明細頁時使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<AnnouncementDetail>, // 明細頁的結構化資料 (--with-details)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>, // 列表頁未定義的隱藏欄位，鍵為去除列號的欄位名稱 (h09 -> h9)
+}
+
+/// 重大訊息的識別鍵：公司代號 + 日期 + 時間 + 標題
+pub type AnnouncementKey = (String, RocDate, String, String);
+
+impl Announcement {
+    /// 判斷是否為同一則公告的識別鍵，MongoDB 防重複與監看模式共用
+    pub fn key(&self) -> AnnouncementKey {
+        (self.company_code.clone(), self.date, self.time.clone(), self.title.clone())
+    }
+
+    /// 由識別鍵計算的穩定識別碼 (32 個十六進位字元)，重新抓取或更正內容都不會改變
+    pub fn stable_id(&self) -> String {
+        let key = [
+            self.company_code.as_str(),
+            &self.date.to_gregorian_string(),
+            &self.time,
+            &self.title,
+        ]
+        .join("\u{1f}");
+        sha256_hex(&key)[..32].to_string()
+    }
+
+    /// `detail_content` 的 SHA-256，沒有內容時以空字串計算
+    pub fn compute_content_hash(&self) -> String {
+        sha256_hex(self.detail_content.as_deref().unwrap_or(""))
+    }
+
+    /// 補上穩定識別碼與公告時間戳記，並重新計算內容雜湊
+    pub fn ensure_identity(&mut self) {
+        if self.announcement_id.is_none() {
+            self.announcement_id = Some(self.stable_id());
+        }
+        self.content_hash = Some(self.compute_content_hash());
+        self.announced_at = Some(self.sort_timestamp());
+    }
+
+    /// 排序與游標分頁使用的公告時間：`announced_at`，沒有時以日期加時間計算，時間無法解析時為當日 00:00 (台灣時間)
+    pub fn sort_timestamp(&self) -> DateTime<Utc> {
+        self.announced_at
+            .or_else(|| self.date.with_time(&self.time))
+            .or_else(|| self.date.with_time("00:00:00"))
+            .unwrap_or_default()
+    }
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 公告內容被更正前的舊版本
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AnnouncementRevision {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<bson::oid::ObjectId>,
+    pub announcement_id: String,
+    pub revision: u32, // 此舊版本的版本號
+    pub content_hash: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<AnnouncementDetail>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::date::timestamp_format")]
+    pub recorded_at: Option<DateTime<Utc>>, // 此版本擷取的時間
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::date::timestamp_format")]
+    pub superseded_at: Option<DateTime<Utc>>, // 被新版本取代的時間
+}
+
+impl AnnouncementRevision {
+    /// 以目前儲存的版本建立舊版本紀錄
+    pub fn from_superseded(announcement: &Announcement, superseded_at: DateTime<Utc>) -> Self {
+        Self {
+            id: None,
+            announcement_id: announcement.announcement_id.clone().unwrap_or_else(|| announcement.stable_id()),
+            revision: announcement.revision.unwrap_or(1),
+            content_hash: announcement.content_hash.clone().unwrap_or_else(|| announcement.compute_content_hash()),
+            title: announcement.title.clone(),
+            detail_content: announcement.detail_content.clone(),
+            detail: announcement.detail.clone(),
+            recorded_at: announcement.created_at,
+            superseded_at: Some(superseded_at),
+        }
+    }
+}
+
+/// 重大訊息明細頁 (t05st01) 擷取的結構化資料
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct AnnouncementDetail {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spokesperson: Option<String>, // 發言人
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spokesperson_title: Option<String>, // 發言人職稱
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spokesperson_phone: Option<String>, // 發言人電話
+    #[serde(default)]
+    pub is_correction: bool, // 是否為更正公告
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<DetailItem>, // 說明欄的編號項目
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>, // 明細頁所有「欄位名稱: 內容」
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+/// 說明欄中的一個編號項目，例如 `1.事實發生日:114/08/15`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DetailItem {
+    pub number: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub content: String,
+}
+
+/// 條款代號對照表的一筆資料
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ClauseCode {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<bson::oid::ObjectId>,
+    pub code: String,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::date::timestamp_format")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 重大訊息條款代號與簡要說明
+pub const CLAUSE_CODES: &[(&str, &str)] = &[
+    ("1", "信用異常或股票交易異動"),
+    ("2", "涉訟或主管違法"),
+    ("3", "停工、減產、資產處理"),
+    ("4", "公司法重大決議"),
+    ("5", "重整或破產程序"),
+    ("6", "高層人事異動或席次不足"),
+    ("7", "更換會計師或承銷商"),
+    ("8", "重要主管異動"),
+    ("9", "會計年度或政策變更"),
+    ("10", "重大契約、合作或新產品量產"),
+    ("11", "資本變動或合併收購"),
+    ("12", "說明會或未申報資訊發布"),
+    ("13", "財務預測差異重大"),
+    ("14", "股利政策異動或延遲"),
+    ("15", "大額投資計畫"),
+    ("16", "增資或債券計畫變動"),
+    ("17", "股東會召開通知"),
+    ("18", "股東會重要決議"),
+    ("19", "舞弊、掏空或主管遭羈押"),
+    ("20", "資產交易或衍生損失重大"),
+    ("21", "經理人或董事競業行為"),
+    ("22", "背書保證達標準"),
+    ("23", "資金貸與達標準"),
+    ("24", "私募證券交易"),
+    ("25", "主要客戶或供應商終止往來"),
+    ("26", "災難、罷工、資安等重大事件"),
+    ("27", "與銀行協商結果確定"),
+    ("28", "關係人或債務人信用異常"),
+    ("29", "內控聲明或審查報告"),
+    ("30", "財報錯誤或遭保留意見"),
+    ("31", "財報提報或自結資訊異動"),
+    ("32", "股票集中保管不足"),
+    ("33", "股權變動通知"),
+    ("34", "董監事遭停止職權"),
+    ("35", "公司買回股份"),
+    ("36", "減資或面額異動作業"),
+    ("37", "上市承諾未履行"),
+    ("38", "公開收購申報或通知"),
+    ("40", "暫停或恢復交易"),
+    ("41", "控股公司持股變動"),
+    ("42", "終止上市或改列申請"),
+    ("43", "重大捐贈"),
+    ("44", "委員會反對或董事會逾越建議"),
+    ("45", "增資由特定人認購"),
+    ("46", "子公司達終止上市標準或營收為零"),
+    ("47", "海外財報與台灣準則差異"),
+    ("48", "特定營業細則情事"),
+    ("49", "子公司控制力喪失或持股下降"),
+    ("50", "子公司海外掛牌相關事項"),
+    ("51", "其他重大決策或影響股價事件"),
+];
@@ -0,0 +1,697 @@
+//! MOPS 重大訊息 HTML／文字回應的解析。
+
+use anyhow::Result;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementDetail, DetailItem};
+
+/// 列表頁每一列隱藏欄位 `h<列號><欄位序號>` 的欄位序號定義
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenField {
+    /// 0：發言日期 (YYYYMMDD)
+    SpokeDate,
+    /// 1：發言時間 (HHMMSS)
+    SpokeTime,
+    /// 2：公司代號
+    CompanyCode,
+    /// 3：公司名稱
+    CompanyName,
+    /// 4：發言序號，查詢明細頁時使用
+    SequenceNumber,
+    /// 5：主旨
+    Subject,
+    /// 6：條款代號
+    ClauseCode,
+    /// 7：事實發生日 (YYYYMMDD)
+    FactOccurrenceDate,
+    /// 8：說明 (詳細內容)
+    Detail,
+}
+
+impl HiddenField {
+    /// 所有已定義的欄位，依欄位序號排列
+    pub const ALL: [HiddenField; 9] = [
+        Self::SpokeDate,
+        Self::SpokeTime,
+        Self::CompanyCode,
+        Self::CompanyName,
+        Self::SequenceNumber,
+        Self::Subject,
+        Self::ClauseCode,
+        Self::FactOccurrenceDate,
+        Self::Detail,
+    ];
+
+    /// 欄位序號
+    pub fn index(self) -> u8 {
+        Self::ALL.iter().position(|&field| field == self).expect("ALL 包含所有欄位") as u8
+    }
+
+    /// 依欄位序號對應
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// 依欄位名稱 `h<列號><欄位序號>` 對應，例如 `h166` 為第 16 列的條款代號
+    pub fn from_name(name: &str) -> Option<Self> {
+        split_hidden_name(name).and_then(|(_, index)| Self::from_index(index))
+    }
+}
+
+/// 單一資料列隱藏欄位中擷取出的詳細資料
+#[derive(Debug, Clone, Default)]
+pub struct DetailInfo {
+    pub spoke_date: Option<RocDate>,
+    /// 發言時間，已轉為 HH:MM:SS
+    pub spoke_time: Option<String>,
+    pub company_code: Option<String>,
+    pub company_name: Option<String>,
+    pub subject: Option<String>,
+    pub detail_content: Option<String>,
+    pub announcement_type: Option<String>,
+    pub fact_date: Option<RocDate>,
+    pub clause_code: Option<String>,
+    pub fact_occurrence_date: Option<RocDate>,
+    pub sequence_number: Option<String>,
+    /// 未定義的隱藏欄位
+    pub extra: BTreeMap<String, String>,
+    pub raw_html: String,
+}
+
+// MOPS 在請求過於密集時回傳的限流頁面關鍵字
+const THROTTLE_MARKERS: [&str; 2] = ["查詢過於頻繁", "FOR SECURITY REASONS"];
+
+// 查詢結果為空時 MOPS 的完整提示句：單日列表與公司歷史查詢；
+// 只比對片段 (例如「查無」) 會把錯誤或維護頁面誤判為查無資料
+const NO_DATA_MARKERS: [&str; 2] = ["沒有找到重大訊息", "查無所需資料"];
+
+// 單日列表資料表格必須具備的表頭，缺少時視為網站結構已變更
+const LIST_REQUIRED_HEADERS: [&str; 2] = ["公司代號", "主旨"];
+
+/// 回應是否為 MOPS 的限流頁面
+pub fn is_throttled(html: &str) -> bool {
+    THROTTLE_MARKERS.iter().any(|marker| html.contains(marker))
+}
+
+// 回應是否為查無資料的頁面
+fn is_no_data(html: &str) -> bool {
+    NO_DATA_MARKERS.iter().any(|marker| html.contains(marker))
+}
+
+/// 列表頁的解析結果分類
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ParseStatus {
+    /// 所有資料列皆已解析
+    Complete,
+    /// 查詢結果確實沒有重大訊息
+    NoAnnouncements,
+    /// 收到限流頁面，內容不可信
+    Throttled,
+    /// 找不到資料表格、缺少必要表頭或所有資料列都無法解析，網站結構可能已變更
+    UnexpectedLayout { reason: String },
+    /// 部分資料列無法解析而略過
+    Partial { skipped: usize },
+}
+
+/// 無法解析而略過的資料列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowWarning {
+    /// 資料表格中的列序 (由 1 起算，含表頭列)
+    pub row: usize,
+    pub message: String,
+}
+
+/// 列表頁的解析結果：分類、解析出的公告與略過的資料列
+#[derive(Debug, Clone)]
+pub struct ParseOutcome {
+    pub status: ParseStatus,
+    pub announcements: Vec<Announcement>,
+    pub warnings: Vec<RowWarning>,
+}
+
+/// 網站結構已變更，無法解析回應
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutChangedError(pub String);
+
+impl fmt::Display for LayoutChangedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MOPS 網站結構可能已變更: {}", self.0)
+    }
+}
+
+impl std::error::Error for LayoutChangedError {}
+
+impl ParseOutcome {
+    // 依解析出的公告與略過的列決定分類；沒有找到資料表格時由呼叫端處理
+    fn from_rows(announcements: Vec<Announcement>, warnings: Vec<RowWarning>) -> Self {
+        let status = match warnings.len() {
+            0 if announcements.is_empty() => ParseStatus::NoAnnouncements,
+            0 => ParseStatus::Complete,
+            skipped if announcements.is_empty() => {
+                ParseStatus::UnexpectedLayout { reason: format!("資料表格的 {} 列皆無法解析", skipped) }
+            }
+            skipped => ParseStatus::Partial { skipped },
+        };
+        Self { status, announcements, warnings }
+    }
+
+    // 沒有找到資料表格：限流、查無資料或結構變更
+    fn without_table(html: &str, reason: String) -> Self {
+        let status = if is_throttled(html) {
+            ParseStatus::Throttled
+        } else if is_no_data(html) {
+            ParseStatus::NoAnnouncements
+        } else {
+            ParseStatus::UnexpectedLayout { reason }
+        };
+        Self { status, announcements: Vec::new(), warnings: Vec::new() }
+    }
+
+    /// 輸出解析分類與略過的資料列
+    pub fn print(&self) {
+        match &self.status {
+            ParseStatus::Complete => {}
+            ParseStatus::NoAnnouncements => println!("查無重大訊息公告"),
+            ParseStatus::Throttled => println!("⚠️  收到限流頁面 (查詢過於頻繁)"),
+            ParseStatus::UnexpectedLayout { reason } => println!("❌ 無法辨識的頁面結構: {}", reason),
+            ParseStatus::Partial { skipped } => {
+                println!("⚠️  部分解析: {} 筆成功，{} 列無法解析已略過", self.announcements.len(), skipped);
+            }
+        }
+        for warning in &self.warnings {
+            println!("    第 {} 列: {}", warning.row, warning.message);
+        }
+    }
+
+    /// 取出解析出的公告；結構變更回傳 [`LayoutChangedError`]，限流頁面亦視為錯誤
+    pub fn into_announcements(self) -> Result<Vec<Announcement>> {
+        match self.status {
+            ParseStatus::UnexpectedLayout { reason } => Err(LayoutChangedError(reason).into()),
+            ParseStatus::Throttled => anyhow::bail!("收到限流頁面，請稍後再試"),
+            _ => Ok(self.announcements),
+        }
+    }
+}
+
+// 表頭文字去除空白 (含全形空白)，避免排版差異影響比對
+fn header_text(th: ElementRef) -> String {
+    th.text().collect::<String>().chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// 解析 MOPS 當日重大訊息列表的 HTML 回應
+pub fn parse_response(html: &str) -> ParseOutcome {
+    let document = Html::parse_document(html);
+    let mut announcements = Vec::new();
+    let mut warnings = Vec::new();
+
+    // 尋找包含 tblHead 表頭的資料表格
+    let table_selector = Selector::parse("table").unwrap();
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("td").unwrap();
+    let header_selector = Selector::parse("th.tblHead").unwrap();
+    let input_selector = Selector::parse("input[type='hidden']").unwrap();
+
+    let Some(table) = document.select(&table_selector).find(|table| table.select(&header_selector).next().is_some()) else {
+        return ParseOutcome::without_table(html, "找不到含 th.tblHead 表頭的資料表格".to_string());
+    };
+
+    let headers: Vec<String> = table.select(&header_selector).map(header_text).collect();
+    let missing: Vec<&str> = LIST_REQUIRED_HEADERS
+        .iter()
+        .copied()
+        .filter(|name| !headers.iter().any(|h| h.contains(name)))
+        .collect();
+    if !missing.is_empty() {
+        let reason = format!("資料表格缺少表頭 {} (實際表頭: {})", missing.join(", "), headers.join(", "));
+        return ParseOutcome::without_table(html, reason);
+    }
+
+    for (index, row) in table.select(&row_selector).enumerate() {
+        let cells: Vec<String> = row
+            .select(&cell_selector)
+            .map(|cell| cell.text().collect::<String>().trim().to_string())
+            .collect();
+
+        // 跳過空行或只有少於5個欄位的行
+        if cells.len() < 5 || cells[0].is_empty() {
+            continue;
+        }
+
+        let date_text = cells[0].trim();
+        let time = cells[1].trim().to_string();
+        let company_code = cells[2].trim().to_string();
+        let company_name = cells[3].trim().to_string();
+        let title = cells[4].trim().to_string();
+
+        // 日期無法辨識或缺少公司代號、主旨的列記錄為警告後略過
+        let problem = match RocDate::parse(date_text) {
+            None => Err(format!("無法辨識的日期格式: {}", date_text)),
+            Some(_) if company_code.is_empty() => Err(format!("缺少公司代號 ({})", title)),
+            Some(_) if title.is_empty() => Err(format!("缺少主旨 ({})", company_code)),
+            Some(date) => Ok(date),
+        };
+        let date = match problem {
+            Ok(date) => date,
+            Err(message) => {
+                warnings.push(RowWarning { row: index + 1, message });
+                continue;
+            }
+        };
+
+        // 提取詳細資料；發言時間欄為空時改用隱藏欄位
+        let detail = extract_detail_info(&row, &input_selector);
+        let time = if time.is_empty() { detail.spoke_time.clone().unwrap_or_default() } else { time };
+
+        // 移除標題中的換行符號，用空格取代
+        let clean_title = title.replace(['\n', '\r'], " ");
+
+        announcements.push(Announcement {
+            id: None,
+            announcement_id: None,
+            content_hash: None,
+            revision: None,
+            company_code,
+            company_name,
+            title: clean_title,
+            announced_at: date.with_time(&time),
+            date,
+            time,
+            detail_content: detail.detail_content,
+            announcement_type: detail.announcement_type,
+            fact_date: detail.fact_date,
+            fact_occurrence_date: detail.fact_occurrence_date,
+            clause_code: detail.clause_code,
+            raw_html: Some(detail.raw_html),
+            created_at: Some(chrono::Utc::now()),
+            query_date: None, // 將在 main 函數中設定
+            market: None,
+            sequence_number: detail.sequence_number,
+            detail: None,
+            extra: detail.extra,
+        });
+    }
+
+    ParseOutcome::from_rows(announcements, warnings)
+}
+
+/// 解析 MOPS 單一公司歷史重大訊息 (t05st01) 的查詢結果
+///
+/// 欄位依表頭名稱對應（公司代號、公司名稱、發言日期、發言時間、主旨），
+/// 回應中沒有公司代號欄位時使用查詢的 `company_code`。
+pub fn parse_company_history(html: &str, company_code: &str) -> ParseOutcome {
+    let document = Html::parse_document(html);
+    let mut announcements = Vec::new();
+    let mut warnings = Vec::new();
+
+    let table_selector = Selector::parse("table").unwrap();
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("td").unwrap();
+    let header_selector = Selector::parse("th.tblHead").unwrap();
+    let input_selector = Selector::parse("input[type='hidden']").unwrap();
+    let button_selector = Selector::parse("input[type='button'], button").unwrap();
+    let seq_no_pattern = Regex::new(r#"seq_no\.value\s*=\s*['"]?(\d+)"#).unwrap();
+
+    let mut found_table = false;
+    for table in document.select(&table_selector) {
+        let headers: Vec<String> = table.select(&header_selector).map(header_text).collect();
+        let column = |names: &[&str]| headers.iter().position(|h| names.iter().any(|n| h.contains(n)));
+
+        let (Some(date_col), Some(title_col)) = (column(&["發言日期"]), column(&["主旨"])) else {
+            continue;
+        };
+        found_table = true;
+        let time_col = column(&["發言時間"]);
+        let code_col = column(&["公司代號"]);
+        let name_col = column(&["公司名稱", "公司簡稱"]);
+
+        for (index, row) in table.select(&row_selector).enumerate() {
+            let cells: Vec<String> = row
+                .select(&cell_selector)
+                .map(|cell| cell.text().collect::<String>().trim().to_string())
+                .collect();
+            let cell = |col: Option<usize>| col.and_then(|c| cells.get(c)).cloned().unwrap_or_default();
+
+            // 表頭列與版面用的空白列沒有 td
+            let Some(date_text) = cells.get(date_col).filter(|d| !d.is_empty()) else {
+                continue;
+            };
+            let Some(date) = RocDate::parse(date_text) else {
+                warnings.push(RowWarning { row: index + 1, message: format!("無法辨識的日期格式: {}", date_text) });
+                continue;
+            };
+            let title = cell(Some(title_col)).replace(['\n', '\r'], " ");
+            if title.is_empty() {
+                warnings.push(RowWarning { row: index + 1, message: format!("缺少主旨 ({})", date_text) });
+                continue;
+            }
+            let code = Some(cell(code_col)).filter(|c| !c.is_empty()).unwrap_or_else(|| company_code.to_string());
+
+            let mut detail = extract_detail_info(&row, &input_selector);
+            let time = Some(cell(time_col)).filter(|t| !t.is_empty()).or(detail.spoke_time.clone()).unwrap_or_default();
+
+            // 序號可能只出現在「詳細資料」按鈕的 onclick 中
+            if detail.sequence_number.is_none() {
+                detail.sequence_number = row
+                    .select(&button_selector)
+                    .filter_map(|button| button.value().attr("onclick"))
+                    .find_map(|onclick| seq_no_pattern.captures(onclick).map(|caps| caps[1].to_string()));
+            }
+
+            announcements.push(Announcement {
+                id: None,
+                announcement_id: None,
+                content_hash: None,
+                revision: None,
+                company_code: code,
+                company_name: cell(name_col),
+                title,
+                announced_at: date.with_time(&time),
+                date,
+                time,
+                detail_content: detail.detail_content,
+                announcement_type: detail.announcement_type,
+                fact_date: detail.fact_date,
+                fact_occurrence_date: detail.fact_occurrence_date,
+                clause_code: detail.clause_code,
+                raw_html: Some(detail.raw_html),
+                created_at: Some(chrono::Utc::now()),
+                query_date: None,
+                market: None,
+                sequence_number: detail.sequence_number,
+                detail: None,
+                extra: detail.extra,
+            });
+        }
+        break; // 找到資料表格後就停止
+    }
+
+    if !found_table {
+        return ParseOutcome::without_table(html, "找不到含「發言日期」與「主旨」表頭的資料表格".to_string());
+    }
+    ParseOutcome::from_rows(announcements, warnings)
+}
+
+/// 從資料列的隱藏欄位擷取詳細內容、條款代號與事實發生日
+///
+/// 欄位名稱為 `h<列號><欄位序號>`，欄位序號為名稱的最後一碼，依 [`HiddenField`] 對應；
+/// 不符合此格式或序號未定義的欄位保留於 `extra`。
+pub fn extract_detail_info(row: &scraper::ElementRef, input_selector: &Selector) -> DetailInfo {
+    let mut info = DetailInfo {
+        // 保存原始 HTML
+        raw_html: row.html(),
+        ..Default::default()
+    };
+
+    for input in row.select(input_selector) {
+        let (Some(name), Some(value)) = (input.value().attr("name"), input.value().attr("value")) else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+
+        let Some(field) = HiddenField::from_name(name) else {
+            info.extra.insert(extra_key(name), value.to_string());
+            continue;
+        };
+        match field {
+            HiddenField::SpokeDate => info.spoke_date = RocDate::parse(value),
+            HiddenField::SpokeTime => info.spoke_time = Some(format_spoke_time(value)),
+            HiddenField::CompanyCode => info.company_code = Some(value.to_string()),
+            HiddenField::CompanyName => info.company_name = Some(value.to_string()),
+            HiddenField::SequenceNumber => info.sequence_number = Some(value.to_string()),
+            HiddenField::Subject => info.subject = Some(value.to_string()),
+            HiddenField::ClauseCode => info.clause_code = Some(value.to_string()),
+            // YYYYMMDD 格式
+            HiddenField::FactOccurrenceDate => info.fact_occurrence_date = RocDate::parse(value),
+            HiddenField::Detail => {
+                // 嘗試提取事實發生日（從詳細內容中）
+                if let Some(fact_line) = value.lines().find(|line| line.contains("事實發生日")) {
+                    if let Some(date_part) = fact_line.split('：').nth(1) {
+                        info.fact_date = RocDate::parse(date_part);
+                    }
+                }
+
+                // 嘗試提取公告類型
+                if let Some(type_line) = value.lines().find(|line| line.contains("符合條款")) {
+                    info.announcement_type = Some(type_line.trim().to_string());
+                }
+
+                info.detail_content = Some(value.to_string());
+            }
+        }
+    }
+
+    info
+}
+
+// 未定義的 h 欄位去除列號 (h09、h19 皆為 h9)，讓不同列的同一欄位使用相同的鍵；其他欄位保留原名
+fn extra_key(name: &str) -> String {
+    match split_hidden_name(name) {
+        Some((_, index)) => format!("h{}", index),
+        None => name.to_string(),
+    }
+}
+
+// 將 h<列號><欄位序號> 拆成列號與欄位序號
+fn split_hidden_name(name: &str) -> Option<(&str, u8)> {
+    let digits = name.strip_prefix('h')?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (row, index) = digits.split_at(digits.len() - 1);
+    Some((row, index.parse().ok()?))
+}
+
+// 發言時間 HHMMSS 轉為 HH:MM:SS，其他格式保留原值
+fn format_spoke_time(value: &str) -> String {
+    if value.len() == 6 && value.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}:{}:{}", &value[..2], &value[2..4], &value[4..])
+    } else {
+        value.to_string()
+    }
+}
+
+// 明細頁的欄位名稱格：th 或 class 含 tblHead 的 td
+fn is_header_cell(cell: &ElementRef) -> bool {
+    cell.value().name() == "th" || cell.value().classes().any(|c| c.contains("tblHead"))
+}
+
+/// 解析重大訊息明細頁 (t05st01)，擷取發言人、更正註記與說明欄的編號項目
+pub fn parse_detail_page(html: &str) -> Result<AnnouncementDetail> {
+    let document = Html::parse_document(html);
+    let row_selector = Selector::parse("tr").unwrap();
+    let mut fields = BTreeMap::new();
+
+    for row in document.select(&row_selector) {
+        // 只取本列的直接子格，避免巢狀表格重複計算
+        let cells: Vec<ElementRef> = row
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+            .collect();
+
+        // 明細頁為「欄位名稱格 + 內容格」成對排列，一列可能有多組
+        let mut i = 0;
+        while i + 1 < cells.len() {
+            if is_header_cell(&cells[i]) && !is_header_cell(&cells[i + 1]) {
+                let label = cells[i].text().collect::<String>();
+                let label = label.trim().trim_end_matches(['：', ':']).trim().to_string();
+                let value = cells[i + 1].text().collect::<String>().trim().to_string();
+                if !label.is_empty() {
+                    fields.entry(label).or_insert(value);
+                }
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    if fields.is_empty() {
+        anyhow::bail!("明細頁中找不到資料欄位，網站結構可能已變更");
+    }
+
+    let field = |label: &str| fields.get(label).filter(|v| !v.is_empty()).cloned();
+
+    // 更正公告：主旨含「更正」，或明細頁有「是否為更正」之類的欄位且為「是」
+    let is_correction = field("主旨").is_some_and(|subject| subject.contains("更正"))
+        || fields.iter().any(|(label, value)| {
+            label.contains("更正") && matches!(value.trim(), "是" | "Y" | "y")
+        });
+
+    Ok(AnnouncementDetail {
+        spokesperson: field("發言人"),
+        spokesperson_title: field("發言人職稱"),
+        spokesperson_phone: field("發言人電話"),
+        is_correction,
+        items: field("說明").map(|text| parse_detail_items(&text)).unwrap_or_default(),
+        fields,
+        fetched_at: Some(chrono::Utc::now()),
+    })
+}
+
+/// 將說明欄拆成編號項目，未編號的行併入上一項
+pub fn parse_detail_items(text: &str) -> Vec<DetailItem> {
+    let item_start = Regex::new(r"^\s*(\d{1,2})\s*[.．、]\s*(.*)$").unwrap();
+    let mut items: Vec<DetailItem> = Vec::new();
+
+    for line in text.lines() {
+        if let Some(caps) = item_start.captures(line) {
+            let number = caps[1].parse().unwrap_or(0);
+            let rest = caps[2].trim();
+
+            // 「事實發生日:114/08/15」之類的短標籤拆成 label 與內容
+            let split = rest
+                .find(['：', ':'])
+                .filter(|&pos| rest[..pos].chars().count() <= 20);
+            let (label, content) = match split {
+                Some(pos) => {
+                    let separator_len = rest[pos..].chars().next().map_or(1, char::len_utf8);
+                    (Some(rest[..pos].trim().to_string()), rest[pos + separator_len..].trim().to_string())
+                }
+                None => (None, rest.to_string()),
+            };
+            items.push(DetailItem { number, label, content });
+        } else if let Some(last) = items.last_mut() {
+            let line = line.trim();
+            if !line.is_empty() {
+                if !last.content.is_empty() {
+                    last.content.push('\n');
+                }
+                last.content.push_str(line);
+            }
+        }
+    }
+
+    items
+}
+
+/// 解析純文字格式的重大訊息列表（每行一筆）
+pub fn parse_text_content(content: &str) -> Result<Vec<Announcement>> {
+    let mut announcements = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+
+    // 找到資料開始的行（跳過標題行）
+    let mut data_started = false;
+
+    for line in lines {
+        let line = line.trim();
+
+        // 跳過空行和標題行
+        if line.is_empty() || line.contains("公司當日重大訊息之詳細內容") ||
+           line.contains("代號") || line.contains("----") {
+            if line.contains("----") {
+                data_started = true;
+            }
+            continue;
+        }
+
+        if !data_started {
+            continue;
+        }
+
+        // 解析資料行
+        // 格式看起來是: 日期 時間 公司名稱 標題 代號
+        if let Some(announcement) = parse_announcement_line(line) {
+            announcements.push(announcement);
+        }
+    }
+
+    Ok(announcements)
+}
+
+/// 解析單行文字格式的重大訊息，例如 `114/08/17 07:00:03 世界健身-KY 公告... 2762`
+pub fn parse_announcement_line(line: &str) -> Option<Announcement> {
+    // 使用正則表達式或字串分割來解析行
+    // 格式: 114/08/17 07:00:03 世界健身-KY 公告本公司名稱... 2762
+
+    // 先嘗試找到日期模式 (114/08/17)
+    if let Some(date_end) = line.find(' ') {
+        let date = RocDate::parse(&line[..date_end])?;
+        let rest = &line[date_end..].trim_start();
+
+        // 找到時間模式 (07:00:03)
+        if let Some(time_end) = rest.find(' ') {
+            let time = &rest[..time_end];
+            let rest = &rest[time_end..].trim_start();
+
+            // 找到最後的數字（公司代號）
+            if let Some(last_space) = rest.rfind(' ') {
+                let company_code = &rest[last_space..].trim();
+                let content = &rest[..last_space].trim();
+
+                // 找到公司名稱和標題的分界點
+                // 假設公司名稱不會太長，通常在前20個字元內
+                let mut company_name = String::new();
+                let mut title = String::new();
+
+                // 簡單的分割邏輯：找到第一個中文標點或特定關鍵字
+                if let Some(split_pos) = content.find("公告") {
+                    if split_pos > 0 {
+                        company_name = content[..split_pos].trim().to_string();
+                        title = content[split_pos..].trim().to_string();
+                    } else {
+                        // 如果找不到合適的分割點，使用前面的部分作為公司名稱
+                        let words: Vec<&str> = content.split_whitespace().collect();
+                        if !words.is_empty() {
+                            company_name = words[0].to_string();
+                            title = words[1..].join(" ");
+                        }
+                    }
+                } else {
+                    // 備用分割邏輯
+                    let words: Vec<&str> = content.split_whitespace().collect();
+                    if !words.is_empty() {
+                        company_name = words[0].to_string();
+                        title = words[1..].join(" ");
+                    }
+                }
+
+                return Some(Announcement {
+                    id: None,
+                    announcement_id: None,
+                    content_hash: None,
+                    revision: None,
+                    company_code: company_code.to_string(),
+                    company_name,
+                    title,
+                    announced_at: date.with_time(time),
+                    date,
+                    time: time.to_string(),
+                    detail_content: None,
+                    announcement_type: None,
+                    fact_date: None,
+                    fact_occurrence_date: None,
+                    clause_code: None,
+                    raw_html: None,
+                    created_at: Some(chrono::Utc::now()),
+                    query_date: None,
+                    market: None,
+                    sequence_number: None,
+                    detail: None,
+                    extra: BTreeMap::new(),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+
+/// 格式化事實發生日 (YYYYMMDD -> YYYY-MM-DD)
+pub fn format_fact_occurrence_date(date_str: &str) -> String {
+    match RocDate::parse(date_str) {
+        Some(date) => date.to_gregorian_string(),
+        // 如果不是可辨識的格式，直接返回原始值
+        None => date_str.to_string(),
+    }
+}
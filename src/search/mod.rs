@@ -0,0 +1,564 @@
+//! 公告標題與內容的全文檢索。
+//!
+//! 以 tantivy 建立嵌入式倒排索引，標題與內容 (`detail_content`) 以 [`tokenizer::CjkBigramTokenizer`] 斷詞，
+//! 結果依 BM25 相關性排序 (標題權重加倍)。查詢語法：
+//!
+//! - 以空白分隔的詞須全部符合：`私募 增資`
+//! - 多字詞即片語，須連續出現：`私募股權`、`"esg report"`
+//! - `OR` 表示任一符合、`-` 排除：`私募 OR 增資 -澄清`，可用括號分組
+//! - 指定欄位：`title:合併`、`detail:私募`
+//!
+//! 中文至少需要兩個字，單一中文字只會比對前後都不是中文的單字。
+//!
+//! 索引由 [`SearchIndex::refresh`] 以游標逐頁讀取儲存後端建立並移除已刪除的公告。之後查詢時若超過同步間隔，
+//! [`SearchIndex::refresh_in_background`] 在背景只讀取擷取時間 (`created_at`) 晚於上次同步的公告，
+//! 每隔 [`FULL_REFRESH_INTERVAL`] 才重新比對全部公告；同步期間查詢照常使用目前的索引，不需等待。
+//! 篩選欄位 (公司、市場別、條款代號、日期、發言時間) 與公告內容也寫入索引，篩選條件與 [`AnnouncementQuery`] 相同，
+//! 檢索結果直接由索引讀出，不需查詢儲存後端。
+
+pub mod tokenizer;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tantivy::collector::{Count, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, QueryParserError, RangeQuery, TermQuery, TermSetQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::tokenizer::TextAnalyzer;
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument, Term};
+use utoipa::ToSchema;
+
+use crate::date::RocDate;
+use crate::model::Announcement;
+use crate::store::{AnnouncementQuery, AnnouncementStore, Cursor};
+use tokenizer::{CjkBigramTokenizer, CJK_TOKENIZER};
+
+/// 距離上次同步超過此時間時，查詢會在背景增量同步索引
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 重新比對全部公告 (移除已刪除的公告、補上沒有擷取時間的公告) 的間隔
+pub const FULL_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+// 增量同步往前多讀的時間：created_at 是擷取時間，抓取明細頁時可能數分鐘後才寫入
+const WATERMARK_OVERLAP: chrono::Duration = chrono::Duration::minutes(30);
+
+// 同步時每次向儲存後端讀取的筆數
+const REFRESH_PAGE_SIZE: u64 = 500;
+
+// 索引寫入器的記憶體上限
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+// 標題相關性權重
+const TITLE_BOOST: f32 = 2.0;
+
+// 內容摘要的最大字數
+const SNIPPET_CHARS: usize = 120;
+
+const HIGHLIGHT_PREFIX: &str = "<mark>";
+const HIGHLIGHT_POSTFIX: &str = "</mark>";
+
+#[derive(Clone, Copy)]
+struct Fields {
+    announcement_id: Field,
+    fingerprint: Field,
+    data: Field,
+    title: Field,
+    detail: Field,
+    company_code: Field,
+    market: Field,
+    clause_code: Field,
+    date: Field,
+    query_date: Field,
+    fact_date: Field,
+    fact_occurrence_date: Field,
+    time: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(CJK_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
+    let fields = Fields {
+        announcement_id: builder.add_text_field("announcement_id", STRING | STORED),
+        fingerprint: builder.add_text_field("fingerprint", STORED),
+        data: builder.add_text_field("data", STORED),
+        title: builder.add_text_field("title", text.clone()),
+        detail: builder.add_text_field("detail", text),
+        company_code: builder.add_text_field("company_code", STRING),
+        market: builder.add_text_field("market", STRING),
+        clause_code: builder.add_text_field("clause_code", STRING),
+        date: builder.add_text_field("date", STRING),
+        query_date: builder.add_text_field("query_date", STRING),
+        fact_date: builder.add_text_field("fact_date", STRING),
+        fact_occurrence_date: builder.add_text_field("fact_occurrence_date", STRING),
+        time: builder.add_text_field("time", STRING),
+    };
+    (builder.build(), fields)
+}
+
+// 索引中保存的公告 JSON，檢索結果直接由此讀出
+fn stored_json(announcement: &Announcement) -> Result<String> {
+    let mut announcement = announcement.clone();
+    announcement.raw_html = None;
+    announcement.ensure_identity();
+    Ok(serde_json::to_string(&announcement)?)
+}
+
+// 索引內容的指紋，保存的公告任一欄位變動時重新寫入
+fn fingerprint(json: &str) -> String {
+    Sha256::digest(json.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn indexed_id(announcement: &Announcement) -> String {
+    announcement.announcement_id.clone().unwrap_or_else(|| announcement.stable_id())
+}
+
+/// 一次同步的結果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshSummary {
+    /// 新增或內容變動而重新寫入的公告數
+    pub indexed: usize,
+    /// 已從儲存後端刪除而移出索引的公告數
+    pub removed: usize,
+    /// 同步後索引中的公告數
+    pub total: usize,
+}
+
+// 已寫入索引的公告指紋與同步進度；只在比對與更新時短暫鎖定，讀取儲存後端期間不持有
+#[derive(Default)]
+struct SyncState {
+    fingerprints: HashMap<String, String>,
+    refreshed_at: Option<Instant>,
+    full_refreshed_at: Option<Instant>,
+    // 下次增量同步讀取此時間之後擷取的公告
+    watermark: Option<DateTime<Utc>>,
+}
+
+impl SyncState {
+    fn is_stale(&self, interval: Duration) -> bool {
+        self.refreshed_at.is_none_or(|refreshed_at| refreshed_at.elapsed() >= interval)
+    }
+}
+
+/// 全文檢索索引
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    fields: Fields,
+    writer: Arc<Mutex<Option<IndexWriter>>>,
+    state: Mutex<SyncState>,
+    // 同一時間只執行一次同步，查詢不需取得
+    syncing: tokio::sync::Mutex<()>,
+    refresh_interval: Duration,
+}
+
+impl SearchIndex {
+    /// 建立記憶體中的索引，程式結束後即消失
+    pub fn in_memory() -> Self {
+        let (schema, fields) = schema();
+        Self::with_index(Index::create_in_ram(schema), fields, SyncState::default())
+            .expect("記憶體索引不需要讀取既有資料")
+    }
+
+    /// 開啟 (或建立) 目錄中的索引，重新啟動後只需同步有變動的公告
+    pub fn open_in_dir(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path).with_context(|| format!("無法建立索引目錄 {}", path.display()))?;
+        let (schema, fields) = schema();
+        let directory = MmapDirectory::open(path).with_context(|| format!("無法開啟索引目錄 {}", path.display()))?;
+        let index = Index::open_or_create(directory, schema).context("索引目錄的欄位與目前版本不符，請刪除後重建")?;
+
+        let mut state = SyncState::default();
+        let searcher = index.reader()?.searcher();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(0)?;
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc: TantivyDocument = store.get(doc_id)?;
+                let text = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                state.fingerprints.insert(text(fields.announcement_id), text(fields.fingerprint));
+            }
+        }
+        Self::with_index(index, fields, state)
+    }
+
+    fn with_index(index: Index, fields: Fields, state: SyncState) -> Result<Self> {
+        index.tokenizers().register(CJK_TOKENIZER, TextAnalyzer::from(CjkBigramTokenizer));
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        Ok(Self {
+            index,
+            reader,
+            fields,
+            writer: Arc::new(Mutex::new(None)),
+            state: Mutex::new(state),
+            syncing: tokio::sync::Mutex::new(()),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        })
+    }
+
+    /// 設定自動同步的間隔
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// 索引中的公告數
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 與儲存後端完整同步：寫入新增或變動的公告並移除已刪除的公告
+    pub async fn refresh(&self, store: &dyn AnnouncementStore) -> Result<RefreshSummary> {
+        let _syncing = self.syncing.lock().await;
+        self.sync(store, None).await
+    }
+
+    /// 增量同步：只讀取上次同步後擷取的公告；從未同步或距離上次完整同步超過 [`FULL_REFRESH_INTERVAL`] 時完整同步
+    pub async fn refresh_recent(&self, store: &dyn AnnouncementStore) -> Result<RefreshSummary> {
+        let _syncing = self.syncing.lock().await;
+        self.sync_recent(store).await
+    }
+
+    /// 距離上次同步超過間隔時，在背景執行 [`SearchIndex::refresh_recent`] 並立即返回；
+    /// 從未同步時 (例如記憶體索引的第一次查詢) 先完整同步再返回
+    pub async fn refresh_in_background(self: &Arc<Self>, store: &Arc<dyn AnnouncementStore>) -> Result<()> {
+        let (synced, stale) = {
+            let state = self.state()?;
+            (state.refreshed_at.is_some(), state.is_stale(self.refresh_interval))
+        };
+        if !synced {
+            let _syncing = self.syncing.lock().await;
+            // 等待期間其他查詢可能已完成第一次同步
+            if self.state()?.refreshed_at.is_none() {
+                self.sync(store.as_ref(), None).await?;
+            }
+            return Ok(());
+        }
+        if !stale {
+            return Ok(());
+        }
+
+        let (index, store) = (Arc::clone(self), Arc::clone(store));
+        tokio::spawn(async move {
+            // 已有同步在執行，或其他查詢觸發的同步剛完成時略過
+            let Ok(_syncing) = index.syncing.try_lock() else { return };
+            if !index.state.lock().is_ok_and(|state| state.is_stale(index.refresh_interval)) {
+                return;
+            }
+            if let Err(e) = index.sync_recent(store.as_ref()).await {
+                println!("❌ 全文索引同步失敗: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    fn state(&self) -> Result<std::sync::MutexGuard<'_, SyncState>> {
+        self.state.lock().map_err(|_| anyhow::anyhow!("索引同步狀態已損毀"))
+    }
+
+    // 呼叫端需持有 `syncing`
+    async fn sync_recent(&self, store: &dyn AnnouncementStore) -> Result<RefreshSummary> {
+        let since = {
+            let state = self.state()?;
+            let full_due = state.full_refreshed_at.is_none_or(|at| at.elapsed() >= FULL_REFRESH_INTERVAL);
+            state.watermark.filter(|_| !full_due)
+        };
+        self.sync(store, since.map(|watermark| watermark - WATERMARK_OVERLAP)).await
+    }
+
+    // 以游標逐頁讀取儲存後端並寫入變動的公告；`since` 為 None 時讀取全部並移除已刪除的公告。呼叫端需持有 `syncing`
+    async fn sync(&self, store: &dyn AnnouncementStore, since: Option<DateTime<Utc>>) -> Result<RefreshSummary> {
+        let started = Utc::now();
+        let mut summary = RefreshSummary::default();
+        let mut seen = HashSet::new();
+        let mut after = None;
+
+        loop {
+            let query = AnnouncementQuery { created_since: since, limit: Some(REFRESH_PAGE_SIZE), after, ..Default::default() };
+            let page = store.query(&query).await?;
+            let Some(last) = page.last() else { break };
+            after = Cursor::after(last);
+
+            let mut entries = Vec::with_capacity(page.len());
+            for announcement in &page {
+                let json = stored_json(announcement)?;
+                entries.push((indexed_id(announcement), fingerprint(&json), json, announcement));
+            }
+            let changed: Vec<_> = {
+                let state = self.state()?;
+                entries.into_iter().filter(|(id, fingerprint, ..)| state.fingerprints.get(id) != Some(fingerprint)).collect()
+            };
+            seen.extend(page.iter().map(indexed_id));
+
+            let documents = changed
+                .iter()
+                .map(|(id, fingerprint, json, announcement)| (id.clone(), self.document(announcement, id, fingerprint, json)))
+                .collect();
+            self.write(documents, Vec::new()).await?;
+            summary.indexed += changed.len();
+            let mut state = self.state()?;
+            for (id, fingerprint, ..) in changed {
+                state.fingerprints.insert(id, fingerprint);
+            }
+            drop(state);
+
+            if after.is_none() || (page.len() as u64) < REFRESH_PAGE_SIZE {
+                break;
+            }
+        }
+
+        if since.is_none() {
+            let removed: Vec<String> = {
+                let mut state = self.state()?;
+                let removed: Vec<String> = state.fingerprints.keys().filter(|id| !seen.contains(*id)).cloned().collect();
+                for id in &removed {
+                    state.fingerprints.remove(id);
+                }
+                removed
+            };
+            summary.removed = removed.len();
+            self.write(Vec::new(), removed).await?;
+        }
+
+        if summary.indexed > 0 || summary.removed > 0 {
+            self.commit().await?;
+        }
+
+        let mut state = self.state()?;
+        let now = Instant::now();
+        state.refreshed_at = Some(now);
+        if since.is_none() {
+            state.full_refreshed_at = Some(now);
+        }
+        state.watermark = Some(started);
+        summary.total = state.fingerprints.len();
+        Ok(summary)
+    }
+
+    fn document(&self, announcement: &Announcement, id: &str, fingerprint: &str, json: &str) -> TantivyDocument {
+        let f = self.fields;
+        let mut doc = TantivyDocument::default();
+        doc.add_text(f.announcement_id, id);
+        doc.add_text(f.fingerprint, fingerprint);
+        doc.add_text(f.data, json);
+        doc.add_text(f.title, &announcement.title);
+        if let Some(detail) = &announcement.detail_content {
+            doc.add_text(f.detail, detail);
+        }
+        doc.add_text(f.company_code, &announcement.company_code);
+        if let Some(market) = &announcement.market {
+            doc.add_text(f.market, market);
+        }
+        if let Some(clause_code) = &announcement.clause_code {
+            doc.add_text(f.clause_code, clause_code);
+        }
+        let dates = [
+            (f.date, Some(announcement.date)),
+            (f.query_date, announcement.query_date),
+            (f.fact_date, announcement.fact_date),
+            (f.fact_occurrence_date, announcement.fact_occurrence_date),
+        ];
+        for (field, date) in dates {
+            if let Some(date) = date {
+                doc.add_text(field, date.to_gregorian_string());
+            }
+        }
+        doc.add_text(f.time, &announcement.time);
+        doc
+    }
+
+    // 在 blocking 執行緒上寫入索引，避免阻塞 async runtime
+    async fn write(&self, documents: Vec<(String, TantivyDocument)>, removed: Vec<String>) -> Result<()> {
+        if documents.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+        let (index, writer, id_field) = (self.index.clone(), Arc::clone(&self.writer), self.fields.announcement_id);
+        tokio::task::spawn_blocking(move || {
+            let mut writer = writer.lock().map_err(|_| anyhow::anyhow!("索引寫入器已損毀"))?;
+            if writer.is_none() {
+                *writer = Some(index.writer(WRITER_MEMORY_BYTES)?);
+            }
+            let writer = writer.as_mut().expect("寫入器已建立");
+            for id in removed {
+                writer.delete_term(Term::from_field_text(id_field, &id));
+            }
+            for (id, document) in documents {
+                writer.delete_term(Term::from_field_text(id_field, &id));
+                writer.add_document(document)?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn commit(&self) -> Result<()> {
+        let (writer, reader) = (Arc::clone(&self.writer), self.reader.clone());
+        tokio::task::spawn_blocking(move || {
+            let mut writer = writer.lock().map_err(|_| anyhow::anyhow!("索引寫入器已損毀"))?;
+            if let Some(writer) = writer.as_mut() {
+                writer.commit()?;
+            }
+            reader.reload()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// 解析檢索語法，未指定欄位的詞同時比對標題與內容
+    pub fn parse(&self, text: &str) -> std::result::Result<Box<dyn Query>, QueryParserError> {
+        let mut parser = QueryParser::for_index(&self.index, vec![self.fields.title, self.fields.detail]);
+        parser.set_conjunction_by_default();
+        parser.set_field_boost(self.fields.title, TITLE_BOOST);
+        parser.parse_query(text)
+    }
+
+    /// 依相關性排序的一頁結果，公告內容由索引讀出，反映上次同步時的資料
+    ///
+    /// `filter` 的分頁、游標、標題關鍵字 (`search`) 與擷取時間欄位不適用，分頁改用 `offset` 與 `limit`。
+    pub fn search(&self, query: &dyn Query, filter: &AnnouncementQuery, offset: usize, limit: usize) -> Result<SearchResults> {
+        let mut clauses = vec![(Occur::Must, query.box_clone())];
+        clauses.extend(self.filter_clauses(filter).into_iter().map(|clause| (Occur::Must, clause)));
+        let combined = BooleanQuery::new(clauses);
+
+        let searcher = self.reader.searcher();
+        let (top_docs, total) = searcher.search(&combined, &(TopDocs::with_limit(limit).and_offset(offset), Count))?;
+
+        let highlighter = Highlighter::new(&searcher, query, self.fields)?;
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let Some(json) = doc.get_first(self.fields.data).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let announcement: Announcement = serde_json::from_str(json)?;
+            let highlights = highlighter.highlight(&announcement);
+            hits.push(SearchHit { announcement, score, highlights });
+        }
+
+        Ok(SearchResults { total: total as u64, hits })
+    }
+
+    // 與 AnnouncementQuery 相同的篩選條件
+    fn filter_clauses(&self, filter: &AnnouncementQuery) -> Vec<Box<dyn Query>> {
+        let f = self.fields;
+        let mut clauses: Vec<Box<dyn Query>> = Vec::new();
+        let terms = |field: Field, values: &[String]| -> Box<dyn Query> {
+            Box::new(TermSetQuery::new(values.iter().map(|value| Term::from_field_text(field, value))))
+        };
+        let range = |field: Field, from: Option<String>, to: Option<String>| -> Box<dyn Query> {
+            let name = self.index.schema().get_field_name(field).to_string();
+            Box::new(RangeQuery::new_str_bounds(name, inclusive(&from), inclusive(&to)))
+        };
+        let day = |date: Option<RocDate>| date.map(|d| d.to_gregorian_string());
+
+        if !filter.companies.is_empty() {
+            clauses.push(terms(f.company_code, &filter.companies));
+        }
+        if let Some(market) = filter.market.as_deref().filter(|m| !m.is_empty() && *m != "all") {
+            clauses.push(Box::new(TermQuery::new(Term::from_field_text(f.market, market), IndexRecordOption::Basic)));
+        }
+        if !filter.clause_codes.is_empty() {
+            clauses.push(terms(f.clause_code, &filter.clause_codes));
+        }
+        // 日期範圍只比對發言日期，與儲存後端一致
+        if filter.start_date.is_some() || filter.end_date.is_some() {
+            clauses.push(range(f.date, day(filter.start_date), day(filter.end_date)));
+        }
+        if let Some(date) = filter.query_date {
+            let term = Term::from_field_text(f.query_date, &date.to_gregorian_string());
+            clauses.push(Box::new(TermQuery::new(term, IndexRecordOption::Basic)));
+        }
+        if filter.occurrence_start_date.is_some() || filter.occurrence_end_date.is_some() {
+            clauses.push(range(f.fact_occurrence_date, day(filter.occurrence_start_date), day(filter.occurrence_end_date)));
+        }
+        if filter.time_from.is_some() || filter.time_to.is_some() {
+            clauses.push(range(f.time, filter.time_from.clone(), filter.time_to.clone()));
+        }
+        clauses
+    }
+}
+
+fn inclusive(value: &Option<String>) -> Bound<&str> {
+    value.as_deref().map_or(Bound::Unbounded, Bound::Included)
+}
+
+// 依查詢詞產生標題與內容的標示
+struct Highlighter {
+    title: SnippetGenerator,
+    detail: SnippetGenerator,
+}
+
+impl Highlighter {
+    fn new(searcher: &Searcher, query: &dyn Query, fields: Fields) -> Result<Self> {
+        let mut title = SnippetGenerator::create(searcher, query, fields.title)?;
+        title.set_max_num_chars(usize::MAX);
+        let mut detail = SnippetGenerator::create(searcher, query, fields.detail)?;
+        detail.set_max_num_chars(SNIPPET_CHARS);
+        Ok(Self { title, detail })
+    }
+
+    fn highlight(&self, announcement: &Announcement) -> Highlights {
+        let mut title = self.title.snippet(&announcement.title);
+        let title = if title.is_empty() {
+            escape_html(&announcement.title)
+        } else {
+            title.set_snippet_prefix_postfix(HIGHLIGHT_PREFIX, HIGHLIGHT_POSTFIX);
+            title.to_html()
+        };
+
+        let snippet = announcement.detail_content.as_deref().and_then(|detail| {
+            let mut snippet = self.detail.snippet(detail);
+            (!snippet.is_empty()).then(|| {
+                snippet.set_snippet_prefix_postfix(HIGHLIGHT_PREFIX, HIGHLIGHT_POSTFIX);
+                snippet.to_html()
+            })
+        });
+
+        Highlights { title, snippet }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// 一頁檢索結果
+#[derive(Debug)]
+pub struct SearchResults {
+    /// 符合條件的總筆數
+    pub total: u64,
+    pub hits: Vec<SearchHit>,
+}
+
+/// 一筆檢索結果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHit {
+    pub announcement: Announcement,
+    /// BM25 相關性分數，越高越相關
+    pub score: f32,
+    pub highlights: Highlights,
+}
+
+/// 以 `<mark>` 標示查詢詞的 HTML 片段，原文已跳脫
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Highlights {
+    /// 完整標題
+    pub title: String,
+    /// 內容中查詢詞附近的摘要，內容沒有符合的詞時省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
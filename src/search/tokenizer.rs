@@ -0,0 +1,118 @@
+//! 中日韓文字的二元 (bigram) 斷詞器。
+//!
+//! 重大訊息沒有空白分詞，連續的中日韓文字切成重疊的兩字詞 (「私募股權」→「私募」「募股」「股權」)，
+//! 單獨一個字時保留單字；英數字以連續的字母與數字為一個詞並轉為小寫，其餘標點與空白只作為分隔。
+//! 全形英數字先轉為半形。詞的位置連續編號，多字查詢因此可以用片語比對相鄰的兩字詞。
+
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// 註冊於索引的斷詞器名稱
+pub const CJK_TOKENIZER: &str = "cjk_bigram";
+
+/// 中日韓二元斷詞器
+#[derive(Debug, Clone, Default)]
+pub struct CjkBigramTokenizer;
+
+impl Tokenizer for CjkBigramTokenizer {
+    type TokenStream<'a> = CjkTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        CjkTokenStream { tokens: tokenize(text), index: 0 }
+    }
+}
+
+/// [`CjkBigramTokenizer`] 的輸出
+pub struct CjkTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl TokenStream for CjkTokenStream {
+    fn advance(&mut self) -> bool {
+        self.index += 1;
+        self.index <= self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Cjk,
+    Word,
+    Separator,
+}
+
+fn classify(c: char) -> CharClass {
+    if is_cjk(c) {
+        CharClass::Cjk
+    } else if c.is_alphanumeric() {
+        CharClass::Word
+    } else {
+        CharClass::Separator
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF       // 平假名、片假名
+        | 0x3400..=0x4DBF     // 擴充 A
+        | 0x4E00..=0x9FFF     // 基本漢字
+        | 0xAC00..=0xD7AF     // 韓文音節
+        | 0xF900..=0xFAFF     // 相容漢字
+        | 0x20000..=0x2FA1F   // 擴充 B 之後
+    )
+}
+
+// 全形英數字與符號轉為半形
+fn normalize(c: char) -> char {
+    match c as u32 {
+        0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        0x3000 => ' ',
+        _ => c,
+    }
+}
+
+/// 將文字切成詞，位移為原文字的位元組位置
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = text.char_indices().map(|(offset, c)| (offset, normalize(c))).collect();
+    let end_of = |index: usize| chars.get(index).map_or(text.len(), |(offset, _)| *offset);
+
+    let mut tokens = Vec::new();
+    let mut push = |from: usize, to: usize, text: String| {
+        let position = tokens.len();
+        tokens.push(Token { offset_from: from, offset_to: to, position, text, position_length: 1 });
+    };
+
+    let mut start = 0;
+    while start < chars.len() {
+        let class = classify(chars[start].1);
+        let mut end = start + 1;
+        while end < chars.len() && classify(chars[end].1) == class {
+            end += 1;
+        }
+
+        match class {
+            CharClass::Cjk if end - start == 1 => push(chars[start].0, end_of(end), chars[start].1.to_string()),
+            CharClass::Cjk => {
+                for i in start..end - 1 {
+                    push(chars[i].0, end_of(i + 2), [chars[i].1, chars[i + 1].1].iter().collect());
+                }
+            }
+            CharClass::Word => {
+                let word: String = chars[start..end].iter().flat_map(|(_, c)| c.to_lowercase()).collect();
+                push(chars[start].0, end_of(end), word);
+            }
+            CharClass::Separator => {}
+        }
+        start = end;
+    }
+
+    tokens
+}
@@ -0,0 +1,58 @@
+//! 查詢結果的本機檔案輸出 (HTML/JSON/TXT)；資料庫儲存見 [`crate::store`]。
+
+use anyhow::Result;
+use std::fs;
+
+use crate::model::Announcement;
+
+/// 儲存原始 HTML 回應為 `<filename>.html`
+pub fn save_html(html_content: &str, filename: &str) -> Result<()> {
+    fs::write(format!("{}.html", filename), html_content)?;
+    println!("✅ 原始 HTML 檔案已自動儲存: {}.html", filename);
+    Ok(())
+}
+
+/// 儲存為 `<filename>.json`，不包含 raw_html 欄位
+pub fn save_json(announcements: &[Announcement], filename: &str) -> Result<()> {
+    // 創建不包含 raw_html 的版本以減少檔案大小
+    let announcements_for_json: Vec<_> = announcements.iter().map(|announcement| {
+        let mut json_announcement = announcement.clone();
+        json_announcement.raw_html = None; // 移除 raw_html 欄位
+        json_announcement
+    }).collect();
+
+    let json_content = serde_json::to_string_pretty(&announcements_for_json)?;
+    fs::write(format!("{}.json", filename), json_content)?;
+    println!("JSON 檔案已儲存: {}.json (不包含原始HTML以減少檔案大小)", filename);
+    Ok(())
+}
+
+/// 儲存為 `<filename>.txt` 表格文字檔
+pub fn save_txt(announcements: &[Announcement], filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("台灣證交所重大訊息\n");
+    content.push_str("==================\n\n");
+
+    if announcements.is_empty() {
+        content.push_str("沒有找到重大訊息\n");
+    } else {
+        content.push_str(&format!("{:<8} {:<20} {:<10} {:<8} {}\n",
+                                 "代號", "公司名稱", "日期", "時間", "標題"));
+        content.push_str(&format!("{}\n", "-".repeat(80)));
+
+        for announcement in announcements {
+            // 移除標題中的換行符號，用空格取代
+            let clean_title = announcement.title.replace(['\n', '\r'], " ");
+            content.push_str(&format!("{:<8} {:<20} {:<10} {:<8} {}\n",
+                                     announcement.company_code,
+                                     announcement.company_name,
+                                     announcement.date.to_roc_string(),
+                                     announcement.time,
+                                     clean_title));
+        }
+    }
+
+    fs::write(format!("{}.txt", filename), content)?;
+    println!("TXT 檔案已儲存: {}.txt", filename);
+    Ok(())
+}
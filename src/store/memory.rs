@@ -0,0 +1,332 @@
+//! 記憶體儲存後端，供測試與展示使用，程式結束後資料即消失。
+//!
+//! 可由 CLI 輸出的 JSON 檔 (`--format json`) 預先載入資料。
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::SubsecRound;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::RwLock;
+
+use super::{
+    next_revision, search_key, sort_clause_codes, AnnouncementQuery, AnnouncementStore, CompanyCount, DateCount, DuplicateMode, ReplaceScope,
+    SaveSummary,
+};
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementKey, AnnouncementRevision, ClauseCode, CLAUSE_CODES};
+
+/// 記憶體儲存後端
+pub struct MemoryStore {
+    announcements: RwLock<Vec<Announcement>>,
+    revisions: RwLock<Vec<AnnouncementRevision>>,
+    clause_codes: Vec<ClauseCode>,
+    source: Option<String>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    /// 建立空的記憶體儲存，條款代號對照表使用內建清單
+    pub fn new() -> Self {
+        let clause_codes = CLAUSE_CODES
+            .iter()
+            .map(|(code, description)| ClauseCode {
+                id: None,
+                code: code.to_string(),
+                description: description.to_string(),
+                created_at: None,
+            })
+            .collect();
+
+        Self {
+            announcements: RwLock::new(Vec::new()),
+            revisions: RwLock::new(Vec::new()),
+            clause_codes,
+            source: None,
+        }
+    }
+
+    /// 以既有公告建立，沒有 id 的公告會自動產生
+    pub fn with_announcements(announcements: Vec<Announcement>) -> Self {
+        let store = Self::new();
+        *store.announcements.write().unwrap() = announcements.into_iter().map(prepare_announcement).collect();
+        store
+    }
+
+    /// 由 JSON 檔 (公告陣列) 載入
+    pub fn load_json(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("無法讀取 {}", path))?;
+        let announcements: Vec<Announcement> =
+            serde_json::from_str(&content).with_context(|| format!("{} 不是有效的公告 JSON 陣列", path))?;
+
+        let mut store = Self::with_announcements(announcements);
+        store.source = Some(path.to_string());
+        Ok(store)
+    }
+
+    /// 目前的公告筆數
+    pub fn len(&self) -> usize {
+        self.announcements.read().unwrap().len()
+    }
+
+    /// 是否沒有任何公告
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// 寫入前移除 raw_html 並補上 id、穩定識別碼與版本號
+fn prepare_announcement(mut announcement: Announcement) -> Announcement {
+    announcement.raw_html = None;
+    announcement.id.get_or_insert_with(bson::oid::ObjectId::new);
+    announcement.ensure_identity();
+    announcement.revision.get_or_insert(1);
+    announcement
+}
+
+// 判斷公告是否落在 replace 範圍內
+fn in_scope(scope: &ReplaceScope, announcement: &Announcement) -> bool {
+    match scope {
+        ReplaceScope::Day { query_date, market } => {
+            announcement.query_date == Some(*query_date)
+                && market.as_ref().is_none_or(|m| announcement.market.as_ref() == Some(m))
+        }
+        ReplaceScope::CompanyRange { company_code, from, to } => {
+            announcement.company_code == *company_code && announcement.date >= *from && announcement.date <= *to
+        }
+    }
+}
+
+// 與 MongoDB 後端相同的篩選條件
+fn matches(query: &AnnouncementQuery, title: Option<&str>, announcement: &Announcement) -> bool {
+    if !query.companies.is_empty() && !query.companies.contains(&announcement.company_code) {
+        return false;
+    }
+
+    if let Some(market) = query.market.as_deref().filter(|m| !m.is_empty() && *m != "all") {
+        if announcement.market.as_deref() != Some(market) {
+            return false;
+        }
+    }
+
+    if !query.clause_codes.is_empty()
+        && !announcement.clause_code.as_ref().is_some_and(|code| query.clause_codes.contains(code))
+    {
+        return false;
+    }
+
+    if query.occurrence_start_date.is_some() || query.occurrence_end_date.is_some() {
+        let in_range = announcement.fact_occurrence_date.is_some_and(|date| {
+            query.occurrence_start_date.is_none_or(|start| date >= start)
+                && query.occurrence_end_date.is_none_or(|end| date <= end)
+        });
+        if !in_range {
+            return false;
+        }
+    }
+
+    // 發言時間皆為 HH:MM:SS，字串比較即為時間先後
+    if query.time_from.as_ref().is_some_and(|from| announcement.time < *from)
+        || query.time_to.as_ref().is_some_and(|to| announcement.time > *to)
+    {
+        return false;
+    }
+
+    // 日期範圍只比對發言日期
+    if query.start_date.is_some_and(|start| announcement.date < start)
+        || query.end_date.is_some_and(|end| announcement.date > end)
+    {
+        return false;
+    }
+
+    if let Some(date) = query.query_date {
+        if announcement.query_date != Some(date) {
+            return false;
+        }
+    }
+
+    if let Some(since) = query.created_since {
+        let since = since.trunc_subsecs(0);
+        if announcement.created_at.is_none_or(|created_at| created_at < since) {
+            return false;
+        }
+    }
+
+    title.is_none_or(|title| search_key(&announcement.title).contains(title))
+}
+
+// 依條件篩選並排序：公告時間、id 皆由新到舊，與游標的比較方式相同
+fn filter_sorted(announcements: &[Announcement], query: &AnnouncementQuery) -> Vec<Announcement> {
+    let title = query.search.as_deref().filter(|s| !s.is_empty()).map(search_key);
+    let mut results: Vec<Announcement> = announcements
+        .iter()
+        .filter(|announcement| matches(query, title.as_deref(), announcement))
+        .cloned()
+        .collect();
+    results.sort_by_key(|a| Reverse((a.announced_at, a.id)));
+    results
+}
+
+#[async_trait]
+impl AnnouncementStore for MemoryStore {
+    fn backend(&self) -> &'static str {
+        "記憶體"
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save(&self, announcements: &[Announcement], scope: &ReplaceScope, mode: DuplicateMode) -> Result<SaveSummary> {
+        let mut stored = self.announcements.write().unwrap();
+        let mut revisions = self.revisions.write().unwrap();
+        let mut summary = SaveSummary::default();
+
+        // replace 模式刪除前先保留範圍內的舊資料，用於比對內容是否更正
+        let mut removed: HashMap<AnnouncementKey, Announcement> = HashMap::new();
+        if mode == DuplicateMode::Replace {
+            let before = stored.len();
+            let (in_range, kept): (Vec<_>, Vec<_>) = stored.drain(..).partition(|a| in_scope(scope, a));
+            *stored = kept;
+            summary.deleted = (before - stored.len()) as u64;
+            removed = in_range.into_iter().map(|a| (a.key(), a)).collect();
+        }
+
+        for announcement in announcements {
+            let key = announcement.key();
+            let mut incoming = announcement.clone();
+            incoming.id = None;
+            incoming.raw_html = None;
+
+            if let Some(previous) = removed.get(&key) {
+                if let Some(revision) = next_revision(previous, &mut incoming) {
+                    revisions.push(revision);
+                    summary.revised += 1;
+                }
+                stored.push(prepare_announcement(incoming));
+                summary.inserted += 1;
+                continue;
+            }
+
+            match stored.iter_mut().find(|existing| existing.key() == key) {
+                Some(existing) => {
+                    let revision = next_revision(existing, &mut incoming);
+                    if revision.is_none() && mode == DuplicateMode::Skip {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    incoming.id = existing.id;
+                    if let Some(revision) = revision {
+                        revisions.push(revision);
+                        summary.revised += 1;
+                    }
+                    // 比對序列化結果判斷內容是否有變更
+                    if serde_json::to_value(&incoming)? != serde_json::to_value(&*existing)? {
+                        *existing = incoming;
+                        summary.updated += 1;
+                    }
+                }
+                None => {
+                    stored.push(prepare_announcement(incoming));
+                    summary.inserted += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn query(&self, query: &AnnouncementQuery) -> Result<Vec<Announcement>> {
+        let stored = self.announcements.read().unwrap();
+        let results = filter_sorted(&stored, query)
+            .into_iter()
+            .filter(|a| query.after.is_none_or(|cursor| cursor.precedes(a)))
+            .skip(query.skip as usize);
+        Ok(match query.limit {
+            Some(limit) => results.take(limit as usize).collect(),
+            None => results.collect(),
+        })
+    }
+
+    async fn count(&self, query: &AnnouncementQuery) -> Result<u64> {
+        let stored = self.announcements.read().unwrap();
+        Ok(filter_sorted(&stored, query).len() as u64)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Announcement>> {
+        let object_id = bson::oid::ObjectId::parse_str(id).ok();
+        let stored = self.announcements.read().unwrap();
+        Ok(stored
+            .iter()
+            .find(|a| (object_id.is_some() && a.id == object_id) || a.announcement_id.as_deref() == Some(id))
+            .cloned())
+    }
+
+    async fn revisions(&self, announcement_id: &str) -> Result<Vec<AnnouncementRevision>> {
+        let revisions = self.revisions.read().unwrap();
+        let mut found: Vec<AnnouncementRevision> =
+            revisions.iter().filter(|r| r.announcement_id == announcement_id).cloned().collect();
+        found.sort_by_key(|r| r.revision);
+        Ok(found)
+    }
+
+    async fn known_keys(&self, date: RocDate) -> Result<HashSet<AnnouncementKey>> {
+        let stored = self.announcements.read().unwrap();
+        Ok(stored.iter().filter(|a| a.date == date).map(Announcement::key).collect())
+    }
+
+    async fn company_counts(&self, limit: usize) -> Result<Vec<CompanyCount>> {
+        let stored = self.announcements.read().unwrap();
+        let mut counts: HashMap<(&str, &str), i64> = HashMap::new();
+        for announcement in stored.iter() {
+            *counts.entry((&announcement.company_code, &announcement.company_name)).or_insert(0) += 1;
+        }
+
+        let mut companies: Vec<CompanyCount> = counts
+            .into_iter()
+            .map(|((code, name), count)| CompanyCount {
+                company_code: code.to_string(),
+                company_name: name.to_string(),
+                count,
+            })
+            .collect();
+        companies.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.company_code.cmp(&b.company_code)));
+        companies.truncate(limit);
+        Ok(companies)
+    }
+
+    async fn date_counts(&self, limit: usize) -> Result<Vec<DateCount>> {
+        let stored = self.announcements.read().unwrap();
+        let mut counts: BTreeMap<RocDate, i64> = BTreeMap::new();
+        for date in stored.iter().filter_map(|a| a.query_date) {
+            *counts.entry(date).or_insert(0) += 1;
+        }
+
+        Ok(counts
+            .into_iter()
+            .rev()
+            .take(limit)
+            .map(|(date, count)| DateCount { date: date.to_gregorian_string(), count })
+            .collect())
+    }
+
+    async fn clause_codes(&self) -> Result<Vec<ClauseCode>> {
+        let mut clause_codes = self.clause_codes.clone();
+        sort_clause_codes(&mut clause_codes);
+        Ok(clause_codes)
+    }
+
+    async fn diagnostics(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "backend": "memory",
+            "source": self.source,
+            "announcements": self.len(),
+            "revisions": self.revisions.read().unwrap().len(),
+        }))
+    }
+}
@@ -0,0 +1,339 @@
+//! 重大訊息的資料庫儲存層。
+//!
+//! [`AnnouncementStore`] 定義寫入、查詢、統計與條款代號等操作，CLI 與 Web 查看器只透過此介面存取資料：
+//!
+//! - [`mongo`]：MongoDB（`mongodb://...`）
+//! - [`sqlite`]：內嵌 SQLite（`sqlite:///path/to/file.db`），不需另外安裝資料庫服務
+//! - [`memory`]：記憶體（`memory://` 或由 JSON 檔預先載入的 `memory:///path/to/file.json`），供測試與展示
+//!
+//! 以 [`open_store`] 依連線字串選擇實作。
+
+pub mod memory;
+pub mod mongo;
+pub mod sqlite;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementKey, AnnouncementRevision, ClauseCode};
+
+pub use memory::MemoryStore;
+pub use mongo::MongoStore;
+pub use sqlite::SqliteStore;
+
+/// 防重複模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateMode {
+    /// 依識別鍵新增或更新
+    #[default]
+    Upsert,
+    /// 刪除寫入範圍內的舊資料後重新插入
+    Replace,
+    /// 已存在且內容未變更的公告略過不寫入；內容更正時仍會更新並保留舊版本
+    Skip,
+}
+
+impl FromStr for DuplicateMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upsert" => Ok(Self::Upsert),
+            "replace" => Ok(Self::Replace),
+            "skip" => Ok(Self::Skip),
+            _ => Err(format!("不支援的防重複模式: {}。支援的模式: upsert, replace, skip", s)),
+        }
+    }
+}
+
+impl fmt::Display for DuplicateMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Upsert => "upsert",
+            Self::Replace => "replace",
+            Self::Skip => "skip",
+        })
+    }
+}
+
+/// replace 模式的寫入範圍，只刪除本次查詢涵蓋的舊資料
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceScope {
+    /// 單日查詢：同一查詢日期，指定市場別時再限定市場
+    Day { query_date: RocDate, market: Option<String> },
+    /// 公司歷史查詢：同一公司在發言日期區間內的公告
+    CompanyRange { company_code: String, from: RocDate, to: RocDate },
+}
+
+impl ReplaceScope {
+    /// 單日查詢的範圍，`market` 為 `all` 時不限定市場別
+    pub fn day(query_date: RocDate, market: &str) -> Self {
+        Self::Day {
+            query_date,
+            market: (market != "all").then(|| market.to_string()),
+        }
+    }
+
+    /// 公司歷史查詢的範圍
+    pub fn company_range(company_code: &str, from: RocDate, to: RocDate) -> Self {
+        Self::CompanyRange { company_code: company_code.to_string(), from, to }
+    }
+}
+
+/// 單筆公告寫入失敗的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveFailure {
+    pub key: AnnouncementKey,
+    pub message: String,
+}
+
+/// 寫入結果統計
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveSummary {
+    pub inserted: u64,
+    pub updated: u64,
+    pub skipped: u64,
+    pub deleted: u64,
+    /// 內容變更而保留舊版本的筆數
+    pub revised: u64,
+    /// 寫入失敗的公告，其餘公告仍會寫入
+    pub failures: Vec<SaveFailure>,
+}
+
+impl SaveSummary {
+    /// 輸出寫入結果
+    pub fn print(&self, mode: DuplicateMode) {
+        match mode {
+            DuplicateMode::Upsert => {
+                println!("Upsert 操作完成 (不包含原始HTML以減少資料庫大小):");
+                println!("  新增: {} 筆資料", self.inserted);
+                println!("  更新: {} 筆資料", self.updated);
+                println!("  總計: {} 筆資料", self.inserted + self.updated);
+            }
+            DuplicateMode::Replace => {
+                if self.deleted > 0 {
+                    println!("已刪除 {} 筆相同範圍的舊資料", self.deleted);
+                }
+                println!("成功插入 {} 筆新資料 (不包含原始HTML以減少資料庫大小)", self.inserted);
+            }
+            DuplicateMode::Skip => {
+                println!("Skip 操作完成 (不包含原始HTML以減少資料庫大小):");
+                println!("  新增: {} 筆資料", self.inserted);
+                println!("  更新: {} 筆內容更正資料", self.updated);
+                println!("  跳過: {} 筆重複資料", self.skipped);
+                println!("  總計處理: {} 筆資料", self.inserted + self.updated + self.skipped);
+            }
+        }
+        if self.revised > 0 {
+            println!("  📝 {} 筆公告內容已更正，舊版本已保留於版本紀錄", self.revised);
+        }
+        if !self.failures.is_empty() {
+            println!("  ❌ 寫入失敗: {} 筆資料", self.failures.len());
+            for failure in &self.failures {
+                let (company_code, date, time, title) = &failure.key;
+                println!("     {} {} {} {}: {}", company_code, date, time, title, failure.message);
+            }
+        }
+    }
+}
+
+/// 公告查詢條件，未指定的欄位不限制
+#[derive(Debug, Clone, Default)]
+pub struct AnnouncementQuery {
+    /// 公司代號，符合任一即可
+    pub companies: Vec<String>,
+    /// 市場別 (sii, otc, rotc, pub)
+    pub market: Option<String>,
+    /// 條款代號，符合任一即可
+    pub clause_codes: Vec<String>,
+    /// 查詢日期
+    pub query_date: Option<RocDate>,
+    /// 發言日期 (`date`) 範圍起點
+    pub start_date: Option<RocDate>,
+    /// 日期範圍終點
+    pub end_date: Option<RocDate>,
+    /// 事實發生日 (`fact_occurrence_date`) 範圍起點
+    pub occurrence_start_date: Option<RocDate>,
+    /// 事實發生日範圍終點
+    pub occurrence_end_date: Option<RocDate>,
+    /// 發言時間下限 (含)，格式為 HH:MM:SS
+    pub time_from: Option<String>,
+    /// 發言時間上限 (含)，格式為 HH:MM:SS
+    pub time_to: Option<String>,
+    /// 標題關鍵字，語意見 [`AnnouncementStore::query`]
+    pub search: Option<String>,
+    /// 擷取時間 (`created_at`) 不早於此時間，以秒為單位比較；沒有擷取時間的公告不符合。
+    /// 內容更正時 `created_at` 會一併更新，全文索引以此增量同步
+    pub created_since: Option<DateTime<Utc>>,
+    pub skip: u64,
+    pub limit: Option<u64>,
+    /// 只取排序在游標之後的公告，與 `skip` 擇一使用
+    pub after: Option<Cursor>,
+}
+
+/// 游標分頁的位置：上一頁最後一筆公告的公告時間與 id
+///
+/// 查詢結果依 (`announced_at`, id) 由新到舊排序，兩者都相同的公告不存在，
+/// 因此不論期間是否有新資料寫入，以游標逐頁讀取都不會重複或遺漏。
+/// 尚未執行 `migrate` 而缺少 `announced_at` 的 MongoDB 文件排在最後，游標的公告時間為 `None`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub announced_at: Option<DateTime<Utc>>,
+    pub id: ObjectId,
+}
+
+impl Cursor {
+    /// 指向某筆公告之後的游標，公告沒有 id 時回傳 `None`
+    pub fn after(announcement: &Announcement) -> Option<Self> {
+        Some(Self { announced_at: announcement.announced_at, id: announcement.id? })
+    }
+
+    /// 編碼為不透明的字串
+    pub fn encode(&self) -> String {
+        let millis = self.announced_at.map(|t| t.timestamp_millis().to_string()).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", millis, self.id.to_hex()))
+    }
+
+    /// 解析 [`Cursor::encode`] 產生的字串
+    pub fn decode(token: &str) -> Option<Self> {
+        let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(token).ok()?).ok()?;
+        let (millis, id) = decoded.split_once(':')?;
+        let announced_at = match millis {
+            "" => None,
+            millis => Some(DateTime::from_timestamp_millis(millis.parse().ok()?)?),
+        };
+        Some(Self { announced_at, id: ObjectId::parse_str(id).ok()? })
+    }
+
+    /// 公告是否排序在游標之後；缺少公告時間的公告排在所有有時間的公告之後
+    pub fn precedes(&self, announcement: &Announcement) -> bool {
+        announcement.id.is_some_and(|id| (announcement.announced_at, id) < (self.announced_at, self.id))
+    }
+}
+
+/// 公司公告數量統計
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CompanyCount {
+    pub company_code: String,
+    pub company_name: String,
+    pub count: i64,
+}
+
+/// 查詢日期公告數量統計，日期為 `YYYY-MM-DD`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DateCount {
+    pub date: String,
+    pub count: i64,
+}
+
+/// 重大訊息儲存介面，MongoDB 與 SQLite 共用
+///
+/// 查詢結果依公告時間 (`announced_at`)、id 由新到舊排序；寫入時不保存 `raw_html`。
+#[async_trait]
+pub trait AnnouncementStore: Send + Sync {
+    /// 儲存後端名稱，顯示於訊息中
+    fn backend(&self) -> &'static str;
+
+    /// 建立索引並初始化條款代號對照表
+    async fn prepare(&self) -> Result<()>;
+
+    /// 依防重複模式寫入公告；replace 模式只刪除 `scope` 範圍內的舊資料
+    async fn save(&self, announcements: &[Announcement], scope: &ReplaceScope, mode: DuplicateMode) -> Result<SaveSummary>;
+
+    /// 查詢公告
+    ///
+    /// 標題關鍵字 (`search`) 在每個後端語意相同：不分大小寫 (Unicode 小寫) 的字面子字串比對，
+    /// `%`、`_`、`.*` 等字元不具萬用字元或正規表示式的意義。
+    async fn query(&self, query: &AnnouncementQuery) -> Result<Vec<Announcement>>;
+
+    /// 符合條件的公告筆數，不受 `skip` / `limit` / `after` 影響
+    async fn count(&self, query: &AnnouncementQuery) -> Result<u64>;
+
+    /// 依 id (資料庫 id 或穩定識別碼 `announcement_id`) 讀取單筆公告，id 無效或不存在時回傳 `None`
+    async fn get(&self, id: &str) -> Result<Option<Announcement>>;
+
+    /// 公告的舊版本紀錄，依版本號由舊到新排序
+    async fn revisions(&self, announcement_id: &str) -> Result<Vec<AnnouncementRevision>>;
+
+    /// 指定發言日期已儲存的公告識別鍵
+    async fn known_keys(&self, date: RocDate) -> Result<HashSet<AnnouncementKey>>;
+
+    /// 公告數量最多的公司
+    async fn company_counts(&self, limit: usize) -> Result<Vec<CompanyCount>>;
+
+    /// 最近的查詢日期與各日公告數量
+    async fn date_counts(&self, limit: usize) -> Result<Vec<DateCount>>;
+
+    /// 條款代號對照表，依代號數字排序
+    async fn clause_codes(&self) -> Result<Vec<ClauseCode>>;
+
+    /// 除錯用的儲存狀態資訊
+    async fn diagnostics(&self) -> Result<serde_json::Value>;
+}
+
+/// 依連線字串開啟儲存後端：`sqlite://<路徑>` 使用 SQLite，`memory://[JSON 檔路徑]` 使用記憶體，
+/// `mongodb://`、`mongodb+srv://` 使用 MongoDB
+///
+/// `database_name`、`collection_name` 只用於 MongoDB。
+pub async fn open_store(uri: &str, database_name: &str, collection_name: &str) -> Result<Arc<dyn AnnouncementStore>> {
+    if let Some(path) = uri.strip_prefix("sqlite://") {
+        if path.is_empty() {
+            anyhow::bail!("SQLite 連線字串缺少檔案路徑，格式為 sqlite:///path/to/file.db");
+        }
+        Ok(Arc::new(SqliteStore::open(path)?))
+    } else if let Some(path) = uri.strip_prefix("memory://") {
+        if path.is_empty() {
+            Ok(Arc::new(MemoryStore::new()))
+        } else {
+            Ok(Arc::new(MemoryStore::load_json(path)?))
+        }
+    } else if uri.starts_with("mongodb://") || uri.starts_with("mongodb+srv://") {
+        Ok(Arc::new(MongoStore::connect(uri, database_name, collection_name).await?))
+    } else {
+        anyhow::bail!("不支援的儲存連線字串: {}。支援 mongodb://...、sqlite:///path.db 或 memory://[path.json]", uri)
+    }
+}
+
+// 比對既有與新抓取的公告：沿用穩定識別碼並設定版本號，內容雜湊不同時回傳要保留的舊版本
+pub(crate) fn next_revision(existing: &Announcement, incoming: &mut Announcement) -> Option<AnnouncementRevision> {
+    let mut existing = existing.clone();
+    existing.ensure_identity();
+    incoming.announcement_id = existing.announcement_id.clone();
+    incoming.ensure_identity();
+
+    let current = existing.revision.unwrap_or(1);
+    if incoming.content_hash == existing.content_hash {
+        incoming.revision = Some(current);
+        None
+    } else {
+        incoming.revision = Some(current + 1);
+        Some(AnnouncementRevision::from_superseded(&existing, chrono::Utc::now()))
+    }
+}
+
+// SQLite 的 created_at 以 RFC 3339 字串儲存，小數秒位數不固定；比較值只到秒且不含時區，
+// 同一秒內的任何時間都大於比較值，字串比較即為時間先後
+pub(crate) fn created_since_text(since: DateTime<Utc>) -> String {
+    since.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+// 標題關鍵字比對使用的正規化文字：Unicode 小寫，包含全形英文字母
+pub(crate) fn search_key(text: &str) -> String {
+    text.to_lowercase()
+}
+
+// 條款代號依數字排序，非數字代號排在最後
+pub(crate) fn sort_clause_codes(clause_codes: &mut [ClauseCode]) {
+    clause_codes.sort_by_key(|c| c.code.parse::<i32>().unwrap_or(999));
+}
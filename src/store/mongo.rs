@@ -0,0 +1,722 @@
+//! MongoDB 儲存後端。
+//!
+//! 日期欄位存為 BSON 日期，查詢只比對 BSON 日期；舊版的字串日期與缺少的 `announced_at` 需先執行 `migrate` 轉換。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bson::{doc, Bson, Document};
+use chrono::SubsecRound;
+use mongodb::error::{BulkWriteError, ErrorKind};
+use mongodb::options::{FindOptions, InsertManyOptions};
+use mongodb::{Client as MongoClient, Collection, Database};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    next_revision, sort_clause_codes, AnnouncementQuery, AnnouncementStore, CompanyCount, DateCount, DuplicateMode,
+    ReplaceScope, SaveFailure, SaveSummary,
+};
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementKey, AnnouncementRevision, ClauseCode, CLAUSE_CODES};
+
+/// 條款代號對照表的集合名稱
+pub const CLAUSE_CODES_COLLECTION: &str = "clause_codes";
+
+/// 公告舊版本的集合名稱
+pub const REVISIONS_COLLECTION: &str = "announcement_revisions";
+
+/// 識別鍵唯一索引的名稱
+pub const IDENTITY_INDEX_NAME: &str = "announcement_identity";
+
+/// upsert / skip 模式每批寫入的公告筆數，每批只需一次查詢與一次寫入
+const BULK_BATCH_SIZE: usize = 200;
+
+/// MongoDB 儲存後端
+#[derive(Clone)]
+pub struct MongoStore {
+    client: MongoClient,
+    database_name: String,
+    collection_name: String,
+}
+
+impl MongoStore {
+    /// 建立連線並確認伺服器可用
+    pub async fn connect(mongodb_uri: &str, database_name: &str, collection_name: &str) -> Result<Self> {
+        let client = MongoClient::with_uri_str(mongodb_uri).await?;
+        client.database(database_name).run_command(doc! { "ping": 1 }, None).await?;
+        Ok(Self {
+            client,
+            database_name: database_name.to_string(),
+            collection_name: collection_name.to_string(),
+        })
+    }
+
+    fn database(&self) -> Database {
+        self.client.database(&self.database_name)
+    }
+
+    fn collection(&self) -> Collection<Announcement> {
+        self.database().collection(&self.collection_name)
+    }
+
+    fn raw_collection(&self) -> Collection<Document> {
+        self.database().collection(&self.collection_name)
+    }
+
+    fn revisions_collection(&self) -> Collection<AnnouncementRevision> {
+        self.database().collection(REVISIONS_COLLECTION)
+    }
+}
+
+// 新公告的第一個版本
+fn first_revision(announcement: &mut Announcement) {
+    announcement.ensure_identity();
+    announcement.revision = Some(1);
+}
+
+// 一批寫入中待寫入的公告與對應的舊版本
+struct PendingWrite {
+    announcement: Announcement,
+    revision: Option<AnnouncementRevision>,
+}
+
+// `update` 指令的回應
+#[derive(Deserialize)]
+struct UpdateReply {
+    #[serde(rename = "nModified", default)]
+    modified: i64,
+    #[serde(default)]
+    upserted: Vec<Document>,
+    #[serde(rename = "writeErrors", default)]
+    write_errors: Vec<BulkWriteError>,
+    #[serde(rename = "writeConcernError")]
+    write_concern_error: Option<Document>,
+}
+
+// 取出 unordered insert_many 的逐筆錯誤，其他錯誤直接回傳
+fn insert_write_errors(error: mongodb::error::Error) -> Result<Vec<BulkWriteError>> {
+    match *error.kind {
+        ErrorKind::BulkWrite(ref failure) if failure.write_concern_error.is_none() => {
+            Ok(failure.write_errors.clone().unwrap_or_default())
+        }
+        _ => Err(error.into()),
+    }
+}
+
+fn write_failure(announcement: &Announcement, error: &BulkWriteError) -> SaveFailure {
+    SaveFailure {
+        key: announcement.key(),
+        message: format!("{} (code {})", error.message, error.code),
+    }
+}
+
+impl MongoStore {
+    // unordered 批次插入，回傳成功筆數；失敗的公告記錄於 summary
+    async fn insert_unordered<T: Serialize + Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+        documents: &[T],
+        announcements: &[&Announcement],
+        summary: &mut SaveSummary,
+    ) -> Result<HashSet<usize>> {
+        if documents.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let options = InsertManyOptions::builder().ordered(false).build();
+        let errors = match collection.insert_many(documents, options).await {
+            Ok(_) => Vec::new(),
+            Err(e) => insert_write_errors(e)?,
+        };
+
+        let mut failed = HashSet::new();
+        for error in &errors {
+            summary.failures.push(write_failure(announcements[error.index], error));
+            failed.insert(error.index);
+        }
+        Ok(failed)
+    }
+
+    // 一批 upsert / skip：一次查詢既有公告，再以一個 unordered `update` 指令寫入
+    async fn save_batch(&self, batch: Vec<Announcement>, mode: DuplicateMode, summary: &mut SaveSummary) -> Result<()> {
+        let filters: Vec<Document> = batch.iter().map(identity_filter).collect();
+        let mut existing: HashMap<AnnouncementKey, Announcement> = HashMap::new();
+        let mut cursor = self.collection().find(doc! { "$or": filters }, None).await?;
+        while cursor.advance().await? {
+            let announcement: Announcement = cursor.deserialize_current()?;
+            existing.insert(announcement.key(), announcement);
+        }
+
+        let mut pending = Vec::new();
+        let mut statements = Vec::new();
+        for mut announcement in batch {
+            let Some(previous) = existing.get(&announcement.key()) else {
+                // 以識別鍵 upsert，同批重複或其他程序同時寫入時仍只保留一筆
+                first_revision(&mut announcement);
+                statements.push(doc! {
+                    "q": identity_filter(&announcement),
+                    "u": announcement_document(&announcement)?,
+                    "upsert": true,
+                });
+                pending.push(PendingWrite { announcement, revision: None });
+                continue;
+            };
+
+            // 內容雜湊不同時保留舊版本再更新
+            let revision = next_revision(previous, &mut announcement);
+            if revision.is_none() && mode == DuplicateMode::Skip {
+                summary.skipped += 1;
+                continue;
+            }
+            announcement.id = previous.id;
+            statements.push(doc! {
+                "q": { "_id": previous.id },
+                "u": announcement_document(&announcement)?,
+            });
+            pending.push(PendingWrite { announcement, revision });
+        }
+
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        let command = doc! {
+            "update": &self.collection_name,
+            "updates": statements,
+            "ordered": false,
+        };
+        let reply: UpdateReply = bson::from_document(self.database().run_command(command, None).await?)?;
+        if let Some(error) = reply.write_concern_error {
+            anyhow::bail!("MongoDB 寫入確認失敗: {}", error);
+        }
+
+        summary.inserted += reply.upserted.len() as u64;
+        summary.updated += reply.modified as u64;
+
+        let mut failed = HashSet::new();
+        for error in &reply.write_errors {
+            summary.failures.push(write_failure(&pending[error.index].announcement, error));
+            failed.insert(error.index);
+        }
+
+        // 只為寫入成功的公告保留舊版本
+        let (revisions, announcements): (Vec<AnnouncementRevision>, Vec<&Announcement>) = pending
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !failed.contains(index))
+            .filter_map(|(_, write)| write.revision.clone().map(|revision| (revision, &write.announcement)))
+            .unzip();
+        let failed = self
+            .insert_unordered(&self.revisions_collection(), &revisions, &announcements, summary)
+            .await?;
+        summary.revised += (revisions.len() - failed.len()) as u64;
+
+        Ok(())
+    }
+}
+
+/// 寫入 MongoDB 的公告文件
+///
+/// `bson::to_document` 以人類可讀模式序列化，日期會變成字串；這裡與 driver 寫入時相同，
+/// 以 raw 序列化器輸出，`date`、`announced_at` 等欄位存為 BSON 日期
+pub fn announcement_document(announcement: &Announcement) -> Result<Document> {
+    Ok(bson::to_raw_document_buf(announcement)?.to_document()?)
+}
+
+/// replace 範圍對應的 MongoDB 篩選條件
+pub fn scope_filter(scope: &ReplaceScope) -> Document {
+    match scope {
+        ReplaceScope::Day { query_date, market } => {
+            let mut filter = doc! { "query_date": query_date.to_bson() };
+            if let Some(market) = market {
+                filter.insert("market", market);
+            }
+            filter
+        }
+        ReplaceScope::CompanyRange { company_code, from, to } => doc! {
+            "company_code": company_code,
+            "date": { "$gte": from.to_bson(), "$lte": to.to_bson() },
+        },
+    }
+}
+
+/// 公告識別條件：公司代號 + 日期 + 時間 + 標題
+pub fn identity_filter(announcement: &Announcement) -> Document {
+    doc! {
+        "company_code": &announcement.company_code,
+        "date": announcement.date.to_bson(),
+        "time": &announcement.time,
+        "title": &announcement.title
+    }
+}
+
+/// 識別鍵 (公司代號 + 日期 + 時間 + 標題) 的唯一索引，由伺服器保證同一則公告只有一筆
+pub fn identity_index() -> mongodb::IndexModel {
+    mongodb::IndexModel::builder()
+        .keys(doc! { "company_code": 1, "date": 1, "time": 1, "title": 1 })
+        .options(
+            mongodb::options::IndexOptions::builder()
+                .name(IDENTITY_INDEX_NAME.to_string())
+                .unique(true)
+                .build(),
+        )
+        .build()
+}
+
+/// 查詢條件對應的 MongoDB 篩選條件，`query` 與 `count` 共用；`after` 游標也轉為條件
+pub fn query_filter(query: &AnnouncementQuery) -> Document {
+    let mut filter = doc! {};
+
+    match query.companies.as_slice() {
+        [] => {}
+        [company] => {
+            filter.insert("company_code", company);
+        }
+        companies => {
+            filter.insert("company_code", doc! { "$in": companies });
+        }
+    }
+
+    if let Some(market) = query.market.as_deref().filter(|m| !m.is_empty() && *m != "all") {
+        filter.insert("market", market);
+    }
+
+    if !query.clause_codes.is_empty() {
+        filter.insert("clause_code", doc! { "$in": &query.clause_codes });
+    }
+
+    // 事實發生日只比對 BSON 日期，舊版字串格式需先執行 migrate
+    let mut occurrence = doc! {};
+    if let Some(start) = query.occurrence_start_date {
+        occurrence.insert("$gte", start.to_bson());
+    }
+    if let Some(end) = query.occurrence_end_date {
+        occurrence.insert("$lte", end.to_bson());
+    }
+    if !occurrence.is_empty() {
+        filter.insert("fact_occurrence_date", occurrence);
+    }
+
+    // 發言時間皆為 HH:MM:SS，字串比較即為時間先後
+    let mut time = doc! {};
+    if let Some(from) = &query.time_from {
+        time.insert("$gte", from);
+    }
+    if let Some(to) = &query.time_to {
+        time.insert("$lte", to);
+    }
+    if !time.is_empty() {
+        filter.insert("time", time);
+    }
+
+    // 日期範圍只比對發言日期，由 (date, time) 索引支援
+    let mut range = doc! {};
+    if let Some(start) = query.start_date {
+        range.insert("$gte", start.to_bson());
+    }
+    if let Some(end) = query.end_date {
+        range.insert("$lte", end.to_bson());
+    }
+    if !range.is_empty() {
+        filter.insert("date", range);
+    }
+
+    if let Some(date) = query.query_date {
+        filter.insert("query_date", date.to_bson());
+    }
+
+    let mut and_conditions = Vec::new();
+
+    // 游標之後：公告時間較早，或時間相同但 _id 較小；缺少 announced_at 的舊文件排在最後
+    if let Some(cursor) = query.after {
+        and_conditions.push(match cursor.announced_at {
+            Some(announced_at) => {
+                let announced_at = bson::DateTime::from_chrono(announced_at);
+                doc! { "$or": [
+                    { "announced_at": { "$lt": announced_at } },
+                    { "announced_at": announced_at, "_id": { "$lt": cursor.id } },
+                    { "announced_at": Bson::Null },
+                ] }
+            }
+            None => doc! { "announced_at": Bson::Null, "_id": { "$lt": cursor.id } },
+        });
+    }
+
+    if !and_conditions.is_empty() {
+        filter.insert("$and", and_conditions);
+    }
+
+    // 關鍵字搜尋：跳脫後為字面比對，與其他後端語意相同
+    if let Some(search) = query.search.as_deref().filter(|s| !s.is_empty()) {
+        filter.insert("title", doc! { "$regex": regex::escape(search), "$options": "i" });
+    }
+
+    // created_at 為 BSON 日期，以秒為單位比較；舊版的字串擷取時間需先執行 `migrate` 轉換
+    if let Some(since) = query.created_since {
+        filter.insert("created_at", doc! { "$gte": bson::DateTime::from_chrono(since.trunc_subsecs(0)) });
+    }
+
+    filter
+}
+
+fn detect_date_format(date_str: &str) -> String {
+    if date_str.contains('-') {
+        if date_str.len() == 10 && date_str.matches('-').count() == 2 {
+            "YYYY-MM-DD".to_string()
+        } else {
+            "other_dash".to_string()
+        }
+    } else if date_str.contains('/') {
+        let parts: Vec<&str> = date_str.split('/').collect();
+        if parts.len() == 3 {
+            let year_len = parts[0].len();
+            if year_len == 3 {
+                "YYY/MM/DD_roc".to_string()
+            } else if year_len == 4 {
+                "YYYY/MM/DD".to_string()
+            } else {
+                "other_slash".to_string()
+            }
+        } else {
+            "invalid_slash".to_string()
+        }
+    } else {
+        "unknown".to_string()
+    }
+}
+
+// $group 的 $sum 結果依大小可能是 int32 或 int64
+fn group_count(doc: &bson::RawDocument) -> Option<i64> {
+    match doc.get("count").ok()?? {
+        bson::RawBsonRef::Int32(count) => Some(count as i64),
+        bson::RawBsonRef::Int64(count) => Some(count),
+        _ => None,
+    }
+}
+
+// 依 query_date 分組的 _id 可能是 BSON 日期或舊版字串，統一轉為 YYYY-MM-DD
+fn group_key_to_date(doc: &bson::RawDocument) -> Option<String> {
+    match doc.get("_id").ok()?? {
+        bson::RawBsonRef::DateTime(datetime) => Some(RocDate::from_bson(datetime).to_gregorian_string()),
+        bson::RawBsonRef::String(date) => Some(date.to_string()),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl AnnouncementStore for MongoStore {
+    fn backend(&self) -> &'static str {
+        "MongoDB"
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        // 建立索引以提升查詢效能
+        let indexes = vec![
+            mongodb::IndexModel::builder()
+                .keys(doc! { "company_code": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "company_code": 1, "announced_at": -1, "_id": -1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "clause_code": 1, "announced_at": -1, "_id": -1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "fact_occurrence_date": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "time": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "query_date": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "date": 1, "time": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "created_at": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "announced_at": -1, "_id": -1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "market": 1, "query_date": 1 })
+                .build(),
+            mongodb::IndexModel::builder()
+                .keys(doc! { "announcement_id": 1 })
+                .build(),
+        ];
+
+        match self.raw_collection().create_indexes(indexes, None).await {
+            Ok(_) => println!("MongoDB 索引建立完成"),
+            Err(e) => println!("警告：建立 MongoDB 索引失敗: {}", e),
+        }
+
+        // 唯一索引另外建立：既有資料有重複時建立失敗，不影響其他索引
+        if let Err(e) = self.raw_collection().create_index(identity_index(), None).await {
+            println!("警告：建立識別鍵唯一索引失敗，集合中可能已有重複公告: {}", e);
+            println!("      請先執行 `dedupe` 試算，再以 `dedupe --apply` 合併重複資料");
+        }
+
+        let revision_index = mongodb::IndexModel::builder()
+            .keys(doc! { "announcement_id": 1, "revision": 1 })
+            .options(mongodb::options::IndexOptions::builder().unique(true).build())
+            .build();
+        if let Err(e) = self.revisions_collection().create_index(revision_index, None).await {
+            println!("警告：建立版本紀錄索引失敗: {}", e);
+        }
+
+        // 初始化條款代號對照表，已有資料時略過
+        let collection: Collection<ClauseCode> = self.database().collection(CLAUSE_CODES_COLLECTION);
+        if collection.count_documents(doc! {}, None).await? > 0 {
+            println!("📋 條款代號對照表已存在，跳過初始化");
+            return Ok(());
+        }
+
+        println!("📋 正在初始化條款代號對照表...");
+        let documents: Vec<ClauseCode> = CLAUSE_CODES
+            .iter()
+            .map(|(code, description)| ClauseCode {
+                id: None,
+                code: code.to_string(),
+                description: description.to_string(),
+                created_at: Some(chrono::Utc::now()),
+            })
+            .collect();
+        collection.insert_many(documents, None).await?;
+        println!("✅ 條款代號對照表初始化完成，共 {} 筆資料", CLAUSE_CODES.len());
+
+        Ok(())
+    }
+
+    async fn save(&self, announcements: &[Announcement], scope: &ReplaceScope, mode: DuplicateMode) -> Result<SaveSummary> {
+        let collection = self.collection();
+        let mut summary = SaveSummary::default();
+
+        // 準備要插入的資料，移除 raw_html
+        let docs_to_insert: Vec<Announcement> = announcements.iter().map(|announcement| {
+            let mut doc = announcement.clone();
+            doc.id = None; // 讓 MongoDB 自動生成 ObjectId
+            doc.raw_html = None; // 移除 raw_html 欄位以減少資料庫大小
+            doc
+        }).collect();
+
+        match mode {
+            DuplicateMode::Upsert | DuplicateMode::Skip => {
+                let mut docs_to_insert = docs_to_insert.into_iter().peekable();
+                while docs_to_insert.peek().is_some() {
+                    let batch: Vec<Announcement> = docs_to_insert.by_ref().take(BULK_BATCH_SIZE).collect();
+                    self.save_batch(batch, mode, &mut summary).await?;
+                }
+            }
+            DuplicateMode::Replace => {
+                // 刪除前先讀取範圍內的舊資料，用於比對內容是否更正
+                let mut previous: HashMap<AnnouncementKey, Announcement> = HashMap::new();
+                let mut cursor = collection.find(scope_filter(scope), None).await?;
+                while cursor.advance().await? {
+                    let announcement: Announcement = cursor.deserialize_current()?;
+                    previous.insert(announcement.key(), announcement);
+                }
+
+                // 只取代本次查詢範圍（例如日期 + 市場別）的資料，避免誤刪其他市場或公司
+                let delete_result = collection.delete_many(scope_filter(scope), None).await?;
+                summary.deleted = delete_result.deleted_count;
+
+                let mut docs_to_insert = docs_to_insert;
+                let mut revisions = Vec::new();
+                for doc in &mut docs_to_insert {
+                    match previous.get(&doc.key()) {
+                        Some(existing) => revisions.push(next_revision(existing, doc)),
+                        None => {
+                            first_revision(doc);
+                            revisions.push(None);
+                        }
+                    }
+                }
+
+                let announcements: Vec<&Announcement> = docs_to_insert.iter().collect();
+                let failed = self.insert_unordered(&collection, &docs_to_insert, &announcements, &mut summary).await?;
+                summary.inserted = (docs_to_insert.len() - failed.len()) as u64;
+
+                // 只為寫入成功的公告保留舊版本
+                let (revisions, announcements): (Vec<AnnouncementRevision>, Vec<&Announcement>) = revisions
+                    .into_iter()
+                    .zip(&docs_to_insert)
+                    .enumerate()
+                    .filter(|(index, _)| !failed.contains(index))
+                    .filter_map(|(_, (revision, doc))| revision.map(|revision| (revision, doc)))
+                    .unzip();
+                let failed = self
+                    .insert_unordered(&self.revisions_collection(), &revisions, &announcements, &mut summary)
+                    .await?;
+                summary.revised = (revisions.len() - failed.len()) as u64;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn query(&self, query: &AnnouncementQuery) -> Result<Vec<Announcement>> {
+        // 依公告時間、_id 降序排序，尚未執行 migrate 而缺少 announced_at 的文件排在最後
+        let mut find_options = FindOptions::builder()
+            .skip(query.skip)
+            .sort(doc! { "announced_at": -1, "_id": -1 })
+            .build();
+        find_options.limit = query.limit.map(|limit| limit as i64);
+
+        let mut cursor = self.collection().find(query_filter(query), find_options).await?;
+        let mut announcements = Vec::new();
+        while cursor.advance().await? {
+            announcements.push(cursor.deserialize_current()?);
+        }
+
+        Ok(announcements)
+    }
+
+    async fn count(&self, query: &AnnouncementQuery) -> Result<u64> {
+        let query = AnnouncementQuery { after: None, ..query.clone() };
+        Ok(self.raw_collection().count_documents(query_filter(&query), None).await?)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Announcement>> {
+        let filter = match bson::oid::ObjectId::parse_str(id) {
+            Ok(object_id) => doc! { "_id": object_id },
+            Err(_) => doc! { "announcement_id": id },
+        };
+        Ok(self.collection().find_one(filter, None).await?)
+    }
+
+    async fn revisions(&self, announcement_id: &str) -> Result<Vec<AnnouncementRevision>> {
+        let find_options = FindOptions::builder().sort(doc! { "revision": 1 }).build();
+        let mut cursor = self
+            .revisions_collection()
+            .find(doc! { "announcement_id": announcement_id }, find_options)
+            .await?;
+
+        let mut revisions = Vec::new();
+        while cursor.advance().await? {
+            revisions.push(cursor.deserialize_current()?);
+        }
+        Ok(revisions)
+    }
+
+    async fn known_keys(&self, date: RocDate) -> Result<HashSet<AnnouncementKey>> {
+        // 相容舊版民國字串日期
+        let filter = doc! { "$or": [
+            { "date": date.to_bson() },
+            { "date": date.to_roc_string() },
+        ] };
+        let options = FindOptions::builder()
+            .projection(doc! { "company_code": 1, "time": 1, "title": 1 })
+            .build();
+
+        let mut keys = HashSet::new();
+        let mut cursor = self.raw_collection().find(filter, options).await?;
+        while cursor.advance().await? {
+            let document: Document = cursor.deserialize_current()?;
+            if let (Ok(code), Ok(time), Ok(title)) =
+                (document.get_str("company_code"), document.get_str("time"), document.get_str("title"))
+            {
+                keys.insert((code.to_string(), date, time.to_string(), title.to_string()));
+            }
+        }
+
+        Ok(keys)
+    }
+
+    async fn company_counts(&self, limit: usize) -> Result<Vec<CompanyCount>> {
+        let pipeline = vec![
+            doc! {
+                "$group": {
+                    "_id": {
+                        "company_code": "$company_code",
+                        "company_name": "$company_name"
+                    },
+                    "count": { "$sum": 1 }
+                }
+            },
+            doc! { "$sort": { "count": -1 } },
+            doc! { "$limit": limit as i64 }
+        ];
+
+        let mut cursor = self.raw_collection().aggregate(pipeline, None).await?;
+        let mut companies = Vec::new();
+        while cursor.advance().await? {
+            let doc = cursor.current();
+            if let (Ok(id_doc), Some(count)) = (doc.get_document("_id"), group_count(doc)) {
+                if let (Ok(code), Ok(name)) = (id_doc.get_str("company_code"), id_doc.get_str("company_name")) {
+                    companies.push(CompanyCount {
+                        company_code: code.to_string(),
+                        company_name: name.to_string(),
+                        count,
+                    });
+                }
+            }
+        }
+
+        Ok(companies)
+    }
+
+    async fn date_counts(&self, limit: usize) -> Result<Vec<DateCount>> {
+        let pipeline = vec![
+            doc! {
+                "$group": {
+                    "_id": "$query_date",
+                    "count": { "$sum": 1 }
+                }
+            },
+            doc! { "$sort": { "_id": -1 } },
+            doc! { "$limit": limit as i64 }
+        ];
+
+        let mut cursor = self.raw_collection().aggregate(pipeline, None).await?;
+        let mut dates = Vec::new();
+        while cursor.advance().await? {
+            let doc = cursor.current();
+            if let (Some(date), Some(count)) = (group_key_to_date(doc), group_count(doc)) {
+                dates.push(DateCount { date, count });
+            }
+        }
+
+        Ok(dates)
+    }
+
+    async fn clause_codes(&self) -> Result<Vec<ClauseCode>> {
+        let collection: Collection<ClauseCode> = self.database().collection(CLAUSE_CODES_COLLECTION);
+        let mut cursor = collection.find(doc! {}, None).await?;
+
+        let mut clause_codes = Vec::new();
+        while cursor.advance().await? {
+            clause_codes.push(cursor.deserialize_current()?);
+        }
+
+        sort_clause_codes(&mut clause_codes);
+        Ok(clause_codes)
+    }
+
+    async fn diagnostics(&self) -> Result<serde_json::Value> {
+        // 以原始文件取樣分析前 100 筆，才能分辨 BSON 日期與各種字串格式
+        let mut stats: HashMap<String, u64> = HashMap::new();
+        let find_options = FindOptions::builder().limit(100).build();
+        let mut cursor = self.raw_collection().find(doc! {}, find_options).await?;
+        while cursor.advance().await? {
+            let document: Document = cursor.deserialize_current()?;
+            for field in ["query_date", "date", "fact_date", "fact_occurrence_date"] {
+                let format_type = match document.get(field) {
+                    Some(Bson::DateTime(_)) => "bson_date".to_string(),
+                    Some(Bson::String(date_str)) => detect_date_format(date_str),
+                    Some(_) => "other_type".to_string(),
+                    None => continue,
+                };
+                *stats.entry(format!("{}_{}", field, format_type)).or_insert(0) += 1;
+            }
+        }
+
+        Ok(serde_json::json!({
+            "backend": self.backend(),
+            "database": self.database_name,
+            "collection": self.collection_name,
+            "date_format_stats": stats,
+        }))
+    }
+}
@@ -0,0 +1,575 @@
+//! 內嵌 SQLite 儲存後端，適合筆電與 CI 等沒有 MongoDB 的環境。
+//!
+//! 常用的篩選欄位各自存成欄位並建立索引，日期存為 `YYYY-MM-DD` 文字以便直接比較大小；
+//! 完整公告 (不含 `raw_html`) 以 JSON 存於 `data` 欄位。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::{
+    created_since_text, next_revision, search_key, sort_clause_codes, AnnouncementQuery, AnnouncementStore, CompanyCount, DateCount, DuplicateMode, ReplaceScope,
+    SaveSummary,
+};
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementKey, AnnouncementRevision, ClauseCode, CLAUSE_CODES};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS announcements (
+    id TEXT PRIMARY KEY,
+    announcement_id TEXT,
+    company_code TEXT NOT NULL,
+    company_name TEXT NOT NULL,
+    title TEXT NOT NULL,
+    search_title TEXT,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    announced_at TEXT,
+    query_date TEXT,
+    fact_date TEXT,
+    fact_occurrence_date TEXT,
+    market TEXT,
+    clause_code TEXT,
+    created_at TEXT,
+    data TEXT NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_announcements_identity ON announcements (company_code, date, time, title);
+CREATE INDEX IF NOT EXISTS idx_announcements_query_date ON announcements (query_date);
+CREATE INDEX IF NOT EXISTS idx_announcements_date_time ON announcements (date, time);
+CREATE INDEX IF NOT EXISTS idx_announcements_cursor ON announcements (announced_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_announcements_market_query_date ON announcements (market, query_date);
+CREATE INDEX IF NOT EXISTS idx_announcements_company_cursor ON announcements (company_code, announced_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_announcements_clause_code ON announcements (clause_code, announced_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_announcements_time ON announcements (time);
+CREATE INDEX IF NOT EXISTS idx_announcements_created_at ON announcements (created_at);
+CREATE INDEX IF NOT EXISTS idx_announcements_announcement_id ON announcements (announcement_id);
+CREATE INDEX IF NOT EXISTS idx_announcements_fact_occurrence_date ON announcements (fact_occurrence_date);
+CREATE TABLE IF NOT EXISTS clause_codes (
+    code TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    created_at TEXT
+);
+CREATE TABLE IF NOT EXISTS announcement_revisions (
+    announcement_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    content_hash TEXT NOT NULL,
+    superseded_at TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (announcement_id, revision)
+);
+";
+
+// 資料表結構的版本，記錄於 PRAGMA user_version；之後變更結構時遞增並加上遷移步驟
+const SCHEMA_VERSION: i32 = 1;
+
+/// SQLite 儲存後端
+#[derive(Clone)]
+pub struct SqliteStore {
+    path: String,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// 開啟 (或建立) 資料庫檔案並建立資料表
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+        if version > SCHEMA_VERSION {
+            anyhow::bail!("SQLite 資料庫 {} 的 schema 版本 {} 比程式支援的 {} 新", path, version, SCHEMA_VERSION);
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self {
+            path: path.to_string(),
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    // 在 blocking 執行緒上使用連線，避免阻塞 async runtime
+    async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| anyhow::anyhow!("SQLite 連線已損毀"))?;
+            f(&mut conn)
+        })
+        .await?
+    }
+}
+
+// 一筆公告對應的欄位值
+struct AnnouncementRow {
+    announcement_id: Option<String>,
+    company_code: String,
+    company_name: String,
+    title: String,
+    search_title: String,
+    date: String,
+    time: String,
+    announced_at: Option<String>,
+    query_date: Option<String>,
+    fact_date: Option<String>,
+    fact_occurrence_date: Option<String>,
+    market: Option<String>,
+    clause_code: Option<String>,
+    created_at: Option<String>,
+    data: String,
+}
+
+impl AnnouncementRow {
+    fn new(announcement: &Announcement) -> Result<Self> {
+        let mut data = announcement.clone();
+        data.id = None; // id 另存於 id 欄位
+        data.raw_html = None; // 移除 raw_html 欄位以減少資料庫大小
+
+        Ok(Self {
+            announcement_id: announcement.announcement_id.clone(),
+            company_code: announcement.company_code.clone(),
+            company_name: announcement.company_name.clone(),
+            title: announcement.title.clone(),
+            search_title: search_key(&announcement.title),
+            date: announcement.date.to_gregorian_string(),
+            time: announcement.time.clone(),
+            announced_at: announcement.announced_at.map(|t| t.to_rfc3339()),
+            query_date: announcement.query_date.map(|d| d.to_gregorian_string()),
+            fact_date: announcement.fact_date.map(|d| d.to_gregorian_string()),
+            fact_occurrence_date: announcement.fact_occurrence_date.map(|d| d.to_gregorian_string()),
+            market: announcement.market.clone(),
+            clause_code: announcement.clause_code.clone(),
+            created_at: announcement.created_at.map(|t| t.to_rfc3339()),
+            data: serde_json::to_string(&data)?,
+        })
+    }
+
+    fn insert(&self, conn: &Connection, id: &str) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT OR REPLACE INTO announcements
+                 (id, announcement_id, company_code, company_name, title, search_title, date, time, announced_at, query_date,
+                  fact_date, fact_occurrence_date, market, clause_code, created_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                id, self.announcement_id, self.company_code, self.company_name, self.title, self.search_title, self.date, self.time,
+                self.announced_at,
+                self.query_date, self.fact_date, self.fact_occurrence_date, self.market, self.clause_code, self.created_at,
+                self.data
+            ],
+        )
+    }
+}
+
+// 由 id 與 data 欄位還原公告，舊資料補上穩定識別碼
+fn row_to_announcement(id: String, data: String) -> Result<Announcement> {
+    let mut announcement: Announcement = serde_json::from_str(&data)?;
+    announcement.id = bson::oid::ObjectId::parse_str(&id).ok();
+    announcement.ensure_identity();
+    Ok(announcement)
+}
+
+fn insert_revision(conn: &Connection, revision: &AnnouncementRevision) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO announcement_revisions (announcement_id, revision, content_hash, superseded_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            revision.announcement_id,
+            revision.revision,
+            revision.content_hash,
+            revision.superseded_at.map(|t| t.to_rfc3339()),
+            serde_json::to_string(revision)?
+        ],
+    )?;
+    Ok(())
+}
+
+// replace 範圍對應的 WHERE 子句與參數
+fn scope_clause(scope: &ReplaceScope) -> (&'static str, Vec<Value>) {
+    match scope {
+        ReplaceScope::Day { query_date, market: Some(market) } => (
+            "query_date = ? AND market = ?",
+            vec![Value::Text(query_date.to_gregorian_string()), Value::Text(market.clone())],
+        ),
+        ReplaceScope::Day { query_date, market: None } => {
+            ("query_date = ?", vec![Value::Text(query_date.to_gregorian_string())])
+        }
+        ReplaceScope::CompanyRange { company_code, from, to } => (
+            "company_code = ? AND date BETWEEN ? AND ?",
+            vec![
+                Value::Text(company_code.clone()),
+                Value::Text(from.to_gregorian_string()),
+                Value::Text(to.to_gregorian_string()),
+            ],
+        ),
+    }
+}
+
+// IN (...) 使用的參數佔位符
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+// 查詢條件對應的 WHERE 子句與參數
+fn where_clause(query: &AnnouncementQuery) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+
+    if !query.companies.is_empty() {
+        conditions.push(format!("company_code IN ({})", placeholders(query.companies.len())));
+        values.extend(query.companies.iter().map(|company| Value::Text(company.clone())));
+    }
+
+    if let Some(market) = query.market.as_deref().filter(|m| !m.is_empty() && *m != "all") {
+        conditions.push("market = ?".to_string());
+        values.push(Value::Text(market.to_string()));
+    }
+
+    if !query.clause_codes.is_empty() {
+        conditions.push(format!("clause_code IN ({})", placeholders(query.clause_codes.len())));
+        values.extend(query.clause_codes.iter().map(|code| Value::Text(code.clone())));
+    }
+
+    if let Some(start) = query.occurrence_start_date {
+        conditions.push("fact_occurrence_date >= ?".to_string());
+        values.push(Value::Text(start.to_gregorian_string()));
+    }
+    if let Some(end) = query.occurrence_end_date {
+        conditions.push("fact_occurrence_date <= ?".to_string());
+        values.push(Value::Text(end.to_gregorian_string()));
+    }
+
+    // 發言時間皆為 HH:MM:SS，文字比較即為時間先後
+    if let Some(from) = &query.time_from {
+        conditions.push("time >= ?".to_string());
+        values.push(Value::Text(from.clone()));
+    }
+    if let Some(to) = &query.time_to {
+        conditions.push("time <= ?".to_string());
+        values.push(Value::Text(to.clone()));
+    }
+
+    // 日期範圍只比對發言日期
+    if let Some(start) = query.start_date {
+        conditions.push("date >= ?".to_string());
+        values.push(Value::Text(start.to_gregorian_string()));
+    }
+    if let Some(end) = query.end_date {
+        conditions.push("date <= ?".to_string());
+        values.push(Value::Text(end.to_gregorian_string()));
+    }
+
+    if let Some(date) = query.query_date {
+        conditions.push("query_date = ?".to_string());
+        values.push(Value::Text(date.to_gregorian_string()));
+    }
+
+    if let Some(since) = query.created_since {
+        conditions.push("created_at >= ?".to_string());
+        values.push(Value::Text(created_since_text(since)));
+    }
+
+    // 關鍵字搜尋：與 Unicode 小寫的標題做字面子字串比對
+    if let Some(search) = query.search.as_deref().filter(|s| !s.is_empty()) {
+        conditions.push("instr(search_title, ?) > 0".to_string());
+        values.push(Value::Text(search_key(search)));
+    }
+
+    // 游標之後：公告時間較早，或時間相同但 id 較小；NULL 在降冪排序中排在最後
+    if let Some(cursor) = query.after {
+        match cursor.announced_at {
+            Some(announced_at) => {
+                let announced_at = announced_at.to_rfc3339();
+                conditions.push("(announced_at < ? OR (announced_at = ? AND id < ?) OR announced_at IS NULL)".to_string());
+                values.push(Value::Text(announced_at.clone()));
+                values.push(Value::Text(announced_at));
+            }
+            None => conditions.push("(announced_at IS NULL AND id < ?)".to_string()),
+        }
+        values.push(Value::Text(cursor.id.to_hex()));
+    }
+
+    if conditions.is_empty() {
+        (String::new(), values)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), values)
+    }
+}
+
+fn save_blocking(
+    conn: &mut Connection,
+    announcements: &[Announcement],
+    scope: &ReplaceScope,
+    mode: DuplicateMode,
+) -> Result<SaveSummary> {
+    let tx = conn.transaction()?;
+    let mut summary = SaveSummary::default();
+
+    // 只取代本次查詢範圍的資料，避免誤刪其他市場或公司；刪除前先讀取舊資料，用於比對內容是否更正
+    let mut previous: HashMap<AnnouncementKey, Announcement> = HashMap::new();
+    if mode == DuplicateMode::Replace {
+        let (condition, values) = scope_clause(scope);
+        let rows: Vec<(String, String)> = tx
+            .prepare(&format!("SELECT id, data FROM announcements WHERE {}", condition))?
+            .query_map(params_from_iter(values.clone()), |r| Ok((r.get(0)?, r.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, data) in rows {
+            let announcement = row_to_announcement(id, data)?;
+            previous.insert(announcement.key(), announcement);
+        }
+
+        summary.deleted =
+            tx.execute(&format!("DELETE FROM announcements WHERE {}", condition), params_from_iter(values))? as u64;
+    }
+
+    for announcement in announcements {
+        let mut incoming = announcement.clone();
+
+        // 識別鍵：公司代號 + 日期 + 時間 + 標題
+        let existing: Option<(String, String)> = tx
+            .query_row(
+                "SELECT id, data FROM announcements WHERE company_code = ?1 AND date = ?2 AND time = ?3 AND title = ?4",
+                params![
+                    incoming.company_code,
+                    incoming.date.to_gregorian_string(),
+                    incoming.time,
+                    incoming.title
+                ],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()?;
+
+        match existing {
+            Some((id, data)) => {
+                let revision = next_revision(&row_to_announcement(id.clone(), data.clone())?, &mut incoming);
+                if revision.is_none() && mode == DuplicateMode::Skip {
+                    summary.skipped += 1;
+                    continue;
+                }
+                if let Some(revision) = revision {
+                    insert_revision(&tx, &revision)?;
+                    summary.revised += 1;
+                }
+
+                let row = AnnouncementRow::new(&incoming)?;
+                if data != row.data {
+                    row.insert(&tx, &id)?;
+                    summary.updated += 1;
+                }
+            }
+            None => {
+                match previous.get(&incoming.key()) {
+                    Some(replaced) => {
+                        if let Some(revision) = next_revision(replaced, &mut incoming) {
+                            insert_revision(&tx, &revision)?;
+                            summary.revised += 1;
+                        }
+                    }
+                    None => {
+                        incoming.ensure_identity();
+                        incoming.revision = Some(1);
+                    }
+                }
+                AnnouncementRow::new(&incoming)?.insert(&tx, &bson::oid::ObjectId::new().to_hex())?;
+                summary.inserted += 1;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(summary)
+}
+
+#[async_trait]
+impl AnnouncementStore for SqliteStore {
+    fn backend(&self) -> &'static str {
+        "SQLite"
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        self.call(|conn| {
+            conn.execute_batch(SCHEMA)?;
+
+            // 初始化條款代號對照表，已有資料時略過
+            let count: i64 = conn.query_row("SELECT COUNT(*) FROM clause_codes", [], |r| r.get(0))?;
+            if count > 0 {
+                println!("📋 條款代號對照表已存在，跳過初始化");
+                return Ok(());
+            }
+
+            println!("📋 正在初始化條款代號對照表...");
+            let tx = conn.transaction()?;
+            let created_at = Utc::now().to_rfc3339();
+            for (code, description) in CLAUSE_CODES {
+                tx.execute(
+                    "INSERT INTO clause_codes (code, description, created_at) VALUES (?1, ?2, ?3)",
+                    params![code, description, created_at],
+                )?;
+            }
+            tx.commit()?;
+            println!("✅ 條款代號對照表初始化完成，共 {} 筆資料", CLAUSE_CODES.len());
+            Ok(())
+        })
+        .await
+    }
+
+    async fn save(&self, announcements: &[Announcement], scope: &ReplaceScope, mode: DuplicateMode) -> Result<SaveSummary> {
+        let announcements = announcements.to_vec();
+        let scope = scope.clone();
+        self.call(move |conn| save_blocking(conn, &announcements, &scope, mode)).await
+    }
+
+    async fn query(&self, query: &AnnouncementQuery) -> Result<Vec<Announcement>> {
+        let query = query.clone();
+        self.call(move |conn| {
+            let (where_sql, mut values) = where_clause(&query);
+            // LIMIT -1 表示不限筆數
+            values.push(Value::Integer(query.limit.map_or(-1, |limit| limit as i64)));
+            values.push(Value::Integer(query.skip as i64));
+
+            let sql = format!(
+                "SELECT id, data FROM announcements{} ORDER BY announced_at DESC, id DESC LIMIT ? OFFSET ?",
+                where_sql
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values), |r| Ok((r.get(0)?, r.get(1)?)))?;
+
+            let mut announcements = Vec::new();
+            for row in rows {
+                let (id, data) = row?;
+                announcements.push(row_to_announcement(id, data)?);
+            }
+            Ok(announcements)
+        })
+        .await
+    }
+
+    async fn count(&self, query: &AnnouncementQuery) -> Result<u64> {
+        let query = AnnouncementQuery { after: None, ..query.clone() };
+        self.call(move |conn| {
+            let (where_sql, values) = where_clause(&query);
+            let sql = format!("SELECT COUNT(*) FROM announcements{}", where_sql);
+            let count: i64 = conn.query_row(&sql, params_from_iter(values), |r| r.get(0))?;
+            Ok(count as u64)
+        })
+        .await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Announcement>> {
+        let id = id.to_string();
+        self.call(move |conn| {
+            let row: Option<(String, String)> = conn
+                .query_row(
+                    "SELECT id, data FROM announcements WHERE id = ?1 OR announcement_id = ?1",
+                    params![id],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .optional()?;
+            row.map(|(id, data)| row_to_announcement(id, data)).transpose()
+        })
+        .await
+    }
+
+    async fn revisions(&self, announcement_id: &str) -> Result<Vec<AnnouncementRevision>> {
+        let announcement_id = announcement_id.to_string();
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT data FROM announcement_revisions WHERE announcement_id = ?1 ORDER BY revision",
+            )?;
+            let rows = stmt.query_map(params![announcement_id], |r| r.get::<_, String>(0))?;
+            let mut revisions = Vec::new();
+            for data in rows {
+                revisions.push(serde_json::from_str(&data?)?);
+            }
+            Ok(revisions)
+        })
+        .await
+    }
+
+    async fn known_keys(&self, date: RocDate) -> Result<HashSet<AnnouncementKey>> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT company_code, time, title FROM announcements WHERE date = ?1")?;
+            let rows = stmt.query_map(params![date.to_gregorian_string()], |r| {
+                Ok((r.get::<_, String>(0)?, date, r.get::<_, String>(1)?, r.get::<_, String>(2)?))
+            })?;
+            Ok(rows.collect::<rusqlite::Result<HashSet<_>>>()?)
+        })
+        .await
+    }
+
+    async fn company_counts(&self, limit: usize) -> Result<Vec<CompanyCount>> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT company_code, company_name, COUNT(*) AS count FROM announcements
+                 GROUP BY company_code, company_name ORDER BY count DESC, company_code LIMIT ?1",
+            )?;
+            let rows = stmt.query_map(params![limit as i64], |r| {
+                Ok(CompanyCount {
+                    company_code: r.get(0)?,
+                    company_name: r.get(1)?,
+                    count: r.get(2)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn date_counts(&self, limit: usize) -> Result<Vec<DateCount>> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT query_date, COUNT(*) FROM announcements WHERE query_date IS NOT NULL
+                 GROUP BY query_date ORDER BY query_date DESC LIMIT ?1",
+            )?;
+            let rows = stmt.query_map(params![limit as i64], |r| {
+                Ok(DateCount {
+                    date: r.get(0)?,
+                    count: r.get(1)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn clause_codes(&self) -> Result<Vec<ClauseCode>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare("SELECT code, description, created_at FROM clause_codes")?;
+            let rows = stmt.query_map([], |r| {
+                let created_at: Option<String> = r.get(2)?;
+                Ok(ClauseCode {
+                    id: None,
+                    code: r.get(0)?,
+                    description: r.get(1)?,
+                    created_at: created_at
+                        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                        .map(|t| t.with_timezone(&Utc)),
+                })
+            })?;
+            let mut clause_codes = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+            sort_clause_codes(&mut clause_codes);
+            Ok(clause_codes)
+        })
+        .await
+    }
+
+    async fn diagnostics(&self) -> Result<serde_json::Value> {
+        let path = self.path.clone();
+        self.call(move |conn| {
+            let sqlite_version: String = conn.query_row("SELECT sqlite_version()", [], |r| r.get(0))?;
+            let clause_codes: i64 = conn.query_row("SELECT COUNT(*) FROM clause_codes", [], |r| r.get(0))?;
+            let revisions: i64 = conn.query_row("SELECT COUNT(*) FROM announcement_revisions", [], |r| r.get(0))?;
+            Ok(serde_json::json!({
+                "backend": "SQLite",
+                "path": path,
+                "sqlite_version": sqlite_version,
+                "clause_codes": clause_codes,
+                "revisions": revisions,
+            }))
+        })
+        .await
+    }
+}
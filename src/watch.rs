@@ -0,0 +1,61 @@
+//! 監看模式的新公告判斷：以識別鍵 (公司代號 + 日期 + 時間 + 標題) 比對今日已知的公告，
+//! 同一則公告的內容雜湊改變時視為更正，與新公告一樣需要保存並輸出。
+
+use std::collections::HashMap;
+
+use crate::model::{Announcement, AnnouncementKey};
+
+/// 今日已知的公告，識別鍵對應內容雜湊
+#[derive(Debug, Default, Clone)]
+pub struct SeenAnnouncements {
+    // 自資料庫載入的公告尚未看過內容，雜湊為 None
+    known: HashMap<AnnouncementKey, Option<String>>,
+}
+
+impl SeenAnnouncements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 以資料庫中已有的識別鍵建立
+    pub fn from_keys(keys: impl IntoIterator<Item = AnnouncementKey>) -> Self {
+        Self { known: keys.into_iter().map(|key| (key, None)).collect() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.known.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+
+    /// 篩出未看過或內容已更正的公告
+    ///
+    /// 自資料庫載入、尚未看過內容的公告第一次出現時只記錄內容雜湊，不視為更正。
+    /// 篩出的公告要等保存成功後再以 [`mark`](Self::mark) 標記，失敗時下次查詢會再次篩出。
+    pub fn diff(&mut self, announcements: Vec<Announcement>) -> Vec<Announcement> {
+        announcements
+            .into_iter()
+            .filter(|announcement| match self.known.get_mut(&announcement.key()) {
+                None => true,
+                Some(hash @ None) => {
+                    *hash = Some(content_hash(announcement));
+                    false
+                }
+                Some(Some(hash)) => *hash != content_hash(announcement),
+            })
+            .collect()
+    }
+
+    /// 標記為已知並記錄目前的內容雜湊
+    pub fn mark(&mut self, announcements: &[Announcement]) {
+        for announcement in announcements {
+            self.known.insert(announcement.key(), Some(content_hash(announcement)));
+        }
+    }
+}
+
+fn content_hash(announcement: &Announcement) -> String {
+    announcement.content_hash.clone().unwrap_or_else(|| announcement.compute_content_hash())
+}
@@ -0,0 +1,268 @@
+//! JSON API，所有端點位於 `/api/v1` 之下：
+//!
+//! | 端點 | 說明 |
+//! |------|------|
+//! | `GET /api/v1/announcements` | 公告列表，查詢參數見 [`ListParams`]，回傳 [`AnnouncementPage`] |
+//! | `GET /api/v1/search` | 標題與內容全文檢索，`q` 加上公告列表的篩選參數，回傳 [`SearchPage`] |
+//! | `GET /api/v1/announcements/:id` | 單筆公告，id 可為資料庫 id 或 announcement_id |
+//! | `GET /api/v1/announcements/:id/revisions` | 目前版本與依版本號排序的舊版本 |
+//! | `GET /api/v1/clause-codes` | 條款代號對照表 |
+//! | `GET /api/v1/stats` | 公告總數、前 10 家公司與最近 30 個查詢日期的數量 |
+//! | `GET /api/v1/diagnostics` | 儲存後端狀態與前 5 筆資料的摘要 |
+//!
+//! OpenAPI 文件由 [`ApiDoc`] 產生，位於 `GET /api/openapi.json`。
+//! 錯誤一律回傳 [`ApiError`]：參數格式錯誤 400 (含參數名稱)、找不到公告 404、
+//! 儲存後端無法使用 503、全文檢索索引等內部錯誤 500。
+//!
+//! 舊版的 `/api/announcements`、`/api/announcements/:id/revisions`、`/api/clause-codes`、
+//! `/api/stats` 與 `/api/debug` 仍保留，其中 `/api/announcements` 只回傳公告陣列 (預設 50 筆)。
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use utoipa::{OpenApi, ToSchema};
+
+use super::error::{ApiError, ApiQuery, ErrorCode};
+use super::query::{self, AnnouncementPage, ListParams, RevisionHistory, SearchPage, SearchText, Stats};
+use super::AppState;
+use crate::model::{Announcement, AnnouncementDetail, AnnouncementRevision, ClauseCode, DetailItem};
+use crate::search::{Highlights, SearchHit};
+use crate::store::{AnnouncementQuery, CompanyCount, DateCount};
+
+/// `/api/v1/announcements` 未指定 `limit` 時的每頁筆數
+pub const DEFAULT_LIMIT: u64 = 50;
+
+/// `/api/v1/search` 未指定 `limit` 時的每頁筆數
+pub const DEFAULT_SEARCH_LIMIT: u64 = 20;
+
+/// API 的 OpenAPI 文件
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "台灣證交所重大訊息 API", description = "查詢已儲存的重大訊息公告、版本紀錄與統計資料"),
+    paths(
+        announcements_handler,
+        search_handler,
+        announcement_handler,
+        revisions_handler,
+        clause_codes_handler,
+        stats_handler,
+        diagnostics_handler
+    ),
+    components(schemas(
+        Announcement,
+        AnnouncementDetail,
+        DetailItem,
+        AnnouncementRevision,
+        AnnouncementPage,
+        SearchPage,
+        SearchHit,
+        Highlights,
+        RevisionHistory,
+        ClauseCode,
+        CompanyCount,
+        DateCount,
+        Stats,
+        Diagnostics,
+        ApiError,
+        ErrorCode
+    )),
+    tags((name = "announcements", description = "重大訊息公告"), (name = "reference", description = "對照表與統計"))
+)]
+pub struct ApiDoc;
+
+pub(crate) fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/openapi.json", get(openapi_handler))
+        .route("/api/v1/announcements", get(announcements_handler))
+        .route("/api/v1/search", get(search_handler))
+        .route("/api/v1/announcements/:id", get(announcement_handler))
+        .route("/api/v1/announcements/:id/revisions", get(revisions_handler))
+        .route("/api/v1/clause-codes", get(clause_codes_handler))
+        .route("/api/v1/stats", get(stats_handler))
+        .route("/api/v1/diagnostics", get(diagnostics_handler))
+        // 舊版路徑
+        .route("/api/announcements", get(legacy_announcements_handler))
+        .route("/api/announcements/:id/revisions", get(revisions_handler))
+        .route("/api/clause-codes", get(clause_codes_handler))
+        .route("/api/stats", get(stats_handler))
+        .route("/api/debug", get(diagnostics_handler))
+}
+
+async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// 查詢公告
+#[utoipa::path(
+    get,
+    path = "/api/v1/announcements",
+    tag = "announcements",
+    params(ListParams),
+    responses(
+        (status = 200, description = "一頁公告，依公告時間、id 由新到舊排序", body = AnnouncementPage),
+        (status = 400, description = "查詢參數格式錯誤", body = ApiError),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn announcements_handler(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<Json<AnnouncementPage>, ApiError> {
+    let page = query::list_announcements(state.store.as_ref(), &params, DEFAULT_LIMIT).await?;
+    Ok(Json(page))
+}
+
+/// 全文檢索標題與內容
+#[utoipa::path(
+    get,
+    path = "/api/v1/search",
+    tag = "announcements",
+    params(SearchText, ListParams),
+    responses(
+        (status = 200, description = "依相關性排序的一頁結果，含標示查詢詞的標題與內容摘要；不支援 cursor 與 search 參數", body = SearchPage),
+        (status = 400, description = "檢索字串或查詢參數格式錯誤", body = ApiError),
+        (status = 500, description = "全文檢索索引錯誤", body = ApiError),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn search_handler(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(text): ApiQuery<SearchText>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<Json<SearchPage>, ApiError> {
+    let page =
+        query::search_announcements(&state.store, &state.search, &text, &params, DEFAULT_SEARCH_LIMIT).await?;
+    Ok(Json(page))
+}
+
+// 舊版列表只回傳公告陣列
+async fn legacy_announcements_handler(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<Json<Vec<Announcement>>, ApiError> {
+    let announcements = state.store.query(&params.to_query(DEFAULT_LIMIT)?).await?;
+    Ok(Json(announcements))
+}
+
+/// 讀取單筆公告
+#[utoipa::path(
+    get,
+    path = "/api/v1/announcements/{id}",
+    tag = "announcements",
+    params(("id" = String, Path, description = "資料庫 id 或 announcement_id")),
+    responses(
+        (status = 200, description = "公告", body = Announcement),
+        (status = 404, description = "找不到公告", body = ApiError),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn announcement_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Announcement>, ApiError> {
+    Ok(Json(query::announcement(state.store.as_ref(), &id).await?))
+}
+
+/// 公告的版本紀錄
+#[utoipa::path(
+    get,
+    path = "/api/v1/announcements/{id}/revisions",
+    tag = "announcements",
+    params(("id" = String, Path, description = "資料庫 id 或 announcement_id")),
+    responses(
+        (status = 200, description = "目前版本與舊版本", body = RevisionHistory),
+        (status = 404, description = "找不到公告", body = ApiError),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn revisions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<RevisionHistory>, ApiError> {
+    Ok(Json(query::revision_history(state.store.as_ref(), &id).await?))
+}
+
+/// 條款代號對照表
+#[utoipa::path(
+    get,
+    path = "/api/v1/clause-codes",
+    tag = "reference",
+    responses(
+        (status = 200, description = "依代號數字排序的條款代號", body = Vec<ClauseCode>),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn clause_codes_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ClauseCode>>, ApiError> {
+    Ok(Json(state.store.clause_codes().await?))
+}
+
+/// 公告統計
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    tag = "reference",
+    responses(
+        (status = 200, description = "公告總數、前 10 家公司與最近 30 個查詢日期的數量", body = Stats),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Stats>, ApiError> {
+    Ok(Json(query::stats(state.store.as_ref(), 10, 30).await?))
+}
+
+/// 儲存後端狀態與資料格式抽樣
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Diagnostics {
+    /// 前 5 筆公告的主要欄位
+    #[schema(value_type = Vec<Object>)]
+    pub debug_info: Vec<serde_json::Value>,
+    pub total_count: u64,
+    /// 各儲存後端提供的狀態資訊
+    #[schema(value_type = Object)]
+    pub store: serde_json::Value,
+}
+
+/// 儲存後端診斷資訊
+#[utoipa::path(
+    get,
+    path = "/api/v1/diagnostics",
+    tag = "reference",
+    responses(
+        (status = 200, description = "儲存後端狀態與資料抽樣", body = Diagnostics),
+        (status = 503, description = "儲存後端無法使用", body = ApiError)
+    )
+)]
+async fn diagnostics_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Diagnostics>, ApiError> {
+    let sample = AnnouncementQuery { limit: Some(5), ..Default::default() };
+    let announcements = state.store.query(&sample).await?;
+
+    let debug_info = announcements.iter().map(|announcement| {
+        serde_json::json!({
+            "company_code": announcement.company_code,
+            "company_name": announcement.company_name,
+            "date": announcement.date,
+            "time": announcement.time,
+            "query_date": announcement.query_date,
+            "fact_date": announcement.fact_date,
+            "created_at": announcement.created_at,
+            "title": announcement.title.chars().take(50).collect::<String>() + "..."
+        })
+    }).collect();
+
+    Ok(Json(Diagnostics {
+        debug_info,
+        total_count: state.store.count(&AnnouncementQuery::default()).await?,
+        store: state.store.diagnostics().await?,
+    }))
+}
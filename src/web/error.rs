@@ -0,0 +1,122 @@
+//! API 的 JSON 錯誤回應。
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 錯誤代碼
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 查詢參數格式錯誤 (400)
+    InvalidParameter,
+    /// 找不到指定的公告 (404)
+    NotFound,
+    /// 儲存後端無法使用或查詢失敗 (503)
+    StoreUnavailable,
+    /// 伺服器內部錯誤，例如全文檢索索引失敗 (500)
+    Internal,
+}
+
+impl ErrorCode {
+    /// 對應的 HTTP 狀態碼
+    pub fn status(self) -> StatusCode {
+        match self {
+            Self::InvalidParameter => StatusCode::BAD_REQUEST,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::StoreUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// 錯誤回應內容
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    /// 錯誤說明
+    pub message: String,
+    /// 格式錯誤的查詢參數名稱
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+}
+
+impl ApiError {
+    pub fn invalid_parameter(parameter: &str, message: impl Into<String>) -> Self {
+        Self { code: ErrorCode::InvalidParameter, message: message.into(), parameter: Some(parameter.to_string()) }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self { code: ErrorCode::NotFound, message: message.into(), parameter: None }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self { code: ErrorCode::Internal, message: message.into(), parameter: None }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.code.status()
+    }
+}
+
+// 記錄詳細原因，回應只說明錯誤類別：來自資料庫驅動程式的錯誤為 503，其他 (索引、序列化等) 為 500
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        println!("❌ 查詢失敗: {:#}", error);
+        if is_store_error(&error) {
+            Self { code: ErrorCode::StoreUnavailable, message: "儲存後端暫時無法使用，請稍後再試".to_string(), parameter: None }
+        } else {
+            Self::internal("伺服器內部錯誤")
+        }
+    }
+}
+
+// 錯誤鏈中是否包含 MongoDB 或 SQLite 的錯誤 (連線失敗、資料表不存在等)
+fn is_store_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<mongodb::error::Error>() || cause.is::<rusqlite::Error>())
+}
+
+// 查詢字串格式錯誤：回報無法解析的參數名稱
+impl From<serde_path_to_error::Error<serde_urlencoded::de::Error>> for ApiError {
+    fn from(error: serde_path_to_error::Error<serde_urlencoded::de::Error>) -> Self {
+        let parameter = error.path().to_string();
+        match parameter.as_str() {
+            "." => Self { code: ErrorCode::InvalidParameter, message: format!("查詢參數格式錯誤: {}", error.inner()), parameter: None },
+            _ => {
+                let message = format!("查詢參數 {} 格式錯誤: {}", parameter, error.inner());
+                Self::invalid_parameter(&parameter, message)
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+/// 與 [`axum::extract::Query`] 相同，解析失敗時回傳 JSON 錯誤並指出格式錯誤的參數
+pub struct ApiQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        let deserializer = serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+        let value = serde_path_to_error::deserialize(deserializer)?;
+        Ok(Self(value))
+    }
+}
@@ -0,0 +1,41 @@
+//! Web 查看器：伺服器端渲染頁面與 `/api/v1` JSON API，兩者共用同一個查詢層。
+//!
+//! - [`query`]：查詢參數、分頁與統計
+//! - [`api`]：JSON 端點與 OpenAPI 文件
+//! - [`pages`]：askama 模板頁面與單頁式搜尋介面
+//! - [`error`]：JSON 錯誤回應
+
+pub mod api;
+pub mod error;
+pub mod pages;
+pub mod query;
+
+use axum::Router;
+use std::sync::Arc;
+use tower_http::{cors::CorsLayer, services::ServeDir};
+
+use crate::search::SearchIndex;
+use crate::store::AnnouncementStore;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub store: Arc<dyn AnnouncementStore>,
+    pub search: Arc<SearchIndex>,
+}
+
+/// 建立 Web 應用程式，資料來源為任一 [`AnnouncementStore`]，全文索引建立於記憶體中
+pub fn create_app(store: Arc<dyn AnnouncementStore>) -> Router {
+    create_app_with_search(store, Arc::new(SearchIndex::in_memory()))
+}
+
+/// 建立 Web 應用程式並使用指定的全文索引
+pub fn create_app_with_search(store: Arc<dyn AnnouncementStore>, search: Arc<SearchIndex>) -> Router {
+    let state = AppState { store, search };
+
+    Router::new()
+        .merge(pages::routes())
+        .merge(api::routes())
+        .nest_service("/static", ServeDir::new("static"))
+        .layer(CorsLayer::permissive())
+        .with_state(Arc::new(state))
+}
@@ -0,0 +1,164 @@
+//! 伺服器端渲染的頁面：公告列表、明細、統計與單頁式搜尋介面。
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+
+use super::error::ApiError;
+use super::query::{self, AnnouncementPage, ListParams};
+use super::AppState;
+use crate::model::{Announcement, AnnouncementRevision};
+use crate::store::{CompanyCount, DateCount};
+
+/// 列表頁未指定 `limit` 時的每頁筆數
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+
+// 分頁列最多顯示的頁碼數
+const PAGER_WIDTH: u64 = 10;
+
+// 單頁式搜尋介面，資料由瀏覽器向 /api/v1 查詢
+const SEARCH_PAGE: &str = include_str!("../../templates/search.html");
+
+#[derive(Template)]
+#[template(path = "index.html")]
+struct IndexTemplate {
+    page: AnnouncementPage,
+    params: ListParams,
+    total: u64,
+    total_pages: u64,
+    page_links: Vec<PageLink>,
+    previous_link: Option<String>,
+    next_link: Option<String>,
+    companies: Vec<CompanyCount>,
+    dates: Vec<String>,
+}
+
+// 分頁列的一個頁碼
+struct PageLink {
+    number: u64,
+    href: String,
+    active: bool,
+}
+
+impl IndexTemplate {
+    // 沒有資料庫 id 時改用 announcement_id
+    fn detail_link(&self, announcement: &Announcement) -> String {
+        let id = match announcement.id {
+            Some(id) => id.to_hex(),
+            None => announcement.announcement_id.clone().unwrap_or_else(|| announcement.stable_id()),
+        };
+        format!("/detail/{}", id)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "detail.html")]
+struct DetailTemplate {
+    announcement: Announcement,
+    revisions: Vec<AnnouncementRevision>,
+}
+
+#[derive(Template)]
+#[template(path = "stats.html")]
+struct StatsTemplate {
+    company_stats: Vec<CompanyCount>,
+    date_stats: Vec<DateCount>,
+    total_announcements: u64,
+}
+
+pub(crate) fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(index_handler))
+        .route("/detail/:id", get(detail_handler))
+        .route("/stats", get(stats_handler))
+        .route("/search", get(search_handler))
+}
+
+// 頁面錯誤以純文字顯示說明，狀態碼與 API 相同
+struct PageError(ApiError);
+
+impl From<ApiError> for PageError {
+    fn from(error: ApiError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for PageError {
+    fn into_response(self) -> Response {
+        (self.0.status(), self.0.message).into_response()
+    }
+}
+
+fn render(template: impl Template) -> Result<Html<String>, PageError> {
+    template.render().map(Html).map_err(|e| {
+        println!("❌ 頁面渲染失敗: {}", e);
+        PageError(ApiError::internal("頁面渲染失敗"))
+    })
+}
+
+async fn index_handler(
+    State(state): State<Arc<AppState>>,
+    Query(mut params): Query<ListParams>,
+) -> Result<impl IntoResponse, PageError> {
+    // 列表頁以頁碼分頁，一律計算總筆數
+    params.cursor = None;
+    params.with_total = None;
+
+    let store = state.store.as_ref();
+    let page = query::list_announcements(store, &params, DEFAULT_PAGE_SIZE).await?;
+    let current = params.page();
+    let total = page.total.unwrap_or_default();
+    let total_pages = page.total_pages.unwrap_or_default();
+
+    // 篩選選項：公告最多的公司與最近的查詢日期
+    let companies = store.company_counts(100).await.map_err(ApiError::from)?;
+    let dates = store
+        .date_counts(30)
+        .await
+        .map_err(ApiError::from)?
+        .into_iter()
+        .map(|date_count| date_count.date)
+        .collect();
+
+    // 以目前頁碼為中心的頁碼範圍，連結保留篩選條件
+    let link = |number: u64| format!("/?{}", params.page_query_string(number));
+    let first = current.saturating_sub(PAGER_WIDTH / 2).max(1);
+    let last = (first + PAGER_WIDTH - 1).min(total_pages);
+    let page_links = (first..=last)
+        .map(|number| PageLink { number, href: link(number), active: number == current })
+        .collect();
+    let previous_link = (current > 1).then(|| link(current - 1));
+    let next_link = (current < total_pages).then(|| link(current + 1));
+
+    render(IndexTemplate { page, params, total, total_pages, page_links, previous_link, next_link, companies, dates })
+}
+
+async fn detail_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, PageError> {
+    let history = query::revision_history(state.store.as_ref(), &id).await?;
+
+    render(DetailTemplate { announcement: history.current, revisions: history.revisions })
+}
+
+async fn stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, PageError> {
+    let stats = query::stats(state.store.as_ref(), 20, 30).await?;
+
+    render(StatsTemplate {
+        company_stats: stats.top_companies,
+        date_stats: stats.recent_dates,
+        total_announcements: stats.total_announcements,
+    })
+}
+
+async fn search_handler() -> Html<&'static str> {
+    Html(SEARCH_PAGE)
+}
@@ -0,0 +1,373 @@
+//! 頁面與 API 共用的查詢層：查詢參數、分頁與統計。
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+use super::error::ApiError;
+use crate::date::RocDate;
+use crate::model::{Announcement, AnnouncementRevision, CLAUSE_CODES};
+use crate::search::{SearchHit, SearchIndex};
+use crate::store::{AnnouncementQuery, AnnouncementStore, CompanyCount, Cursor, DateCount};
+
+/// 每頁筆數上限
+pub const MAX_LIMIT: u64 = 1000;
+
+/// 全文檢索每頁筆數上限
+pub const MAX_SEARCH_LIMIT: u64 = 100;
+
+/// 公告列表的查詢參數
+///
+/// 日期接受西元或民國格式，無法辨識時回傳 400；指定 `start_date`/`end_date` 時忽略 `date`。
+/// `company` 與 `clause_code` 可用逗號分隔多個值。表單送出的空字串視為未指定。分頁可用 `page` 或上一頁回傳的 `cursor`，兩者不可同時指定。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// 公司代號，多家以逗號分隔 (例如 2330,2317)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    /// 查詢日期 (YYYY-MM-DD 或民國 YYY/MM/DD)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// 發言日期起日 (含)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    /// 發言日期迄日 (含)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    /// 市場別 (sii, otc, rotc, pub)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    /// 條款代號，多個以逗號分隔 (例如 11,20)，代號見 `/api/v1/clause-codes`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clause_code: Option<String>,
+    /// 事實發生日起始日期 (含)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurrence_start_date: Option<String>,
+    /// 事實發生日結束日期 (含)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurrence_end_date: Option<String>,
+    /// 發言時間下限 (含)，HH:MM 或 HH:MM:SS，例如 17:00 查詢盤後公告
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_from: Option<String>,
+    /// 發言時間上限 (含)，HH:MM 或 HH:MM:SS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_to: Option<String>,
+    /// 標題關鍵字
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// 頁碼，由 1 開始
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[param(minimum = 1)]
+    pub page: Option<u64>,
+    /// 每頁筆數，最多 1000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[param(minimum = 1, maximum = 1000)]
+    pub limit: Option<u64>,
+    /// 上一頁回傳的 `next_cursor`，從該筆之後繼續讀取
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// 是否計算總筆數，預設只在第一頁 (未指定 `cursor`) 計算；游標查詢時設為 true 才會每頁計數
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_total: Option<bool>,
+}
+
+impl ListParams {
+    /// 目前頁碼
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// 每頁筆數，未指定時使用 `default`，最多 [`MAX_LIMIT`] 筆
+    pub fn limit(&self, default: u64) -> u64 {
+        self.limit.unwrap_or(default).clamp(1, MAX_LIMIT)
+    }
+
+    /// 是否計算總筆數，未指定時只有第一頁計算，游標翻頁不重複計數
+    pub fn with_total(&self) -> bool {
+        self.with_total.unwrap_or_else(|| non_empty(&self.cursor).is_none())
+    }
+
+    /// 解析 `cursor`，格式錯誤或與 `page` 同時指定時回傳錯誤
+    pub fn cursor(&self) -> Result<Option<Cursor>, ApiError> {
+        let Some(token) = non_empty(&self.cursor) else {
+            return Ok(None);
+        };
+        if self.page.is_some() {
+            return Err(ApiError::invalid_parameter("cursor", "cursor 與 page 不可同時指定"));
+        }
+        Cursor::decode(&token)
+            .map(Some)
+            .ok_or_else(|| ApiError::invalid_parameter("cursor", format!("無效的游標: {}", token)))
+    }
+
+    /// 對應的儲存層查詢條件 (含分頁)，日期、時間或條款代號無法辨識、範圍顛倒或游標無效時回傳錯誤
+    pub fn to_query(&self, default_limit: u64) -> Result<AnnouncementQuery, ApiError> {
+        let (start_date, end_date) = parse_date_range(("start_date", &self.start_date), ("end_date", &self.end_date))?;
+        let (occurrence_start_date, occurrence_end_date) = parse_date_range(
+            ("occurrence_start_date", &self.occurrence_start_date),
+            ("occurrence_end_date", &self.occurrence_end_date),
+        )?;
+        let time_from = parse_time("time_from", &self.time_from)?;
+        let time_to = parse_time("time_to", &self.time_to)?;
+        if let (Some(from), Some(to)) = (&time_from, &time_to) {
+            if from > to {
+                return Err(ApiError::invalid_parameter("time_from", format!("起始時間 {} 晚於結束時間 {}", from, to)));
+            }
+        }
+        let clause_codes = split_list(&self.clause_code);
+        if let Some(code) = clause_codes.iter().find(|code| !CLAUSE_CODES.iter().any(|(known, _)| known == code)) {
+            return Err(ApiError::invalid_parameter("clause_code", format!("未知的條款代號: {}", code)));
+        }
+        let limit = self.limit(default_limit);
+        let after = self.cursor()?;
+
+        Ok(AnnouncementQuery {
+            companies: split_list(&self.company),
+            market: non_empty(&self.market),
+            clause_codes,
+            // 未指定日期範圍時才使用單一日期
+            query_date: match (start_date, end_date) {
+                (None, None) => parse_date("date", &self.date)?,
+                _ => None,
+            },
+            start_date,
+            end_date,
+            occurrence_start_date,
+            occurrence_end_date,
+            time_from,
+            time_to,
+            search: non_empty(&self.search),
+            created_since: None,
+            skip: if after.is_some() { 0 } else { (self.page() - 1) * limit },
+            limit: Some(limit),
+            after,
+        })
+    }
+
+    /// 保留篩選條件、切換到指定頁碼的查詢字串，供分頁連結使用
+    pub fn page_query_string(&self, page: u64) -> String {
+        let mut params = self.clone();
+        params.page = Some(page);
+        params.cursor = None;
+        serde_urlencoded::to_string(&params).unwrap_or_default()
+    }
+
+    // 查詢條件是否相同 (忽略空字串)，供頁面標示目前的選項
+    pub(crate) fn is_company(&self, code: &str) -> bool {
+        split_list(&self.company).iter().any(|company| company == code)
+    }
+
+    pub(crate) fn is_date(&self, date: &str) -> bool {
+        non_empty(&self.date).as_deref() == Some(date)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+// 逗號分隔的多個值，忽略空白項目
+fn split_list(value: &Option<String>) -> Vec<String> {
+    value
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_date(parameter: &str, value: &Option<String>) -> Result<Option<RocDate>, ApiError> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    RocDate::parse(&value).map(Some).ok_or_else(|| {
+        ApiError::invalid_parameter(
+            parameter,
+            format!("無法辨識的日期: {}，請使用 YYYY-MM-DD 或民國 YYY/MM/DD", value),
+        )
+    })
+}
+
+// 起訖日期，起始日期晚於結束日期時以起始日期的參數回報錯誤
+fn parse_date_range(
+    (start_parameter, start): (&str, &Option<String>),
+    (end_parameter, end): (&str, &Option<String>),
+) -> Result<(Option<RocDate>, Option<RocDate>), ApiError> {
+    let start_date = parse_date(start_parameter, start)?;
+    let end_date = parse_date(end_parameter, end)?;
+    if let (Some(start), Some(end)) = (start_date, end_date) {
+        if start > end {
+            return Err(ApiError::invalid_parameter(
+                start_parameter,
+                format!("起始日期 {} 晚於結束日期 {}", start, end),
+            ));
+        }
+    }
+    Ok((start_date, end_date))
+}
+
+// 發言時間統一為儲存格式 HH:MM:SS
+fn parse_time(parameter: &str, value: &Option<String>) -> Result<Option<String>, ApiError> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    NaiveTime::parse_from_str(&value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(&value, "%H:%M"))
+        .map(|time| Some(time.format("%H:%M:%S").to_string()))
+        .map_err(|_| ApiError::invalid_parameter(parameter, format!("無法辨識的時間: {}，請使用 HH:MM 或 HH:MM:SS", value)))
+}
+
+/// 一頁公告與分頁資訊
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementPage {
+    /// 依公告時間、id 由新到舊排序
+    pub items: Vec<Announcement>,
+    pub limit: u64,
+    /// 讀取下一頁用的游標，已是最後一頁時省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// 符合條件的總筆數，游標翻頁或 `with_total=false` 時省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// 目前頁碼，以游標查詢時省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+    /// 總頁數，以游標查詢或未計算總筆數時省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<u64>,
+}
+
+/// 依查詢參數取得一頁公告
+pub async fn list_announcements(
+    store: &dyn AnnouncementStore,
+    params: &ListParams,
+    default_limit: u64,
+) -> Result<AnnouncementPage, ApiError> {
+    let mut query = params.to_query(default_limit)?;
+    let limit = query.limit.unwrap_or(default_limit);
+
+    // 多取一筆判斷是否還有下一頁
+    query.limit = Some(limit + 1);
+    let mut items = store.query(&query).await?;
+    let next_cursor = if items.len() as u64 > limit {
+        items.truncate(limit as usize);
+        items.last().and_then(Cursor::after).map(|cursor| cursor.encode())
+    } else {
+        None
+    };
+
+    let total = if params.with_total() { Some(store.count(&query).await?) } else { None };
+    let page = query.after.is_none().then(|| params.page());
+
+    Ok(AnnouncementPage {
+        items,
+        limit,
+        next_cursor,
+        total,
+        page,
+        total_pages: page.and(total).map(|total| total.div_ceil(limit)),
+    })
+}
+
+/// 全文檢索的查詢字串
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchText {
+    /// 檢索語法：空白分隔的詞須全部符合，多字詞須連續出現，支援 "片語"、OR、-排除與 title:/detail: 欄位
+    pub q: Option<String>,
+}
+
+/// 一頁全文檢索結果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchPage {
+    /// 依相關性由高到低排序
+    pub items: Vec<SearchHit>,
+    /// 符合條件的總筆數
+    pub total: u64,
+    pub page: u64,
+    pub limit: u64,
+    pub total_pages: u64,
+}
+
+/// 以全文索引檢索標題與內容，篩選參數與公告列表相同
+pub async fn search_announcements(
+    store: &Arc<dyn AnnouncementStore>,
+    index: &Arc<SearchIndex>,
+    text: &SearchText,
+    params: &ListParams,
+    default_limit: u64,
+) -> Result<SearchPage, ApiError> {
+    let Some(text) = non_empty(&text.q) else {
+        return Err(ApiError::invalid_parameter("q", "請指定檢索字串 q"));
+    };
+    if non_empty(&params.search).is_some() {
+        return Err(ApiError::invalid_parameter("search", "全文檢索請使用 q，search 只用於公告列表的標題篩選"));
+    }
+    if non_empty(&params.cursor).is_some() {
+        return Err(ApiError::invalid_parameter("cursor", "全文檢索依相關性排序，請改用 page 分頁"));
+    }
+    let filter = params.to_query(default_limit)?;
+    let limit = params.limit(default_limit).min(MAX_SEARCH_LIMIT);
+    let query = index
+        .parse(&text)
+        .map_err(|e| ApiError::invalid_parameter("q", format!("無法解析的檢索字串 {}: {}", text, e)))?;
+
+    // 超過同步間隔時在背景同步，本次查詢使用目前的索引
+    index.refresh_in_background(store).await?;
+    let results = index.search(query.as_ref(), &filter, ((params.page() - 1) * limit) as usize, limit as usize)?;
+
+    Ok(SearchPage {
+        items: results.hits,
+        total: results.total,
+        page: params.page(),
+        limit,
+        total_pages: results.total.div_ceil(limit),
+    })
+}
+
+/// 讀取單筆公告；id 可為資料庫 id 或 announcement_id，不存在時回傳 404
+pub async fn announcement(store: &dyn AnnouncementStore, id: &str) -> Result<Announcement, ApiError> {
+    let mut announcement = store
+        .get(id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("找不到公告 {}", id)))?;
+    announcement.ensure_identity();
+    Ok(announcement)
+}
+
+/// 公告的目前版本與舊版本
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevisionHistory {
+    pub announcement_id: String,
+    pub current: Announcement,
+    /// 依版本號由舊到新排序
+    pub revisions: Vec<AnnouncementRevision>,
+}
+
+/// 取得公告及其舊版本
+pub async fn revision_history(store: &dyn AnnouncementStore, id: &str) -> Result<RevisionHistory, ApiError> {
+    let current = announcement(store, id).await?;
+    let announcement_id = current.announcement_id.clone().unwrap_or_default();
+    let revisions = store.revisions(&announcement_id).await?;
+    Ok(RevisionHistory { announcement_id, current, revisions })
+}
+
+/// 公告總數與各公司、各日期的數量
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Stats {
+    pub total_announcements: u64,
+    pub top_companies: Vec<CompanyCount>,
+    pub recent_dates: Vec<DateCount>,
+}
+
+/// 統計資料，公司與日期各取前 `companies`、`dates` 筆
+pub async fn stats(store: &dyn AnnouncementStore, companies: usize, dates: usize) -> Result<Stats, ApiError> {
+    Ok(Stats {
+        total_announcements: store.count(&AnnouncementQuery::default()).await?,
+        top_companies: store.company_counts(companies).await?,
+        recent_dates: store.date_counts(dates).await?,
+    })
+}
@@ -0,0 +1,84 @@
+//! 原始回應封存：壓縮存放、相同內容去重與清單篩選。
+
+use std::fs;
+use std::path::PathBuf;
+
+use twse_announcements::archive::{ArchiveFilter, ArchiveKind, ArchiveStorage, FetchInfo, HtmlArchive, MANIFEST_FILE};
+use twse_announcements::date::RocDate;
+
+// 每個測試使用獨立的暫存目錄
+fn archive_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("twse_archive_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn list_page(date: RocDate, market: &str) -> FetchInfo {
+    FetchInfo { kind: ArchiveKind::List, date: Some(date), market: Some(market.to_string()), company_code: None }
+}
+
+#[tokio::test]
+async fn stores_compressed_and_loads_back() {
+    let dir = archive_dir("roundtrip");
+    let archive = HtmlArchive::open(&dir).unwrap();
+    let html = "<html><body>重大訊息".repeat(200);
+    let date = RocDate::from_ymd(2025, 8, 15).unwrap();
+
+    let entry = archive.store(&list_page(date, "sii"), "https://mops.twse.com.tw/mops/web/ajax_t05st02", 200, &html).await.unwrap();
+    assert_eq!(entry.storage, ArchiveStorage::File);
+    assert_eq!(entry.size, html.len() as u64);
+    assert!(entry.compressed_size < entry.size);
+    assert_eq!(archive.load(&entry).await.unwrap(), html);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn identical_content_is_stored_once() {
+    let dir = archive_dir("dedup");
+    let archive = HtmlArchive::open(&dir).unwrap();
+    let date = RocDate::from_ymd(2025, 8, 15).unwrap();
+
+    let first = archive.store(&list_page(date, "sii"), "url", 200, "<html>相同</html>").await.unwrap();
+    let second = archive.store(&list_page(date, "sii"), "url", 200, "<html>相同</html>").await.unwrap();
+    archive.store(&list_page(date, "sii"), "url", 503, "<html>忙碌</html>").await.unwrap();
+    assert_eq!(first.sha256, second.sha256);
+
+    // 每次擷取都有清單紀錄，但相同內容只有一個檔案
+    let entries = archive.entries(&ArchiveFilter::default()).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap().lines().count(), 3);
+    let objects: usize = fs::read_dir(dir.join("objects"))
+        .unwrap()
+        .map(|shard| fs::read_dir(shard.unwrap().path()).unwrap().count())
+        .sum();
+    assert_eq!(objects, 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn filters_entries() {
+    let dir = archive_dir("filter");
+    let archive = HtmlArchive::open(&dir).unwrap();
+    let aug15 = RocDate::from_ymd(2025, 8, 15).unwrap();
+    let aug18 = RocDate::from_ymd(2025, 8, 18).unwrap();
+
+    archive.store(&list_page(aug15, "sii"), "url", 200, "a").await.unwrap();
+    archive.store(&list_page(aug15, "otc"), "url", 200, "b").await.unwrap();
+    archive.store(&list_page(aug18, "sii"), "url", 200, "c").await.unwrap();
+    let detail = FetchInfo {
+        kind: ArchiveKind::Detail,
+        date: Some(aug15),
+        market: Some("sii".to_string()),
+        company_code: Some("2330".to_string()),
+    };
+    archive.store(&detail, "url", 200, "d").await.unwrap();
+
+    let count = |filter: ArchiveFilter| archive.entries(&filter).unwrap().len();
+    assert_eq!(count(ArchiveFilter { date: Some(aug15), ..Default::default() }), 3);
+    assert_eq!(count(ArchiveFilter { kind: Some(ArchiveKind::List), market: Some("sii".to_string()), ..Default::default() }), 2);
+    assert_eq!(count(ArchiveFilter { company_code: Some("2330".to_string()), ..Default::default() }), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+//! 回補進度檔：續跑時重試失敗日期，成功後自清單移除。
+
+use std::fs;
+
+use twse_announcements::backfill::BackfillCheckpoint;
+use twse_announcements::date::RocDate;
+
+fn aug(day: u32) -> RocDate {
+    RocDate::from_ymd(2025, 8, day).unwrap()
+}
+
+#[test]
+fn resume_requeues_failed_dates() {
+    let mut checkpoint = BackfillCheckpoint::new(aug(11), aug(22));
+    checkpoint.record(aug(11), true);
+    checkpoint.record(aug(12), false);
+    checkpoint.record(aug(13), true);
+    assert_eq!(checkpoint.last_completed.as_deref(), Some("2025-08-13"));
+    assert_eq!(checkpoint.failed_dates, ["2025-08-12"]);
+
+    // 續跑時：失敗日期與上次完成日期之後的日期需要查詢
+    let pending: Vec<_> = (11..=15).filter(|&day| checkpoint.is_pending(aug(day))).collect();
+    assert_eq!(pending, [12, 14, 15]);
+
+    // 重試成功後移除，上次完成日期不倒退
+    checkpoint.record(aug(12), true);
+    assert!(checkpoint.failed_dates.is_empty());
+    assert_eq!(checkpoint.last_completed.as_deref(), Some("2025-08-13"));
+    assert!(!checkpoint.is_pending(aug(12)));
+
+    // 再次失敗不重複記錄
+    checkpoint.record(aug(14), false);
+    checkpoint.record(aug(14), false);
+    assert_eq!(checkpoint.failed_dates, ["2025-08-14"]);
+}
+
+#[test]
+fn load_keeps_failed_dates_for_the_same_range() {
+    let path = std::env::temp_dir().join(format!("twse_backfill_checkpoint_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let mut checkpoint = BackfillCheckpoint::new(aug(11), aug(22));
+    checkpoint.record(aug(12), false);
+    checkpoint.record(aug(13), true);
+    checkpoint.save(path).unwrap();
+
+    let loaded = BackfillCheckpoint::load(path, aug(11), aug(22)).unwrap();
+    assert_eq!(loaded, checkpoint);
+    assert!(loaded.is_pending(aug(12)));
+    // 範圍不同的進度檔不沿用
+    assert!(BackfillCheckpoint::load(path, aug(11), aug(29)).is_none());
+
+    fs::remove_file(path).unwrap();
+}
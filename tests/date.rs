@@ -0,0 +1,150 @@
+//! 民國／西元日期的解析，以及 JSON 與 BSON 兩種序列化格式的來回轉換。
+
+use bson::{doc, Bson};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use twse_announcements::date::{deserialize_lenient, roc_format, timestamp_format, RocDate};
+
+fn aug17() -> RocDate {
+    RocDate::from_ymd(2025, 8, 17).unwrap()
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    date: RocDate,
+    #[serde(with = "roc_format")]
+    roc_date: RocDate,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    fact_date: Option<RocDate>,
+    #[serde(default, with = "timestamp_format")]
+    announced_at: Option<DateTime<Utc>>,
+}
+
+fn record() -> Record {
+    Record {
+        date: aug17(),
+        roc_date: aug17(),
+        fact_date: RocDate::from_ymd(2025, 8, 15),
+        announced_at: aug17().with_time("17:30:05"),
+    }
+}
+
+#[test]
+fn parse_accepts_roc_and_gregorian_formats() {
+    for input in [
+        "114/08/17",
+        "114/8/17",
+        "2025-08-17",
+        "2025/08/17",
+        "2025.08.17",
+        "20250817",
+        "1140817",
+        "民國114年8月17日",
+        "2025年8月17日",
+        " 114/08/17 (更正) ",
+    ] {
+        assert_eq!(RocDate::parse(input), Some(aug17()), "{}", input);
+    }
+
+    for input in ["", "yesterday", "114/13/01", "2025-02-30", "114/08", "114/08/17/01", "202508", "0/01/01"] {
+        assert_eq!(RocDate::parse(input), None, "{}", input);
+    }
+    assert!("不詳".parse::<RocDate>().unwrap_err().to_string().contains("不詳"));
+}
+
+#[test]
+fn formats_and_times() {
+    let date = aug17();
+    assert_eq!(date.roc_year(), 114);
+    assert_eq!(date.to_roc_string(), "114/08/17");
+    assert_eq!(date.to_roc_string_short(), "114/8/17");
+    assert_eq!(date.to_gregorian_string(), "2025-08-17");
+    assert_eq!(date.to_compact_string(), "20250817");
+    assert_eq!(date.to_string(), "2025-08-17");
+
+    // 公告時間為台灣時間 (UTC+8)
+    assert_eq!(date.with_time("17:30:05"), Some(Utc.with_ymd_and_hms(2025, 8, 17, 9, 30, 5).unwrap()));
+    assert_eq!(date.with_time("08:05"), Some(Utc.with_ymd_and_hms(2025, 8, 17, 0, 5, 0).unwrap()));
+    assert_eq!(date.with_time("下午"), None);
+
+    assert_eq!(RocDate::from_bson(date.to_bson()), date);
+    assert_eq!(date.to_bson().timestamp_millis(), Utc.with_ymd_and_hms(2025, 8, 17, 0, 0, 0).unwrap().timestamp_millis());
+}
+
+#[test]
+fn json_uses_readable_strings() {
+    let value = serde_json::to_value(record()).unwrap();
+    assert_eq!(value, json!({
+        "date": "2025-08-17",
+        "roc_date": "114/08/17",
+        "fact_date": "2025-08-15",
+        "announced_at": "2025-08-17T09:30:05Z",
+    }));
+
+    assert_eq!(serde_json::from_value::<Record>(value).unwrap(), record());
+}
+
+#[test]
+fn bson_uses_dates() {
+    let raw = bson::to_raw_document_buf(&record()).unwrap();
+    let document = raw.to_document().unwrap();
+    assert_eq!(document.get("date"), Some(&Bson::DateTime(aug17().to_bson())));
+    assert_eq!(document.get("roc_date"), Some(&Bson::DateTime(aug17().to_bson())));
+    assert_eq!(document.get("fact_date"), Some(&Bson::DateTime(RocDate::from_ymd(2025, 8, 15).unwrap().to_bson())));
+    assert_eq!(
+        document.get("announced_at"),
+        Some(&Bson::DateTime(bson::DateTime::from_chrono(Utc.with_ymd_and_hms(2025, 8, 17, 9, 30, 5).unwrap())))
+    );
+
+    // 原始 BSON 與 Document 兩種讀取方式都能還原
+    assert_eq!(bson::from_slice::<Record>(raw.as_bytes()).unwrap(), record());
+    assert_eq!(bson::from_document::<Record>(document).unwrap(), record());
+}
+
+#[test]
+fn reads_legacy_strings_and_extended_json() {
+    // 舊資料的字串日期與延伸 JSON 日期
+    let legacy = doc! {
+        "date": "114/08/17",
+        "roc_date": "2025-08-17",
+        "fact_date": "20250815",
+        "announced_at": "2025-08-17T17:30:05+08:00",
+    };
+    assert_eq!(bson::from_document::<Record>(legacy).unwrap(), record());
+
+    let extended = json!({
+        "date": {"$date": {"$numberLong": aug17().to_bson().timestamp_millis().to_string()}},
+        "roc_date": {"$date": "2025-08-17T00:00:00Z"},
+        "fact_date": {"$date": RocDate::from_ymd(2025, 8, 15).unwrap().to_bson().timestamp_millis()},
+        "announced_at": {"$date": "2025-08-17T09:30:05Z"},
+    });
+    assert_eq!(serde_json::from_value::<Record>(extended).unwrap(), record());
+
+    let error = serde_json::from_value::<Record>(json!({"date": "不詳", "roc_date": "114/08/17"})).unwrap_err();
+    assert!(error.to_string().contains("不詳"), "{}", error);
+}
+
+#[test]
+fn lenient_dates_ignore_unreadable_values() {
+    let base = json!({"date": "2025-08-17", "roc_date": "114/08/17"});
+    let with_fact = |fact: serde_json::Value| {
+        let mut value = base.clone();
+        value["fact_date"] = fact;
+        serde_json::from_value::<Record>(value).unwrap().fact_date
+    };
+
+    assert_eq!(serde_json::from_value::<Record>(base.clone()).unwrap().fact_date, None);
+    assert_eq!(with_fact(json!(null)), None);
+    assert_eq!(with_fact(json!("見說明")), None);
+    assert_eq!(with_fact(json!("")), None);
+    assert_eq!(with_fact(json!("114/08/15")), RocDate::from_ymd(2025, 8, 15));
+    assert_eq!(with_fact(json!({"$date": "2025-08-15T00:00:00Z"})), RocDate::from_ymd(2025, 8, 15));
+
+    // BSON 中的 null 與自由文字同樣視為未指定
+    let legacy = doc! {"date": "2025-08-17", "roc_date": "114/08/17", "fact_date": Bson::Null};
+    assert_eq!(bson::from_document::<Record>(legacy).unwrap().fact_date, None);
+    let legacy = doc! {"date": "2025-08-17", "roc_date": "114/08/17", "fact_date": "不詳"};
+    assert_eq!(bson::from_document::<Record>(legacy).unwrap().fact_date, None);
+}
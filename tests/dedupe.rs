@@ -0,0 +1,73 @@
+//! 重複公告合併計畫：保留的文件、補上的欄位與舊版本編號。
+
+use bson::{doc, oid::ObjectId, Bson, Document};
+use chrono::{TimeZone, Utc};
+
+use twse_announcements::dedupe::plan_merge;
+
+fn duplicate(created_at: (u32, u32), detail_content: Option<&str>) -> Document {
+    let mut document = doc! {
+        "_id": ObjectId::new(),
+        "company_code": "2330",
+        "company_name": "台積電",
+        "title": "本公司董事會決議股利分派",
+        "date": "114/08/15",
+        "time": "17:30:00",
+        "created_at": Utc.with_ymd_and_hms(2025, 8, 15, created_at.0, created_at.1, 0).unwrap().to_rfc3339(),
+    };
+    if let Some(content) = detail_content {
+        document.insert("detail_content", content);
+    }
+    document
+}
+
+#[test]
+fn keeps_newest_and_fills_missing_fields() {
+    let mut older = duplicate((9, 0), Some("相同內容"));
+    older.insert("clause_code", "14");
+    let newer = duplicate((10, 0), Some("相同內容"));
+    let newer_id = newer.get("_id").cloned().unwrap();
+    let older_id = older.get("_id").cloned().unwrap();
+
+    let plan = plan_merge(vec![older, newer], 0).unwrap();
+    assert_eq!(plan.keep, newer_id);
+    assert_eq!(plan.remove, [older_id]);
+    assert_eq!(plan.filled_fields, ["clause_code"]);
+    assert_eq!(plan.merged.get_str("clause_code").unwrap(), "14");
+    assert!(plan.revisions.is_empty());
+    assert_eq!(plan.merged.get("revision"), Some(&Bson::Int64(1)));
+    assert_eq!(plan.merged.get_str("announcement_id").unwrap().len(), 32);
+}
+
+#[test]
+fn different_content_becomes_revisions() {
+    let documents = vec![
+        duplicate((11, 0), Some("第三版")),
+        duplicate((9, 0), Some("第一版")),
+        duplicate((10, 0), Some("第二版")),
+        duplicate((9, 30), Some("第一版")),
+    ];
+
+    let plan = plan_merge(documents, 0).unwrap();
+    assert_eq!(plan.remove.len(), 3);
+    assert_eq!(plan.merged.get_str("detail_content").unwrap(), "第三版");
+    assert_eq!(plan.merged.get("revision"), Some(&Bson::Int64(3)));
+
+    // 相同內容只保存一次，依建立時間由舊到新編號
+    let history: Vec<(u32, Option<&str>)> =
+        plan.revisions.iter().map(|r| (r.revision, r.detail_content.as_deref())).collect();
+    assert_eq!(history, [(1, Some("第一版")), (2, Some("第二版"))]);
+    let announcement_id = plan.merged.get_str("announcement_id").unwrap();
+    assert!(plan.revisions.iter().all(|r| r.announcement_id == announcement_id));
+}
+
+#[test]
+fn revisions_continue_after_archived_versions() {
+    let mut current = duplicate((11, 0), Some("新內容"));
+    current.insert("revision", 3);
+    let documents = vec![current, duplicate((10, 0), Some("重複寫入的舊內容"))];
+
+    let plan = plan_merge(documents, 2).unwrap();
+    assert_eq!(plan.revisions.iter().map(|r| r.revision).collect::<Vec<_>>(), [3]);
+    assert_eq!(plan.merged.get("revision"), Some(&Bson::Int64(4)));
+}
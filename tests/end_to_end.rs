@@ -0,0 +1,288 @@
+//! 以本機模擬的 MOPS 伺服器驅動 擷取 → 解析 → 儲存 → Web API 的完整流程，不需連線網路。
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use twse_announcements::client::{RetriesExhausted, TwseClient, TwseClientConfig};
+use twse_announcements::date::RocDate;
+use twse_announcements::mock::{Fault, MockMops};
+use twse_announcements::parser::LayoutChangedError;
+use twse_announcements::store::{AnnouncementStore, DuplicateMode, MemoryStore, ReplaceScope};
+use twse_announcements::web::create_app;
+
+const NORMAL_DAY: &str = include_str!("fixtures/parser/list_normal_day.html");
+const KY_COMPANIES: &str = include_str!("fixtures/parser/list_ky_companies.html");
+const HISTORY: &str = include_str!("fixtures/parser/history_company.html");
+const DETAIL: &str = include_str!("fixtures/parser/detail_correction.html");
+
+fn aug15() -> RocDate {
+    RocDate::from_ymd(2025, 8, 15).unwrap()
+}
+
+// 不限速、退避與冷卻都縮短，讓重試在測試中立即完成
+async fn mock_client() -> (MockMops, TwseClient) {
+    let mock = MockMops::new();
+    mock.set_list(aug15(), "all", NORMAL_DAY);
+    let base_url = mock.spawn().await.unwrap();
+    let client = TwseClient::with_config(TwseClientConfig {
+        base_url,
+        timeout: Duration::from_millis(500),
+        max_retries: 2,
+        backoff_base: Duration::from_millis(10),
+        backoff_max: Duration::from_millis(50),
+        throttle_cooldown: Duration::from_millis(10),
+        requests_per_minute: 0,
+        ..Default::default()
+    });
+    (mock, client)
+}
+
+#[tokio::test]
+async fn fetch_parse_store_and_serve() {
+    let (mock, client) = mock_client().await;
+
+    let (mut announcements, pages) = client.fetch_announcements(aug15(), "sii").await.unwrap();
+    assert_eq!(pages, [("sii", NORMAL_DAY.to_string())]);
+    assert_eq!(announcements.len(), 3);
+    assert!(announcements.iter().all(|a| a.market.as_deref() == Some("sii")));
+
+    // 請求內容與 MOPS 相同：民國年與市場別
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].page, "ajax_t05st02");
+    assert_eq!(requests[0].form["year"], "114");
+    assert_eq!(requests[0].form["TYPEK"], "sii");
+
+    // 與 CLI 相同，以查詢日期標記後寫入
+    for announcement in &mut announcements {
+        announcement.query_date = Some(aug15());
+    }
+    let store = Arc::new(MemoryStore::new());
+    let summary = store.save(&announcements, &ReplaceScope::day(aug15(), "sii"), DuplicateMode::Upsert).await.unwrap();
+    assert_eq!(summary.inserted, 3);
+
+    let response = create_app(store)
+        .oneshot(Request::builder().uri("/api/v1/announcements?date=114/08/15&company=2317").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["items"][0]["title"], "代子公司 Foxconn Industrial Internet 公告取得使用權資產");
+    assert_eq!(body["items"][0]["clause_code"], "20");
+}
+
+#[tokio::test]
+async fn days_without_fixture_are_empty() {
+    let (_mock, client) = mock_client().await;
+    let (announcements, _) = client.fetch_announcements(RocDate::from_ymd(2025, 8, 16).unwrap(), "all").await.unwrap();
+    assert!(announcements.is_empty());
+}
+
+#[tokio::test]
+async fn all_markets_are_fetched_and_tagged_separately() {
+    let mock = MockMops::new();
+    mock.set_list(aug15(), "sii", NORMAL_DAY);
+    mock.set_list(aug15(), "otc", KY_COMPANIES);
+    let client = TwseClient::with_config(TwseClientConfig {
+        base_url: mock.spawn().await.unwrap(),
+        requests_per_minute: 0,
+        market_interval: Duration::from_millis(100),
+        ..Default::default()
+    });
+
+    let started = std::time::Instant::now();
+    let (announcements, pages) = client.fetch_announcements(aug15(), "all").await.unwrap();
+    assert_eq!(pages.iter().map(|(market, _)| *market).collect::<Vec<_>>(), ["sii", "otc", "rotc", "pub"]);
+    let typeks: Vec<_> = mock.requests().iter().map(|r| r.form["TYPEK"].clone()).collect();
+    assert_eq!(typeks, ["sii", "otc", "rotc", "pub"]);
+    // 不限制每分鐘請求數時，四個請求之間仍保持最短間隔
+    assert!(started.elapsed() >= Duration::from_millis(300), "{:?}", started.elapsed());
+
+    // 每筆公告都標記所屬市場，不會留下未標記的資料
+    assert!(!announcements.is_empty());
+    assert!(announcements.iter().all(|a| a.market.is_some()));
+    let sii = announcements.iter().filter(|a| a.market.as_deref() == Some("sii")).count();
+    assert_eq!(sii, 3);
+    assert_eq!(announcements.len() - sii, announcements.iter().filter(|a| a.market.as_deref() == Some("otc")).count());
+}
+
+#[tokio::test]
+async fn retries_after_throttle_and_server_error() {
+    let (mock, client) = mock_client().await;
+    mock.push_faults([Fault::Throttle, Fault::ServerError]);
+
+    let (announcements, _) = client.fetch_announcements(aug15(), "sii").await.unwrap();
+    assert_eq!(announcements.len(), 3);
+    let faults: Vec<_> = mock.requests().iter().map(|r| r.fault).collect();
+    assert_eq!(faults, [Some(Fault::Throttle), Some(Fault::ServerError), None]);
+}
+
+#[tokio::test]
+async fn gives_up_after_retry_limit() {
+    let (mock, client) = mock_client().await;
+    mock.push_faults([Fault::ServerError; 3]);
+
+    let error = client.fetch_announcements(aug15(), "sii").await.unwrap_err();
+    assert!(format!("{:#}", error).contains("503"), "{:#}", error);
+    // 回補以此判斷不再整日重試，總請求數不超過 HTTP 重試上限
+    assert_eq!(error.downcast_ref::<RetriesExhausted>(), Some(&RetriesExhausted { retries: 2 }));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn throttle_cooldown_applies_without_rate_limit() {
+    let mock = MockMops::new();
+    mock.set_list(aug15(), "all", NORMAL_DAY);
+    let base_url = mock.spawn().await.unwrap();
+    let client = TwseClient::with_config(TwseClientConfig {
+        base_url,
+        max_retries: 2,
+        throttle_cooldown: Duration::from_millis(300),
+        requests_per_minute: 0,
+        ..Default::default()
+    });
+    mock.push_faults([Fault::Throttle]);
+
+    let started = std::time::Instant::now();
+    let (announcements, _) = client.fetch_announcements(aug15(), "sii").await.unwrap();
+    assert_eq!(announcements.len(), 3);
+    assert_eq!(mock.requests().len(), 2);
+    // 不限制請求數時仍需等待冷卻期才重試
+    assert!(started.elapsed() >= Duration::from_millis(300), "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn retries_after_timeout() {
+    let (mock, client) = mock_client().await;
+    mock.set_timeout_delay(Duration::from_secs(5));
+    mock.push_faults([Fault::Timeout]);
+
+    let (announcements, _) = client.fetch_announcements(aug15(), "sii").await.unwrap();
+    assert_eq!(announcements.len(), 3);
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn layout_change_is_reported() {
+    let (mock, client) = mock_client().await;
+    mock.push_faults([Fault::LayoutChange]);
+
+    let error = client.fetch_announcements(aug15(), "sii").await.unwrap_err();
+    assert!(error.is::<LayoutChangedError>(), "{:#}", error);
+    // 結構變更不重試
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn history_and_details() {
+    let (mock, client) = mock_client().await;
+    mock.set_history("2330", 114, HISTORY);
+    mock.set_detail("2330", "7", DETAIL);
+
+    let from = RocDate::from_ymd(2024, 12, 1).unwrap();
+    let to = RocDate::from_ymd(2025, 2, 28).unwrap();
+    let (mut announcements, pages) = client.fetch_company_history("2330", "sii", from, to).await.unwrap();
+    // 民國 113 年沒有設定回應，視為查無資料；114 年只保留區間內的兩筆
+    assert_eq!(pages.iter().map(|(year, _)| *year).collect::<Vec<_>>(), [113, 114]);
+    assert_eq!(announcements.iter().map(|a| a.sequence_number.as_deref()).collect::<Vec<_>>(), [Some("3"), Some("7")]);
+    // 以指定的市場別查詢並標記
+    assert!(announcements.iter().all(|a| a.market.as_deref() == Some("sii")));
+    let history_request = mock.requests().into_iter().find(|r| r.form.get("step").is_some_and(|s| s == "1")).unwrap();
+    assert_eq!(history_request.form["TYPEK"], "sii");
+    assert_eq!(history_request.form["co_id"], "2330");
+
+    // 查詢全部市場時回應無法分辨市場別，保持空白
+    let (untagged, _) = client.fetch_company_history("2330", "all", from, to).await.unwrap();
+    assert_eq!(untagged.len(), 2);
+    assert!(untagged.iter().all(|a| a.market.is_none()));
+
+    let outcomes = client.fetch_details(&mut announcements, 2).await;
+    assert_eq!(outcomes.len(), 2);
+    let detail = announcements[1].detail.as_ref().unwrap();
+    assert!(detail.is_correction);
+    assert_eq!(detail.spokesperson.as_deref(), Some("黃ＯＯ"));
+    // 序號 3 沒有設定明細頁
+    assert!(announcements[0].detail.is_none());
+
+    let detail_request = mock.requests().into_iter().find(|r| r.form.get("seq_no").is_some_and(|s| s == "7")).unwrap();
+    assert_eq!(detail_request.form["step"], "2");
+    assert_eq!(detail_request.form["spoke_date"], "20250212");
+}
+
+#[tokio::test]
+async fn fetch_details_reports_each_item() {
+    let (mock, client) = mock_client().await;
+    mock.set_history("2330", 114, HISTORY);
+    mock.set_detail("2330", "7", DETAIL);
+
+    let from = RocDate::from_ymd(2025, 1, 1).unwrap();
+    let to = RocDate::from_ymd(2025, 2, 28).unwrap();
+    let (mut announcements, _) = client.fetch_company_history("2330", "sii", from, to).await.unwrap();
+    let mut without_sequence = announcements[1].clone();
+    without_sequence.sequence_number = None;
+    announcements.push(without_sequence);
+
+    // 第一個明細請求回傳 503，重試後成功；併發查詢的結果仍依原順序回傳
+    mock.push_faults([Fault::ServerError]);
+    let outcomes = client.fetch_details(&mut announcements, 3).await;
+    assert_eq!(outcomes.iter().map(|(index, _)| *index).collect::<Vec<_>>(), [0, 1, 2]);
+
+    // 序號 3 沒有明細頁，回應為查無資料
+    assert!(outcomes[0].1.is_err());
+    assert!(announcements[0].detail.is_none());
+    assert_eq!(outcomes[1].1.as_deref().unwrap(), DETAIL);
+    assert!(announcements[1].detail.as_ref().unwrap().is_correction);
+    // 缺少發言序號時不發送請求
+    assert!(outcomes[2].1.as_ref().unwrap_err().to_string().contains("缺少發言序號"));
+    assert!(announcements[2].detail.is_none());
+
+    let detail_requests = mock.requests().into_iter().filter(|r| r.form.contains_key("seq_no")).count();
+    assert_eq!(detail_requests, 3);
+}
+
+// 以 CLI 查詢單日，回傳工作目錄中產生的檔案 (含第一層子目錄內的檔案)
+async fn run_cli(base_url: &str, name: &str, extra_args: &[&str]) -> Vec<String> {
+    let dir = std::env::temp_dir().join(format!("twse_cli_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut command = tokio::process::Command::new(env!("CARGO_BIN_EXE_twse-announcements"));
+    command
+        .args(["--date", "2025-08-15", "--market", "sii", "--requests-per-minute", "0", "--mops-base-url", base_url])
+        .args(extra_args)
+        .current_dir(&dir);
+    let output = command.output().await.unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let entry = entry.unwrap();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.path().is_dir() {
+            for nested in std::fs::read_dir(entry.path()).unwrap() {
+                files.push(format!("{}/{}", name, nested.unwrap().file_name().to_string_lossy()));
+            }
+        }
+        files.push(name);
+    }
+    files.sort();
+    std::fs::remove_dir_all(&dir).unwrap();
+    files
+}
+
+#[tokio::test]
+async fn every_fetch_is_archived_by_default() {
+    let mock = MockMops::new();
+    mock.set_list(aug15(), "sii", NORMAL_DAY);
+    let base_url = mock.spawn().await.unwrap();
+
+    let files = run_cli(&base_url, "default", &[]).await;
+    assert!(files.iter().any(|f| f == "archive/manifest.jsonl"), "{:?}", files);
+
+    let files = run_cli(&base_url, "archived", &["--archive-dir", "raw"]).await;
+    assert!(files.iter().any(|f| f == "raw/manifest.jsonl"), "{:?}", files);
+}
@@ -0,0 +1,339 @@
+//! 記憶體儲存後端的篩選、統計與防重複寫入行為。
+
+use twse_announcements::date::RocDate;
+use twse_announcements::model::Announcement;
+use twse_announcements::store::{AnnouncementQuery, AnnouncementStore, Cursor, DuplicateMode, MemoryStore, ReplaceScope};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/announcements.json");
+
+fn date(value: &str) -> RocDate {
+    RocDate::parse(value).unwrap()
+}
+
+fn fixture_store() -> MemoryStore {
+    MemoryStore::load_json(FIXTURE).unwrap()
+}
+
+fn announcement(code: &str, title: &str, query_date: &str, market: &str) -> Announcement {
+    serde_json::from_value(serde_json::json!({
+        "company_code": code,
+        "company_name": "測試公司",
+        "title": title,
+        "date": date(query_date).to_roc_string(),
+        "time": "09:00:00",
+        "query_date": query_date,
+        "market": market,
+    }))
+    .unwrap()
+}
+
+fn titles(announcements: &[Announcement]) -> Vec<&str> {
+    announcements.iter().map(|a| a.title.as_str()).collect()
+}
+
+#[tokio::test]
+async fn load_json_assigns_ids() {
+    let store = fixture_store();
+    assert_eq!(store.len(), 5);
+
+    let all = store.query(&AnnouncementQuery::default()).await.unwrap();
+    assert!(all.iter().all(|a| a.id.is_some()));
+
+    let id = all[0].id.unwrap().to_hex();
+    let found = store.get(&id).await.unwrap().unwrap();
+    assert_eq!(found.title, all[0].title);
+    assert!(store.get("not-an-object-id").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn query_sorts_newest_first() {
+    let store = fixture_store();
+    let all = store.query(&AnnouncementQuery::default()).await.unwrap();
+    assert_eq!(
+        titles(&all),
+        [
+            "公告本公司董事會通過合併案",
+            "本公司董事會決議股利分派",
+            "澄清媒體報導",
+            "公告本公司代子公司取得設備",
+            "代子公司公告 ESG Report",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn query_filters_by_company_market_and_query_date() {
+    let store = fixture_store();
+
+    let query = AnnouncementQuery { companies: vec!["2330".into()], ..Default::default() };
+    assert_eq!(store.count(&query).await.unwrap(), 2);
+
+    let query = AnnouncementQuery { market: Some("otc".into()), ..Default::default() };
+    assert_eq!(titles(&store.query(&query).await.unwrap()), ["公告本公司董事會通過合併案"]);
+
+    // market=all 等同不限定
+    let query = AnnouncementQuery { market: Some("all".into()), ..Default::default() };
+    assert_eq!(store.count(&query).await.unwrap(), 5);
+
+    let query = AnnouncementQuery { query_date: Some(date("2025-08-15")), market: Some("sii".into()), ..Default::default() };
+    assert_eq!(titles(&store.query(&query).await.unwrap()), ["本公司董事會決議股利分派", "澄清媒體報導"]);
+}
+
+#[tokio::test]
+async fn query_filters_by_clause_code_occurrence_date_and_time() {
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let mut batch = vec![
+        announcement("1101", "盤後合併案", "2025-08-15", "sii"),
+        announcement("1102", "盤中合併案", "2025-08-15", "sii"),
+        announcement("1103", "盤後澄清", "2025-08-15", "sii"),
+    ];
+    for (announcement, (clause_code, time, occurred)) in
+        batch.iter_mut().zip([("11", "17:30:00", "2025-08-01"), ("11", "10:00:00", "2025-07-01"), ("51", "18:00:00", "2025-08-10")])
+    {
+        announcement.clause_code = Some(clause_code.into());
+        announcement.time = time.into();
+        announcement.fact_occurrence_date = Some(date(occurred));
+    }
+    store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+
+    let after_close = AnnouncementQuery {
+        clause_codes: vec!["11".into()],
+        time_from: Some("17:00:00".into()),
+        ..Default::default()
+    };
+    assert_eq!(titles(&store.query(&after_close).await.unwrap()), ["盤後合併案"]);
+
+    let occurred = AnnouncementQuery {
+        occurrence_start_date: Some(date("2025-08-01")),
+        occurrence_end_date: Some(date("2025-08-31")),
+        time_to: Some("17:59:59".into()),
+        ..Default::default()
+    };
+    assert_eq!(titles(&store.query(&occurred).await.unwrap()), ["盤後合併案"]);
+
+    let companies = AnnouncementQuery { companies: vec!["1102".into(), "1103".into()], ..Default::default() };
+    assert_eq!(titles(&store.query(&companies).await.unwrap()), ["盤後澄清", "盤中合併案"]);
+    assert_eq!(store.count(&companies).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn date_range_matches_announcement_date_only() {
+    let store = fixture_store();
+
+    // 環球晶的事實發生日為 8/1，但只比對發言日期
+    let query = AnnouncementQuery {
+        start_date: Some(date("2025-08-01")),
+        end_date: Some(date("2025-08-02")),
+        ..Default::default()
+    };
+    assert!(store.query(&query).await.unwrap().is_empty());
+
+    let query = AnnouncementQuery { end_date: Some(date("2025-08-13")), ..Default::default() };
+    assert_eq!(titles(&store.query(&query).await.unwrap()), ["代子公司公告 ESG Report"]);
+
+    let query = AnnouncementQuery { start_date: Some(date("2025-08-14")), ..Default::default() };
+    assert_eq!(store.count(&query).await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn search_is_case_insensitive() {
+    let store = fixture_store();
+
+    let query = AnnouncementQuery { search: Some("esg report".into()), ..Default::default() };
+    assert_eq!(titles(&store.query(&query).await.unwrap()), ["代子公司公告 ESG Report"]);
+
+    let query = AnnouncementQuery { search: Some("董事會".into()), ..Default::default() };
+    assert_eq!(store.count(&query).await.unwrap(), 2);
+
+    // 關鍵字為字面比對，正規表示式的特殊字元不具意義
+    for search in ["(", ".*", "董事.決議"] {
+        let query = AnnouncementQuery { search: Some(search.into()), ..Default::default() };
+        assert_eq!(store.count(&query).await.unwrap(), 0, "{}", search);
+    }
+}
+
+#[tokio::test]
+async fn skip_and_limit_do_not_affect_count() {
+    let store = fixture_store();
+    let query = AnnouncementQuery { skip: 1, limit: Some(2), ..Default::default() };
+
+    assert_eq!(titles(&store.query(&query).await.unwrap()), ["本公司董事會決議股利分派", "澄清媒體報導"]);
+    assert_eq!(store.count(&query).await.unwrap(), 5);
+}
+
+#[tokio::test]
+async fn cursor_walks_every_announcement_once() {
+    // 同一時間的公告依 id 排序，逐頁讀取時不重複也不遺漏
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let batch: Vec<Announcement> = (1..=5)
+        .map(|n| announcement(&format!("110{}", n), &format!("公告{}", n), "2025-08-15", "sii"))
+        .collect();
+    store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+
+    let mut seen = Vec::new();
+    let mut after = None;
+    loop {
+        let query = AnnouncementQuery { limit: Some(2), after, ..Default::default() };
+        let page = store.query(&query).await.unwrap();
+        assert_eq!(store.count(&query).await.unwrap(), 5);
+        let Some(last) = page.last() else { break };
+        after = Cursor::after(last);
+        seen.extend(page.iter().map(|a| a.title.clone()));
+    }
+
+    let all = store.query(&AnnouncementQuery::default()).await.unwrap();
+    assert_eq!(seen, titles(&all));
+    assert_eq!(seen.len(), 5);
+}
+
+#[test]
+fn cursor_round_trips_and_rejects_garbage() {
+    let mut first = announcement("1101", "公告一", "2025-08-15", "sii");
+    assert_eq!(Cursor::after(&first), None);
+
+    first.id = Some(bson::oid::ObjectId::new());
+    let cursor = Cursor::after(&first).unwrap();
+    assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+    assert_eq!(Cursor::decode("not-a-cursor"), None);
+    assert_eq!(Cursor::decode(""), None);
+}
+
+#[tokio::test]
+async fn stats_group_by_company_and_query_date() {
+    let store = fixture_store();
+
+    let companies = store.company_counts(2).await.unwrap();
+    assert_eq!(companies.len(), 2);
+    assert_eq!((companies[0].company_code.as_str(), companies[0].count), ("2330", 2));
+    assert_eq!(companies[1].count, 1);
+
+    let dates = store.date_counts(30).await.unwrap();
+    let dates: Vec<(&str, i64)> = dates.iter().map(|d| (d.date.as_str(), d.count)).collect();
+    assert_eq!(dates, [("2025-08-15", 3), ("2025-08-14", 1), ("2025-08-13", 1)]);
+
+    let clause_codes = store.clause_codes().await.unwrap();
+    assert_eq!(clause_codes.first().map(|c| c.code.as_str()), Some("1"));
+}
+
+#[tokio::test]
+async fn known_keys_by_announcement_date() {
+    let store = fixture_store();
+    let keys = store.known_keys(date("2025-08-15")).await.unwrap();
+    assert_eq!(keys.len(), 3);
+    assert!(keys.iter().any(|(code, _, time, _)| code == "2317" && time == "08:10:00"));
+}
+
+#[tokio::test]
+async fn upsert_inserts_then_updates_changed_content() {
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let mut batch = vec![announcement("1101", "公告一", "2025-08-15", "sii")];
+
+    let summary = store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    assert_eq!((summary.inserted, summary.updated), (1, 0));
+
+    // 內容相同時不計為更新
+    let summary = store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    assert_eq!((summary.inserted, summary.updated), (0, 0));
+
+    batch[0].detail_content = Some("補充說明".into());
+    let summary = store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    assert_eq!((summary.inserted, summary.updated), (0, 1));
+
+    let stored = store.query(&AnnouncementQuery::default()).await.unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].detail_content.as_deref(), Some("補充說明"));
+}
+
+#[tokio::test]
+async fn skip_keeps_existing_announcements() {
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let first = announcement("1101", "公告一", "2025-08-15", "sii");
+    store.save(std::slice::from_ref(&first), &scope, DuplicateMode::Skip).await.unwrap();
+
+    let second = announcement("1102", "公告二", "2025-08-15", "sii");
+    let summary = store.save(&[first, second], &scope, DuplicateMode::Skip).await.unwrap();
+    assert_eq!((summary.inserted, summary.updated, summary.skipped), (1, 0, 1));
+}
+
+#[tokio::test]
+async fn skip_still_records_corrections() {
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let mut batch = vec![announcement("1101", "公告一", "2025-08-15", "sii")];
+    batch[0].detail_content = Some("原始內容".into());
+    store.save(&batch, &scope, DuplicateMode::Skip).await.unwrap();
+
+    batch[0].detail_content = Some("更正內容".into());
+    let summary = store.save(&batch, &scope, DuplicateMode::Skip).await.unwrap();
+    assert_eq!((summary.updated, summary.skipped, summary.revised), (1, 0, 1));
+
+    let stored = store.query(&AnnouncementQuery { companies: vec!["1101".into()], ..Default::default() }).await.unwrap();
+    assert_eq!(stored[0].detail_content.as_deref(), Some("更正內容"));
+    assert_eq!(stored[0].revision, Some(2));
+}
+
+#[tokio::test]
+async fn corrections_keep_previous_versions() {
+    let store = MemoryStore::new();
+    let scope = ReplaceScope::day(date("2025-08-15"), "all");
+    let mut batch = vec![announcement("1101", "公告一", "2025-08-15", "sii")];
+
+    batch[0].detail_content = Some("第一版".into());
+    store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    let first = store.query(&AnnouncementQuery::default()).await.unwrap().remove(0);
+    let announcement_id = first.announcement_id.clone().unwrap();
+    assert_eq!(announcement_id, batch[0].stable_id());
+    assert_eq!(first.content_hash, Some(first.compute_content_hash()));
+    assert_eq!(first.revision, Some(1));
+
+    // 相同內容不產生新版本
+    let summary = store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    assert_eq!(summary.revised, 0);
+
+    batch[0].detail_content = Some("第二版".into());
+    store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+    // replace 模式重新寫入時也比對被刪除的舊資料
+    batch[0].detail_content = Some("第三版".into());
+    let summary = store.save(&batch, &scope, DuplicateMode::Replace).await.unwrap();
+    assert_eq!((summary.deleted, summary.inserted, summary.revised), (1, 1, 1));
+
+    let current = store.get(&announcement_id).await.unwrap().unwrap();
+    assert_eq!(current.detail_content.as_deref(), Some("第三版"));
+    assert_eq!(current.revision, Some(3));
+
+    let revisions = store.revisions(&announcement_id).await.unwrap();
+    let history: Vec<(u32, Option<&str>)> =
+        revisions.iter().map(|r| (r.revision, r.detail_content.as_deref())).collect();
+    assert_eq!(history, [(1, Some("第一版")), (2, Some("第二版"))]);
+    assert!(revisions.iter().all(|r| r.superseded_at.is_some()));
+    assert_ne!(revisions[0].content_hash, revisions[1].content_hash);
+}
+
+#[tokio::test]
+async fn replace_only_deletes_within_scope() {
+    let store = MemoryStore::new();
+    let seed = vec![
+        announcement("1101", "上市舊公告", "2025-08-15", "sii"),
+        announcement("6488", "上櫃公告", "2025-08-15", "otc"),
+        announcement("1101", "前一日公告", "2025-08-14", "sii"),
+    ];
+    store.save(&seed, &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Upsert).await.unwrap();
+
+    let fresh = vec![announcement("1101", "上市新公告", "2025-08-15", "sii")];
+    let summary = store
+        .save(&fresh, &ReplaceScope::day(date("2025-08-15"), "sii"), DuplicateMode::Replace)
+        .await
+        .unwrap();
+    assert_eq!((summary.deleted, summary.inserted), (1, 1));
+
+    let mut remaining = titles(&store.query(&AnnouncementQuery::default()).await.unwrap())
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(remaining, ["上市新公告", "上櫃公告", "前一日公告"]);
+}
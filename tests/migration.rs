@@ -0,0 +1,147 @@
+//! 遷移試算：各種舊資料文件產生的 `$set` 更新與報告統計。
+
+use bson::{doc, oid::ObjectId, Bson, Document};
+use chrono::{TimeZone, Utc};
+
+use twse_announcements::date::RocDate;
+use twse_announcements::migration::{plan_document, MigrationReport};
+
+fn aug15() -> bson::DateTime {
+    RocDate::from_ymd(2025, 8, 15).unwrap().to_bson()
+}
+
+// 2025-08-15 17:30:05 台灣時間
+fn announced_at() -> bson::DateTime {
+    bson::DateTime::from_chrono(Utc.with_ymd_and_hms(2025, 8, 15, 9, 30, 5).unwrap())
+}
+
+struct Case {
+    name: &'static str,
+    document: Document,
+    expected: Option<Document>,
+    // 依序為 date 欄位的 (已為日期, 可轉換, 無法辨識)
+    date_stats: (u64, u64, u64),
+    // created_at 欄位的 (已為日期, 可轉換, 無法辨識)
+    created_at_stats: (u64, u64, u64),
+    announced_at_added: u64,
+    announced_at_missing: u64,
+}
+
+#[test]
+fn plan_document_table() {
+    let cases = [
+        Case {
+            name: "字串日期轉為 BSON 日期並補上 announced_at",
+            document: doc! { "_id": ObjectId::new(), "date": "114/08/15", "query_date": "2025-08-15", "time": "17:30:05" },
+            expected: Some(doc! { "$set": { "date": aug15(), "query_date": aug15(), "announced_at": announced_at() } }),
+            date_stats: (0, 1, 0),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 1,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "已遷移的文件不需更新",
+            document: doc! { "_id": ObjectId::new(), "date": aug15(), "query_date": aug15(), "time": "17:30:05", "announced_at": announced_at() },
+            expected: None,
+            date_stats: (1, 0, 0),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 0,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "已是 BSON 日期但缺少 announced_at",
+            document: doc! { "_id": ObjectId::new(), "date": aug15(), "time": "17:30:05" },
+            expected: Some(doc! { "$set": { "announced_at": announced_at() } }),
+            date_stats: (1, 0, 0),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 1,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "缺少 time 時只轉換日期",
+            document: doc! { "_id": ObjectId::new(), "date": "2025-08-15" },
+            expected: Some(doc! { "$set": { "date": aug15() } }),
+            date_stats: (0, 1, 0),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 0,
+            announced_at_missing: 1,
+        },
+        Case {
+            name: "無法辨識的日期保留原值並列入報告",
+            document: doc! { "_id": ObjectId::new(), "date": "不詳", "fact_date": "2025-08-15", "time": "17:30:05" },
+            expected: Some(doc! { "$set": { "fact_date": aug15() } }),
+            date_stats: (0, 0, 1),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 0,
+            announced_at_missing: 1,
+        },
+        Case {
+            name: "字串擷取時間轉為 BSON 日期",
+            document: doc! { "_id": ObjectId::new(), "date": aug15(), "time": "17:30:05", "announced_at": announced_at(), "created_at": "2025-08-15T17:30:05.123+08:00" },
+            expected: Some(doc! { "$set": { "created_at": bson::DateTime::from_chrono(Utc.with_ymd_and_hms(2025, 8, 15, 9, 30, 5).unwrap() + chrono::Duration::milliseconds(123)) } }),
+            date_stats: (1, 0, 0),
+            created_at_stats: (0, 1, 0),
+            announced_at_added: 0,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "已是 BSON 日期的擷取時間不需更新",
+            document: doc! { "_id": ObjectId::new(), "date": aug15(), "time": "17:30:05", "announced_at": announced_at(), "created_at": announced_at() },
+            expected: None,
+            date_stats: (1, 0, 0),
+            created_at_stats: (1, 0, 0),
+            announced_at_added: 0,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "無法辨識的擷取時間保留原值並列入報告",
+            document: doc! { "_id": ObjectId::new(), "date": "2025-08-15", "time": "17:30:05", "created_at": "2025/08/15 17:30" },
+            expected: Some(doc! { "$set": { "date": aug15(), "announced_at": announced_at() } }),
+            date_stats: (0, 1, 0),
+            created_at_stats: (0, 0, 1),
+            announced_at_added: 1,
+            announced_at_missing: 0,
+        },
+        Case {
+            name: "非字串的日期值視為無法辨識",
+            document: doc! { "_id": ObjectId::new(), "date": 20250815, "query_date": Bson::Null, "time": "17:30:05" },
+            expected: None,
+            date_stats: (0, 0, 1),
+            created_at_stats: (0, 0, 0),
+            announced_at_added: 0,
+            announced_at_missing: 1,
+        },
+    ];
+
+    for case in cases {
+        let mut report = MigrationReport::default();
+        let update = plan_document(&case.document, &mut report);
+        assert_eq!(update, case.expected, "{}", case.name);
+
+        let stats = &report.fields["date"];
+        assert_eq!((stats.typed, stats.converted, stats.unparseable), case.date_stats, "{}", case.name);
+        let stats = &report.fields["created_at"];
+        assert_eq!((stats.typed, stats.converted, stats.unparseable), case.created_at_stats, "{}", case.name);
+        assert_eq!(report.announced_at_added, case.announced_at_added, "{}", case.name);
+        assert_eq!(report.announced_at_missing, case.announced_at_missing, "{}", case.name);
+        // 無法辨識的值附上文件 id，方便人工檢查
+        assert_eq!(report.unparseable_samples.len() as u64, case.date_stats.2 + case.created_at_stats.2, "{}", case.name);
+        if let Some(sample) = report.unparseable_samples.first() {
+            assert!(sample.contains(&case.document.get_object_id("_id").unwrap().to_hex()), "{}", sample);
+        }
+    }
+}
+
+#[test]
+fn plan_document_accumulates_across_documents() {
+    let mut report = MigrationReport::default();
+    for date in ["114/08/15", "2025-08-15", "20250815", "民國114年8月15日"] {
+        let update = plan_document(&doc! { "_id": ObjectId::new(), "date": date, "time": "17:30" }, &mut report);
+        assert_eq!(update.unwrap().get_document("$set").unwrap().get("date"), Some(&Bson::DateTime(aug15())), "{}", date);
+    }
+
+    assert_eq!(report.fields["date"].converted, 4);
+    assert_eq!(report.announced_at_added, 4);
+    assert_eq!(report.samples.len(), 4);
+    assert!(report.samples[0].contains("\"114/08/15\" -> 2025-08-15"), "{}", report.samples[0]);
+}
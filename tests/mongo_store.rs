@@ -0,0 +1,100 @@
+//! MongoDB 儲存後端寫入的文件格式與查詢條件 (不需連線資料庫)。
+
+use bson::{doc, oid::ObjectId, Bson, Document};
+
+use twse_announcements::date::RocDate;
+use twse_announcements::model::Announcement;
+use twse_announcements::store::mongo::{announcement_document, query_filter};
+use twse_announcements::store::{AnnouncementQuery, Cursor};
+
+fn announcement() -> Announcement {
+    let mut announcement: Announcement = serde_json::from_value(serde_json::json!({
+        "company_code": "2330",
+        "company_name": "台積電",
+        "title": "本公司董事會決議股利分派",
+        "date": "114/08/15",
+        "time": "17:30:00",
+        "query_date": "2025-08-15",
+        "fact_date": "114/08/15",
+        "fact_occurrence_date": "2025-08-14",
+        "created_at": "2025-08-15T10:00:00Z",
+    }))
+    .unwrap();
+    announcement.ensure_identity();
+    announcement
+}
+
+#[test]
+fn upsert_document_stores_bson_dates() {
+    let document = announcement_document(&announcement()).unwrap();
+
+    for field in ["date", "announced_at", "query_date", "fact_date", "fact_occurrence_date", "created_at"] {
+        assert!(matches!(document.get(field), Some(Bson::DateTime(_))), "{} 應為 BSON 日期: {:?}", field, document.get(field));
+    }
+    assert_eq!(document.get_str("time").unwrap(), "17:30:00");
+
+    // 讀回後內容不變
+    let restored: Announcement = bson::from_document(document).unwrap();
+    assert_eq!(restored.key(), announcement().key());
+    assert_eq!(restored.announced_at, announcement().announced_at);
+    assert_eq!(restored.created_at, announcement().created_at);
+}
+
+fn and_conditions(filter: &Document) -> Vec<Document> {
+    filter.get_array("$and").unwrap().iter().map(|c| c.as_document().unwrap().clone()).collect()
+}
+
+#[test]
+fn cursor_filter_reaches_documents_without_announced_at() {
+    let id = ObjectId::new();
+    let stored = announcement();
+    let cursor = Cursor { announced_at: stored.announced_at, id };
+    let filter = query_filter(&AnnouncementQuery { after: Some(cursor), ..Default::default() });
+    let announced_at = bson::DateTime::from_chrono(stored.announced_at.unwrap());
+    assert_eq!(and_conditions(&filter), [doc! { "$or": [
+        { "announced_at": { "$lt": announced_at } },
+        { "announced_at": announced_at, "_id": { "$lt": id } },
+        { "announced_at": Bson::Null },
+    ] }]);
+
+    // 上一頁最後一筆缺少 announced_at 時，只在同樣缺少的文件中往後讀
+    let legacy = Cursor { announced_at: None, id };
+    assert_eq!(Cursor::decode(&legacy.encode()), Some(legacy));
+    let filter = query_filter(&AnnouncementQuery { after: Some(legacy), ..Default::default() });
+    assert_eq!(and_conditions(&filter), [doc! { "announced_at": Bson::Null, "_id": { "$lt": id } }]);
+}
+
+#[test]
+fn date_filters_compare_bson_dates() {
+    let start = RocDate::parse("2025-08-01").unwrap();
+    let end = RocDate::parse("2025-08-31").unwrap();
+    let filter = query_filter(&AnnouncementQuery { start_date: Some(start), end_date: Some(end), query_date: Some(end), ..Default::default() });
+
+    // 單一欄位的 BSON 日期比較，不含舊版字串格式的 $or 分支
+    assert_eq!(filter, doc! {
+        "date": { "$gte": start.to_bson(), "$lte": end.to_bson() },
+        "query_date": end.to_bson(),
+    });
+
+    // count 與 query 使用相同條件，count 不受游標影響
+    let page = AnnouncementQuery { start_date: Some(start), after: Some(Cursor { announced_at: None, id: ObjectId::new() }), ..Default::default() };
+    let count = AnnouncementQuery { after: None, ..page.clone() };
+    assert_eq!(query_filter(&page).get("date"), query_filter(&count).get("date"));
+    assert!(query_filter(&count).get("$and").is_none());
+}
+
+#[test]
+fn created_since_compares_bson_timestamps() {
+    let since = chrono::DateTime::parse_from_rfc3339("2025-08-15T10:00:00.750Z").unwrap().with_timezone(&chrono::Utc);
+    let filter = query_filter(&AnnouncementQuery { created_since: Some(since), ..Default::default() });
+
+    // 以秒為單位比較，與其他後端一致
+    let second = chrono::DateTime::parse_from_rfc3339("2025-08-15T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+    assert_eq!(filter.get_document("created_at").unwrap(), &doc! { "$gte": bson::DateTime::from_chrono(second) });
+}
+
+#[test]
+fn title_search_is_escaped_for_literal_matching() {
+    let filter = query_filter(&AnnouncementQuery { search: Some("100% (A.B)*".into()), ..Default::default() });
+    assert_eq!(filter.get_document("title").unwrap(), &doc! { "$regex": r"100% \(A\.B\)\*", "$options": "i" });
+}
@@ -0,0 +1,234 @@
+//! 列表頁解析結果的分類 (完整、查無資料、限流、結構變更與部分解析)、明細頁欄位與個別欄位的解析規則；
+//! 完整回應的解析結果見 `parser_golden.rs`。
+
+use scraper::{Html, Selector};
+
+use twse_announcements::date::RocDate;
+use twse_announcements::parser::{
+    extract_detail_info, format_fact_occurrence_date, parse_announcement_line, parse_company_history, parse_detail_page,
+    parse_response, HiddenField, LayoutChangedError, ParseStatus,
+};
+
+const HEADER: &str = "<tr><th class='tblHead'>發言日期</th><th class='tblHead'>發言時間</th>\
+    <th class='tblHead'>公司代號</th><th class='tblHead'>公司名稱</th><th class='tblHead'>主旨</th></tr>";
+
+fn row(date: &str, code: &str, title: &str) -> String {
+    format!("<tr><td>{}</td><td>17:30:00</td><td>{}</td><td>台積電</td><td>{}</td></tr>", date, code, title)
+}
+
+fn list_page(rows: &[String]) -> String {
+    format!("<html><body><table>{}{}</table></body></html>", HEADER, rows.concat())
+}
+
+#[test]
+fn complete_page() {
+    let outcome = parse_response(&list_page(&[row("114/08/15", "2330", "董事會決議股利分派")]));
+    assert_eq!(outcome.status, ParseStatus::Complete);
+    assert!(outcome.warnings.is_empty());
+    assert_eq!(outcome.into_announcements().unwrap().len(), 1);
+}
+
+#[test]
+fn no_announcements_page() {
+    let outcome = parse_response("<html><body><center>沒有找到重大訊息</center></body></html>");
+    assert_eq!(outcome.status, ParseStatus::NoAnnouncements);
+    assert!(outcome.into_announcements().unwrap().is_empty());
+}
+
+#[test]
+fn error_pages_mentioning_no_data_are_not_empty_days() {
+    // 只含「查無」等片段的錯誤頁面不是查無資料
+    for html in [
+        "<html><body><center>查無此權限，請重新登入</center></body></html>",
+        "<html><body>系統維護中，查無法提供服務</body></html>",
+        "<html><body>No data available: service error</body></html>",
+    ] {
+        let outcome = parse_response(html);
+        assert!(matches!(outcome.status, ParseStatus::UnexpectedLayout { .. }), "{}", html);
+    }
+}
+
+#[test]
+fn throttled_page_is_an_error() {
+    let outcome = parse_response("<html><body>查詢過於頻繁，請稍後再試</body></html>");
+    assert_eq!(outcome.status, ParseStatus::Throttled);
+    assert!(outcome.into_announcements().is_err());
+}
+
+#[test]
+fn unknown_page_is_layout_change() {
+    let outcome = parse_response("<html><body><div class='result'>2330 台積電</div></body></html>");
+    assert!(matches!(outcome.status, ParseStatus::UnexpectedLayout { .. }));
+    let error = outcome.into_announcements().unwrap_err();
+    assert!(error.is::<LayoutChangedError>());
+}
+
+#[test]
+fn renamed_headers_are_layout_change() {
+    let html = list_page(&[row("114/08/15", "2330", "董事會決議股利分派")]).replace("主旨", "標題");
+    let outcome = parse_response(&html);
+    let ParseStatus::UnexpectedLayout { reason } = &outcome.status else {
+        panic!("預期為結構變更，實際為 {:?}", outcome.status);
+    };
+    assert!(reason.contains("主旨"));
+}
+
+#[test]
+fn partial_parse_reports_skipped_rows() {
+    let outcome = parse_response(&list_page(&[
+        row("114/08/15", "2330", "董事會決議股利分派"),
+        row("日期待補", "2317", "格式錯誤的日期"),
+        row("114/08/15", "", "缺少公司代號"),
+    ]));
+    assert_eq!(outcome.status, ParseStatus::Partial { skipped: 2 });
+    assert_eq!(outcome.warnings.iter().map(|w| w.row).collect::<Vec<_>>(), [3, 4]);
+    assert!(outcome.warnings[0].message.contains("日期待補"));
+    assert_eq!(outcome.into_announcements().unwrap().len(), 1);
+}
+
+#[test]
+fn all_rows_skipped_is_layout_change() {
+    let outcome = parse_response(&list_page(&[row("2330", "台積電", "董事會決議股利分派")]));
+    assert!(matches!(outcome.status, ParseStatus::UnexpectedLayout { .. }));
+    assert_eq!(outcome.warnings.len(), 1);
+}
+
+#[test]
+fn company_history_rows() {
+    let outcome = parse_company_history(include_str!("fixtures/parser/history_company.html"), "2330");
+    assert_eq!(outcome.status, ParseStatus::Complete);
+    let announcements = outcome.into_announcements().unwrap();
+
+    // 發言序號取自「詳細資料」按鈕或隱藏欄位
+    let rows: Vec<_> = announcements.iter().map(|a| (a.date.to_roc_string(), a.time.as_str(), a.sequence_number.as_deref())).collect();
+    assert_eq!(rows, [
+        ("114/01/16".to_string(), "14:05:37", Some("3")),
+        ("114/02/12".to_string(), "17:12:09", Some("7")),
+        ("114/03/05".to_string(), "08:00:00", Some("12")),
+    ]);
+    assert!(announcements.iter().all(|a| a.company_code == "2330" && a.company_name == "台積電"));
+    assert_eq!(announcements[2].clause_code.as_deref(), Some("12"));
+    // 歷史頁沒有市場別，由呼叫端依查詢的 TYPEK 標記
+    assert!(announcements.iter().all(|a| a.market.is_none()));
+}
+
+#[test]
+fn history_without_table_is_layout_change() {
+    let outcome = parse_company_history("<html><body><table><tr><td>維護中</td></tr></table></body></html>", "2330");
+    assert!(matches!(outcome.status, ParseStatus::UnexpectedLayout { .. }));
+
+    let outcome = parse_company_history("<html><body>查無所需資料</body></html>", "2330");
+    assert_eq!(outcome.status, ParseStatus::NoAnnouncements);
+}
+
+#[test]
+fn hidden_field_names_map_to_schema() {
+    // h<列號><欄位序號>：列號可為多位數，欄位序號為最後一碼
+    assert_eq!(HiddenField::from_name("h06"), Some(HiddenField::ClauseCode));
+    assert_eq!(HiddenField::from_name("h16"), Some(HiddenField::ClauseCode));
+    assert_eq!(HiddenField::from_name("h160"), Some(HiddenField::SpokeDate));
+    assert_eq!(HiddenField::from_name("h1068"), Some(HiddenField::Detail));
+    assert_eq!(HiddenField::from_name("h09"), None);
+    assert_eq!(HiddenField::from_name("seq_no"), None);
+    assert_eq!(HiddenField::from_name("h"), None);
+    assert!(HiddenField::ALL.iter().all(|&field| HiddenField::from_index(field.index()) == Some(field)));
+}
+
+#[test]
+fn hidden_fields_follow_schema() {
+    // 第 12 列的隱藏欄位為 h120 ~ h128
+    let html = "<table><tr><td>\
+        <input type='hidden' name='h124' value=' 12 '>\
+        <input type='hidden' name='h126' value='51'>\
+        <input type='hidden' name='h127' value='20250814'>\
+        <input type='hidden' name='h128' value='1.事實發生日：114/08/13\n符合條款第四條第51款'>\
+        <input type='hidden' name='h125' value='主旨'>\
+        <input type='hidden' name='h121' value='173012'>\
+        <input type='hidden' name='h129' value='N'>\
+        <input type='hidden' name='TYPEK' value='sii'>\
+        </td></tr></table>";
+    let document = Html::parse_document(html);
+    let row = document.select(&Selector::parse("tr").unwrap()).next().unwrap();
+    let detail = extract_detail_info(&row, &Selector::parse("input[type='hidden']").unwrap());
+
+    assert_eq!(detail.sequence_number.as_deref(), Some("12"));
+    assert_eq!(detail.clause_code.as_deref(), Some("51"));
+    assert_eq!(detail.fact_occurrence_date, RocDate::from_ymd(2025, 8, 14));
+    assert_eq!(detail.fact_date, RocDate::from_ymd(2025, 8, 13));
+    assert_eq!(detail.announcement_type.as_deref(), Some("符合條款第四條第51款"));
+    assert_eq!(detail.subject.as_deref(), Some("主旨"));
+    assert_eq!(detail.spoke_time.as_deref(), Some("17:30:12"));
+    assert!(detail.raw_html.contains("h128"));
+
+    // 未定義的欄位保留下來，h 欄位去除列號
+    let extra: Vec<(&str, &str)> = detail.extra.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    assert_eq!(extra, [("TYPEK", "sii"), ("h9", "N")]);
+}
+
+#[test]
+fn empty_hidden_fields_are_ignored() {
+    let html = "<table><tr><td><input type='hidden' name='h06' value='  '><input type='hidden' name='h08' value=''></td></tr></table>";
+    let document = Html::parse_document(html);
+    let row = document.select(&Selector::parse("tr").unwrap()).next().unwrap();
+    let detail = extract_detail_info(&row, &Selector::parse("input[type='hidden']").unwrap());
+
+    assert!(detail.clause_code.is_none());
+    assert!(detail.detail_content.is_none());
+}
+
+#[test]
+fn missing_time_cell_uses_hidden_field() {
+    let html = list_page(&[row("114/08/15", "2330", "董事會決議股利分派")
+        .replace("<td>17:30:00</td>", "<td></td>")
+        .replace("</tr>", "<td><input type='hidden' name='h01' value='173012'><input type='hidden' name='h09' value='Y'></td></tr>")]);
+    let announcements = parse_response(&html).into_announcements().unwrap();
+    assert_eq!(announcements[0].time, "17:30:12");
+    assert_eq!(announcements[0].extra.get("h9").map(String::as_str), Some("Y"));
+}
+
+#[test]
+fn fact_occurrence_date_formats() {
+    assert_eq!(format_fact_occurrence_date("20250815"), "2025-08-15");
+    assert_eq!(format_fact_occurrence_date("114/08/15"), "2025-08-15");
+    // 無法辨識時保留原始值
+    assert_eq!(format_fact_occurrence_date("待確認"), "待確認");
+    assert_eq!(format_fact_occurrence_date(""), "");
+}
+
+#[test]
+fn text_line_with_ky_company() {
+    let announcement = parse_announcement_line("114/08/17 07:00:03 世界健身-KY 公告本公司受邀參加法人說明會 2762").unwrap();
+    assert_eq!(announcement.company_code, "2762");
+    assert_eq!(announcement.company_name, "世界健身-KY");
+    assert_eq!(announcement.title, "公告本公司受邀參加法人說明會");
+    assert_eq!(announcement.time, "07:00:03");
+
+    assert!(parse_announcement_line("發言日期 發言時間 公司名稱 主旨 代號").is_none());
+}
+
+#[test]
+fn detail_page_fields_and_items() {
+    let detail = parse_detail_page(include_str!("fixtures/parser/detail_correction.html")).unwrap();
+    assert_eq!(detail.spokesperson.as_deref(), Some("黃ＯＯ"));
+    assert_eq!(detail.spokesperson_title.as_deref(), Some("財務長"));
+    assert_eq!(detail.spokesperson_phone.as_deref(), Some("03-5636688"));
+    // 主旨含「更正」
+    assert!(detail.is_correction);
+    assert_eq!(detail.fields["符合條款"], "第14款");
+    assert!(detail.fetched_at.is_some());
+
+    // 說明欄依編號拆項，未編號的續行併入上一項
+    let items: Vec<_> = detail.items.iter().map(|item| (item.number, item.label.as_deref(), item.content.as_str())).collect();
+    assert_eq!(items, [
+        (1, Some("董事會決議日期"), "114/08/15"),
+        (2, Some("發放股利種類及金額"), "每股現金股利 5 元\n(含原公告誤植之金額)"),
+        (3, Some("除息基準日"), "114/09/18"),
+        (4, Some("其他應敘明事項"), "無"),
+    ]);
+}
+
+#[test]
+fn detail_page_without_fields_is_an_error() {
+    let error = parse_detail_page("<html><body><center>查無資料</center></body></html>").unwrap_err();
+    assert!(error.to_string().contains("網站結構"), "{}", error);
+}
@@ -0,0 +1,110 @@
+//! 解析器的 golden file 測試。
+//!
+//! `tests/fixtures/parser/` 中每個回應檔 (`*.html`、`*.txt`) 對應一個同名的 `*.json` 預期結果，
+//! 依檔名前綴選擇解析函式：
+//!
+//! - `list_*.html`：`parse_response` (單日列表)
+//! - `history_*.html`：`parse_company_history` (公司代號 2330)
+//! - `detail_*.html`：`parse_detail_page` (明細頁)
+//! - `*.txt`：`parse_text_content` (純文字列表)
+//!
+//! 解析器行為有意變更時，以 `UPDATE_GOLDEN=1 cargo test --test parser_golden` 重新產生預期結果，
+//! 並在提交前檢查差異。
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use twse_announcements::model::Announcement;
+use twse_announcements::parser::{self, ParseOutcome};
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/parser");
+
+// 移除每次執行都不同的欄位 (建立時間) 與冗長的原始 HTML
+fn normalize(announcements: Vec<Announcement>) -> Vec<Announcement> {
+    announcements
+        .into_iter()
+        .map(|mut announcement| {
+            announcement.created_at = None;
+            announcement.raw_html = None;
+            announcement
+        })
+        .collect()
+}
+
+fn outcome_json(outcome: ParseOutcome) -> Value {
+    json!({
+        "status": outcome.status,
+        "warnings": outcome.warnings,
+        "announcements": normalize(outcome.announcements),
+    })
+}
+
+fn parse_fixture(path: &Path) -> Value {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let content = fs::read_to_string(path).unwrap();
+
+    if name.ends_with(".txt") {
+        return json!(normalize(parser::parse_text_content(&content).unwrap()));
+    }
+    if name.starts_with("list_") {
+        return outcome_json(parser::parse_response(&content));
+    }
+    if name.starts_with("history_") {
+        return outcome_json(parser::parse_company_history(&content, "2330"));
+    }
+    if name.starts_with("detail_") {
+        let mut detail = parser::parse_detail_page(&content).unwrap();
+        detail.fetched_at = None;
+        return json!(detail);
+    }
+    panic!("無法判斷 {} 要使用的解析函式，請以 list_、history_、detail_ 開頭或使用 .txt", name);
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(FIXTURE_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html" || ext == "txt"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn parser_output_matches_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    let paths = fixtures();
+    assert!(!paths.is_empty(), "{} 中沒有測試回應檔", FIXTURE_DIR);
+
+    for path in paths {
+        let actual = parse_fixture(&path);
+        let expected_path = path.with_extension("json");
+
+        if update {
+            fs::write(&expected_path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            continue;
+        }
+
+        let Ok(expected) = fs::read_to_string(&expected_path) else {
+            mismatches.push(format!("缺少預期結果 {}", expected_path.display()));
+            continue;
+        };
+        let expected: Value = serde_json::from_str(&expected).unwrap();
+        if actual != expected {
+            mismatches.push(format!(
+                "{} 與預期結果不符，實際輸出:\n{}",
+                path.display(),
+                serde_json::to_string_pretty(&actual).unwrap()
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{}\n\n解析器行為有意變更時請以 UPDATE_GOLDEN=1 重新產生預期結果",
+        mismatches.join("\n\n")
+    );
+}
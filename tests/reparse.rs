@@ -0,0 +1,49 @@
+//! `reparse` 子命令：任何檔案失敗都以非零結束碼結束。
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const NORMAL_DAY: &str = include_str!("fixtures/parser/list_normal_day.html");
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("twse_reparse_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn reparse(dir: &PathBuf) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_twse-announcements"))
+        .args(["--format", "json", "reparse"])
+        .arg(dir)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn succeeds_when_every_file_parses() {
+    let dir = scratch_dir("ok");
+    fs::write(dir.join("list_sii_20250815.html"), NORMAL_DAY).unwrap();
+
+    let output = reparse(&dir);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.join("list_sii_20250815.json").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fails_when_any_file_fails() {
+    let dir = scratch_dir("failed");
+    fs::write(dir.join("list_sii_20250815.html"), NORMAL_DAY).unwrap();
+    // 檔名無法判斷查詢日期
+    fs::write(dir.join("notes.html"), NORMAL_DAY).unwrap();
+
+    let output = reparse(&dir);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 個檔案重新解析失敗"), "{}", String::from_utf8_lossy(&output.stderr));
+    // 其他檔案仍照常處理
+    assert!(dir.join("list_sii_20250815.json").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
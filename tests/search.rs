@@ -0,0 +1,241 @@
+//! 全文檢索：中文二元斷詞、片語與布林查詢、相關性排序、篩選與索引同步。
+
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use twse_announcements::date::RocDate;
+use twse_announcements::model::Announcement;
+use twse_announcements::search::tokenizer::tokenize;
+use twse_announcements::search::{RefreshSummary, SearchIndex, SearchResults};
+use twse_announcements::store::{open_store, AnnouncementQuery, AnnouncementStore, DuplicateMode, MemoryStore, ReplaceScope};
+
+fn date(value: &str) -> RocDate {
+    RocDate::parse(value).unwrap()
+}
+
+fn announcement(code: &str, title: &str, detail: &str, time: &str) -> Announcement {
+    serde_json::from_value(serde_json::json!({
+        "company_code": code,
+        "company_name": "測試公司",
+        "title": title,
+        "date": "114/08/15",
+        "time": time,
+        "query_date": "2025-08-15",
+        "market": "sii",
+        "detail_content": detail,
+    }))
+    .unwrap()
+}
+
+async fn sample_store() -> MemoryStore {
+    let mut merger = announcement("1101", "公告本公司董事會決議辦理私募普通股", "1.董事會決議日期:114/08/15\n2.私募有價證券種類:普通股\n3.私募對象:策略性投資人", "17:30:00");
+    merger.clause_code = Some("11".into());
+    let batch = vec![
+        merger,
+        announcement("1102", "澄清媒體報導", "本公司並無辦理私募之計畫，特此澄清。", "09:00:00"),
+        announcement("1103", "公告本公司現金增資發行新股", "現金增資發行普通股 10,000 張，不辦理私募。", "18:00:00"),
+        announcement("1104", "代子公司公告 ESG Report 發布", "本公司發布 2024 年度永續報告書 (ESG Report)。", "14:00:00"),
+    ];
+    let store = MemoryStore::new();
+    store.save(&batch, &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Upsert).await.unwrap();
+    store
+}
+
+async fn search(index: &SearchIndex, text: &str, filter: &AnnouncementQuery) -> SearchResults {
+    let query = index.parse(text).unwrap();
+    index.search(query.as_ref(), filter, 0, 10).unwrap()
+}
+
+fn codes(results: &SearchResults) -> Vec<String> {
+    results.hits.iter().map(|hit| hit.announcement.company_code.clone()).collect()
+}
+
+// 分數相同時順序不固定，比對前先排序
+fn sorted_codes(results: &SearchResults) -> Vec<String> {
+    let mut codes = codes(results);
+    codes.sort();
+    codes
+}
+
+#[test]
+fn tokenizer_splits_cjk_into_bigrams() {
+    let texts: Vec<String> = tokenize("私募股權，ＥＳＧ Report 2024年").into_iter().map(|t| t.text).collect();
+    assert_eq!(texts, ["私募", "募股", "股權", "esg", "report", "2024", "年"]);
+
+    // 位移對應原文 (全形字為 3 個位元組)
+    let tokens = tokenize("私募，ＥＳＧ");
+    assert_eq!((tokens[0].offset_from, tokens[0].offset_to), (0, 6));
+    assert_eq!((tokens[1].offset_from, tokens[1].offset_to), (9, 18));
+    assert_eq!(tokens.iter().map(|t| t.position).collect::<Vec<_>>(), [0, 1]);
+}
+
+#[tokio::test]
+async fn finds_terms_in_detail_content_and_ranks_titles_first() {
+    let store = sample_store().await;
+    let index = SearchIndex::in_memory();
+    assert_eq!(index.refresh(&store).await.unwrap(), RefreshSummary { indexed: 4, removed: 0, total: 4 });
+
+    // 標題與內容都有「私募」的公告排在最前
+    let results = search(&index, "私募", &AnnouncementQuery::default()).await;
+    assert_eq!(results.total, 3);
+    assert_eq!(codes(&results)[0], "1101");
+
+    let top = &results.hits[0].highlights;
+    assert_eq!(top.title, "公告本公司董事會決議辦理<mark>私募</mark>普通股");
+    assert!(top.snippet.as_deref().unwrap().contains("<mark>私募</mark>有價證券"));
+
+    // 只有內容符合時標題原樣輸出
+    let clarification = results.hits.iter().find(|hit| hit.announcement.company_code == "1102").unwrap();
+    assert_eq!(clarification.highlights.title, "澄清媒體報導");
+}
+
+#[tokio::test]
+async fn supports_phrase_boolean_and_field_queries() {
+    let store = sample_store().await;
+    let index = SearchIndex::in_memory();
+    index.refresh(&store).await.unwrap();
+    let all = AnnouncementQuery::default();
+
+    // 多字詞須連續出現：「私募普通股」只出現在 1101 標題
+    assert_eq!(codes(&search(&index, "私募普通股", &all).await), ["1101"]);
+    assert_eq!(codes(&search(&index, "\"esg report\"", &all).await), ["1104"]);
+    assert_eq!(codes(&search(&index, "esg", &all).await), ["1104"]);
+
+    assert_eq!(sorted_codes(&search(&index, "私募 普通股", &all).await), ["1101", "1103"]);
+
+    assert_eq!(codes(&search(&index, "私募 -澄清 -增資", &all).await), ["1101"]);
+    assert_eq!(sorted_codes(&search(&index, "澄清 OR 永續", &all).await), ["1102", "1104"]);
+    assert_eq!(codes(&search(&index, "title:私募", &all).await), ["1101"]);
+
+    assert!(index.parse("title:(私募").is_err());
+}
+
+#[tokio::test]
+async fn applies_announcement_filters() {
+    let store = sample_store().await;
+    let index = SearchIndex::in_memory();
+    index.refresh(&store).await.unwrap();
+
+    let after_close = AnnouncementQuery { time_from: Some("17:00:00".into()), ..Default::default() };
+    assert_eq!(sorted_codes(&search(&index, "私募", &after_close).await), ["1101", "1103"]);
+
+    let clause = AnnouncementQuery { clause_codes: vec!["11".into()], ..Default::default() };
+    assert_eq!(codes(&search(&index, "私募", &clause).await), ["1101"]);
+
+    let companies = AnnouncementQuery { companies: vec!["1102".into(), "1104".into()], ..Default::default() };
+    assert_eq!(codes(&search(&index, "本公司", &companies).await).len(), 2);
+
+    let range = AnnouncementQuery { start_date: Some(date("2025-08-16")), ..Default::default() };
+    assert_eq!(search(&index, "私募", &range).await.total, 0);
+}
+
+#[tokio::test]
+async fn refresh_indexes_only_changes() {
+    let store = sample_store().await;
+    let index = SearchIndex::in_memory();
+    index.refresh(&store).await.unwrap();
+    assert_eq!(index.refresh(&store).await.unwrap(), RefreshSummary { indexed: 0, removed: 0, total: 4 });
+
+    // 更正內容後重新寫入，舊內容不再符合
+    let mut corrected = store.query(&AnnouncementQuery { companies: vec!["1102".into()], ..Default::default() }).await.unwrap();
+    corrected[0].detail_content = Some("本公司並無合併之計畫。".into());
+    corrected[0].id = None;
+    store.save(&corrected, &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Upsert).await.unwrap();
+
+    assert_eq!(index.refresh(&store).await.unwrap(), RefreshSummary { indexed: 1, removed: 0, total: 4 });
+    assert_eq!(index.len(), 4);
+    assert_eq!(codes(&search(&index, "合併", &AnnouncementQuery::default()).await), ["1102"]);
+    assert_eq!(search(&index, "私募", &AnnouncementQuery::default()).await.total, 2);
+
+    // 以 replace 重新寫入當日資料後，不在新資料中的公告移出索引
+    let remaining = AnnouncementQuery { companies: vec!["1101".into(), "1102".into(), "1103".into()], ..Default::default() };
+    let remaining = store.query(&remaining).await.unwrap();
+    store.save(&remaining, &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Replace).await.unwrap();
+
+    // 重新寫入的公告取得新 id，索引中保存的公告一併更新
+    assert_eq!(index.refresh(&store).await.unwrap(), RefreshSummary { indexed: 3, removed: 1, total: 3 });
+    assert_eq!(search(&index, "永續", &AnnouncementQuery::default()).await.total, 0);
+}
+
+#[tokio::test]
+async fn directory_index_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("twse_search_index_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let store = sample_store().await;
+
+    let index = SearchIndex::open_in_dir(&path).unwrap();
+    index.refresh(&store).await.unwrap();
+    drop(index);
+
+    let reopened = SearchIndex::open_in_dir(&path).unwrap();
+    assert_eq!(reopened.len(), 4);
+    assert_eq!(reopened.refresh(&store).await.unwrap().indexed, 0);
+    assert_eq!(codes(&search(&reopened, "永續報告書", &AnnouncementQuery::default()).await), ["1104"]);
+    drop(reopened);
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[tokio::test]
+async fn incremental_refresh_reads_only_recently_fetched() {
+    let store = sample_store().await;
+    let index = SearchIndex::in_memory();
+    index.refresh(&store).await.unwrap();
+
+    // 擷取時間早於上次同步的公告不會被增量同步讀到，範例資料沒有擷取時間
+    let mut fetched = announcement("2330", "公告本公司私募普通股定價", "私募價格每股 500 元。", "19:00:00");
+    fetched.created_at = Some(Utc::now());
+    let mut stale = announcement("2317", "澄清媒體報導私募傳聞", "本公司並無私募計畫。", "19:30:00");
+    stale.created_at = Some(Utc::now() - Duration::days(1));
+    store.save(&[fetched, stale], &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Upsert).await.unwrap();
+
+    assert_eq!(index.refresh_recent(&store).await.unwrap(), RefreshSummary { indexed: 1, removed: 0, total: 5 });
+    assert_eq!(codes(&search(&index, "私募 定價", &AnnouncementQuery::default()).await), ["2330"]);
+
+    // 完整同步補上增量同步讀不到的公告
+    assert_eq!(index.refresh(&store).await.unwrap(), RefreshSummary { indexed: 1, removed: 0, total: 6 });
+}
+
+#[tokio::test]
+async fn hits_are_read_from_the_index() {
+    let store: Arc<dyn AnnouncementStore> = Arc::new(sample_store().await);
+    let index = Arc::new(SearchIndex::in_memory());
+
+    // 第一次查詢前完整同步
+    index.refresh_in_background(&store).await.unwrap();
+    assert_eq!(index.len(), 4);
+
+    // 儲存後端清空後，到下次同步前結果仍由索引讀出
+    store.save(&[], &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Replace).await.unwrap();
+    let results = search(&index, "永續", &AnnouncementQuery::default()).await;
+    assert_eq!(results.hits[0].announcement.company_code, "1104");
+    assert!(results.hits[0].announcement.detail_content.as_deref().unwrap().contains("永續報告書"));
+    assert!(results.hits[0].announcement.announcement_id.is_some());
+}
+
+#[tokio::test]
+async fn created_since_compares_whole_seconds_on_every_backend() {
+    let path = std::env::temp_dir().join(format!("twse_search_created_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let sqlite = open_store(&format!("sqlite://{}", path.display()), "", "").await.unwrap();
+    let memory: Arc<dyn AnnouncementStore> = Arc::new(MemoryStore::new());
+
+    let fetched_at = |code: &str, at: &str| {
+        let mut announcement = announcement(code, "公告", "", "10:00:00");
+        announcement.created_at = Some(at.parse().unwrap());
+        announcement
+    };
+    let batch = [
+        fetched_at("1101", "2025-08-15T09:59:59.900Z"),
+        fetched_at("1102", "2025-08-15T10:00:00Z"),
+        fetched_at("1103", "2025-08-15T10:00:00.500Z"),
+        announcement("1104", "公告", "", "10:00:00"),
+    ];
+    let since = AnnouncementQuery { created_since: Some("2025-08-15T10:00:00.200Z".parse().unwrap()), ..Default::default() };
+    for store in [&sqlite, &memory] {
+        store.save(&batch, &ReplaceScope::day(date("2025-08-15"), "all"), DuplicateMode::Upsert).await.unwrap();
+        let mut found: Vec<String> = store.query(&since).await.unwrap().into_iter().map(|a| a.company_code).collect();
+        found.sort();
+        assert_eq!(found, ["1102", "1103"], "{}", store.backend());
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,104 @@
+//! SQLite 儲存後端：資料表的 schema 版本，以及與記憶體後端相同的標題關鍵字語意。
+
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use twse_announcements::date::RocDate;
+use twse_announcements::model::Announcement;
+use twse_announcements::store::{open_store, AnnouncementQuery, AnnouncementStore, DuplicateMode, MemoryStore, ReplaceScope};
+
+fn database_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("twse_sqlite_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn open(path: &std::path::Path) -> Arc<dyn AnnouncementStore> {
+    open_store(&format!("sqlite://{}", path.display()), "", "").await.unwrap()
+}
+
+fn announcement(code: &str, title: &str) -> Announcement {
+    serde_json::from_value(serde_json::json!({
+        "company_code": code,
+        "company_name": "測試公司",
+        "title": title,
+        "date": "114/08/15",
+        "time": "17:30:00",
+        "query_date": "2025-08-15",
+        "fact_occurrence_date": "2025-08-14",
+        "market": "sii",
+    }))
+    .unwrap()
+}
+
+fn user_version(path: &std::path::Path) -> i32 {
+    Connection::open(path).unwrap().query_row("PRAGMA user_version", [], |r| r.get(0)).unwrap()
+}
+
+#[tokio::test]
+async fn new_database_is_created_at_current_schema_version() {
+    let path = database_path("schema");
+    let store = open(&path).await;
+    assert_eq!(user_version(&path), 1);
+
+    let mut saved = announcement("2330", "公告本公司 ＥＳＧ 報告書");
+    saved.ensure_identity();
+    let scope = ReplaceScope::day(RocDate::from_ymd(2025, 8, 15).unwrap(), "sii");
+    store.save(std::slice::from_ref(&saved), &scope, DuplicateMode::Upsert).await.unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    let (announcement_id, occurrence, search_title): (String, String, String) = conn
+        .query_row("SELECT announcement_id, fact_occurrence_date, search_title FROM announcements", [], |r| {
+            Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+        })
+        .unwrap();
+    assert_eq!(Some(announcement_id.as_str()), saved.announcement_id.as_deref());
+    assert_eq!(occurrence, "2025-08-14");
+    assert_eq!(search_title, "公告本公司 ｅｓｇ 報告書");
+
+    // 再次開啟沿用既有資料表；較新版本的資料庫拒絕開啟
+    drop(store);
+    assert_eq!(open(&path).await.count(&AnnouncementQuery::default()).await.unwrap(), 1);
+    conn.pragma_update(None, "user_version", 2).unwrap();
+    let error = open_store(&format!("sqlite://{}", path.display()), "", "").await.err().unwrap();
+    assert!(error.to_string().contains("schema"), "{}", error);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn title_search_is_literal_and_case_insensitive_on_every_backend() {
+    let batch = vec![
+        announcement("1101", "代子公司公告 ＥＳＧ Report 發布"),
+        announcement("1102", "現金增資 100% 認購"),
+        announcement("1103", "公告 A_B 合併案"),
+        announcement("1104", "公告 AxB 合併案"),
+    ];
+    let path = database_path("search");
+    let stores: [(&str, Arc<dyn AnnouncementStore>); 2] = [("memory", Arc::new(MemoryStore::new())), ("sqlite", open(&path).await)];
+
+    for (backend, store) in stores {
+        let scope = ReplaceScope::day(RocDate::from_ymd(2025, 8, 15).unwrap(), "sii");
+        store.save(&batch, &scope, DuplicateMode::Upsert).await.unwrap();
+
+        for (search, expected) in [
+            // 不分大小寫，包含全形英文字母
+            ("ｅｓｇ REPORT", vec!["1101"]),
+            ("report", vec!["1101"]),
+            // % 與 _ 不是萬用字元
+            ("%", vec!["1102"]),
+            ("a_b", vec!["1103"]),
+            // 正規表示式的特殊字元也是字面比對
+            ("a.b", vec![]),
+            (".*", vec![]),
+            ("(", vec![]),
+        ] {
+            let query = AnnouncementQuery { search: Some(search.into()), ..Default::default() };
+            let mut codes: Vec<_> = store.query(&query).await.unwrap().into_iter().map(|a| a.company_code).collect();
+            codes.sort();
+            assert_eq!(codes, expected, "{} {}", backend, search);
+            assert_eq!(store.count(&query).await.unwrap(), expected.len() as u64, "{} {}", backend, search);
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,69 @@
+//! 監看模式比對今日已知公告：新公告、已看過的公告與內容更正。
+
+use twse_announcements::model::Announcement;
+use twse_announcements::watch::SeenAnnouncements;
+
+fn announcement(code: &str, time: &str, content: &str) -> Announcement {
+    serde_json::from_value(serde_json::json!({
+        "company_code": code,
+        "company_name": "測試公司",
+        "title": "公告本公司董事會決議",
+        "date": "114/08/15",
+        "time": time,
+        "detail_content": content,
+    }))
+    .unwrap()
+}
+
+fn codes(announcements: &[Announcement]) -> Vec<&str> {
+    announcements.iter().map(|a| a.company_code.as_str()).collect()
+}
+
+#[test]
+fn new_announcements_are_reported_until_marked() {
+    let mut seen = SeenAnnouncements::new();
+    let first = vec![announcement("2330", "17:30:00", "1.董事會決議日期:114/08/15")];
+
+    let new = seen.diff(first.clone());
+    assert_eq!(codes(&new), ["2330"]);
+    // 保存失敗未標記時，下次查詢再次篩出
+    assert_eq!(seen.diff(first.clone()).len(), 1);
+
+    seen.mark(&new);
+    assert_eq!(seen.len(), 1);
+    assert!(seen.diff(first.clone()).is_empty());
+
+    // 同一家公司不同時間的公告為另一則
+    let second = vec![first[0].clone(), announcement("2330", "18:00:00", "1.董事會決議日期:114/08/15")];
+    let new = seen.diff(second);
+    assert_eq!(new.iter().map(|a| a.time.as_str()).collect::<Vec<_>>(), ["18:00:00"]);
+}
+
+#[test]
+fn revised_content_is_reported_again() {
+    let mut seen = SeenAnnouncements::new();
+    let original = announcement("2317", "17:30:00", "1.除息基準日:114/09/18");
+    seen.mark(std::slice::from_ref(&original));
+
+    let revised = announcement("2317", "17:30:00", "1.除息基準日:114/09/19");
+    assert_eq!(codes(&seen.diff(vec![original.clone(), revised.clone()])), ["2317"]);
+    assert_eq!(seen.diff(vec![revised.clone()])[0].detail_content, revised.detail_content);
+
+    seen.mark(std::slice::from_ref(&revised));
+    assert!(seen.diff(vec![revised]).is_empty());
+    assert_eq!(seen.len(), 1);
+}
+
+#[test]
+fn keys_loaded_from_store_are_already_seen() {
+    let stored = announcement("6488", "09:00:00", "1.事實發生日:114/08/15");
+    let mut seen = SeenAnnouncements::from_keys([stored.key()]);
+
+    // 資料庫只有識別鍵，第一次看到內容時記錄雜湊而不視為更正
+    assert!(seen.diff(vec![stored.clone()]).is_empty());
+    assert!(seen.diff(vec![stored]).is_empty());
+
+    let revised = announcement("6488", "09:00:00", "1.事實發生日:114/08/14");
+    assert_eq!(codes(&seen.diff(vec![revised])), ["6488"]);
+    assert_eq!(codes(&seen.diff(vec![announcement("1240", "09:00:00", "")])), ["1240"]);
+}
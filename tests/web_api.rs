@@ -0,0 +1,441 @@
+//! 以記憶體儲存後端驅動 Web 應用程式的頁面與 API 路由，不需要資料庫。
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use twse_announcements::store::{open_store, AnnouncementQuery, AnnouncementStore, DuplicateMode, MemoryStore, ReplaceScope};
+use twse_announcements::web::create_app;
+use twse_announcements::web::error::{ApiError, ErrorCode};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/announcements.json");
+
+fn fixture_store() -> Arc<MemoryStore> {
+    Arc::new(MemoryStore::load_json(FIXTURE).unwrap())
+}
+
+async fn get(app: Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+async fn get_json(app: Router, uri: &str) -> Value {
+    let (status, body) = get(app, uri).await;
+    assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+    serde_json::from_str(&body).unwrap()
+}
+
+fn company_codes(value: &Value) -> Vec<&str> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["company_code"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn legacy_api_filters_announcements() {
+    let app = create_app(fixture_store());
+
+    let all = get_json(app.clone(), "/api/announcements").await;
+    assert_eq!(company_codes(&all), ["6488", "2330", "2317", "2330", "1240"]);
+
+    let company = get_json(app.clone(), "/api/announcements?company=2330").await;
+    assert_eq!(company_codes(&company), ["2330", "2330"]);
+
+    // 日期參數接受民國格式
+    let day = get_json(app.clone(), "/api/announcements?date=114/08/15&market=sii").await;
+    assert_eq!(company_codes(&day), ["2330", "2317"]);
+
+    let range = get_json(app.clone(), "/api/announcements?start_date=2025-08-13&end_date=2025-08-14").await;
+    assert_eq!(company_codes(&range), ["2330", "1240"]);
+
+    let search = get_json(app.clone(), "/api/announcements?search=esg&limit=1").await;
+    assert_eq!(company_codes(&search), ["1240"]);
+    assert_eq!(search[0]["date"], "114/08/13");
+
+    let limited = get_json(app, "/api/announcements?limit=2").await;
+    assert_eq!(limited.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn api_stats_and_clause_codes() {
+    let app = create_app(fixture_store());
+
+    let stats = get_json(app.clone(), "/api/stats").await;
+    assert_eq!(stats["total_announcements"], 5);
+    assert_eq!(stats["top_companies"][0]["company_code"], "2330");
+    assert_eq!(stats["top_companies"][0]["count"], 2);
+
+    let clause_codes = get_json(app.clone(), "/api/clause-codes").await;
+    let codes: Vec<&str> = clause_codes.as_array().unwrap().iter().map(|c| c["code"].as_str().unwrap()).collect();
+    assert_eq!(codes.first(), Some(&"1"));
+    assert!(codes.windows(2).all(|w| w[0].parse::<i32>().unwrap() < w[1].parse::<i32>().unwrap()));
+
+    let debug = get_json(app.clone(), "/api/debug").await;
+    assert_eq!(debug["total_count"], 5);
+    assert_eq!(debug["store"]["backend"], "memory");
+
+    // 舊版路徑與 /api/v1 回傳相同內容
+    assert_eq!(get_json(app.clone(), "/api/v1/stats").await, stats);
+    assert_eq!(get_json(app.clone(), "/api/v1/clause-codes").await, clause_codes);
+    assert_eq!(get_json(app, "/api/v1/diagnostics").await["store"]["backend"], "memory");
+}
+
+#[tokio::test]
+async fn pages_render() {
+    let store = fixture_store();
+    let app = create_app(store.clone());
+
+    let (status, body) = get(app.clone(), "/?company=2317").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("澄清媒體報導"));
+    assert!(!body.contains("本公司董事會決議股利分派"));
+
+    let (status, body) = get(app.clone(), "/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("台積電"));
+
+    let query = AnnouncementQuery { companies: vec!["6488".into()], ..Default::default() };
+    let id = store.query(&query).await.unwrap()[0].id.unwrap().to_hex();
+    let (status, body) = get(app.clone(), &format!("/detail/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("公告本公司董事會通過合併案"));
+
+    let (status, _) = get(app.clone(), "/detail/000000000000000000000000").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = get(app.clone(), "/?start_date=2025-08-13&end_date=2025-08-14&company=").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("(共 2 筆)"));
+
+    let (status, body) = get(app, "/search").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("/api/v1/announcements"));
+}
+
+#[tokio::test]
+async fn api_returns_revision_history() {
+    let store = fixture_store();
+    let query = AnnouncementQuery { companies: vec!["2317".into()], ..Default::default() };
+    let mut corrected = store.query(&query).await.unwrap().remove(0);
+    let announcement_id = corrected.announcement_id.clone().unwrap();
+
+    corrected.detail_content = Some("更正後的說明".into());
+    let scope = ReplaceScope::day(corrected.query_date.unwrap(), "all");
+    store.save(&[corrected], &scope, DuplicateMode::Upsert).await.unwrap();
+
+    let app = create_app(store.clone());
+    let history = get_json(app.clone(), &format!("/api/v1/announcements/{}/revisions", announcement_id)).await;
+    assert_eq!(history["announcement_id"], announcement_id.as_str());
+    assert_eq!(history["current"]["revision"], 2);
+    assert_eq!(history["current"]["detail_content"], "更正後的說明");
+    assert_eq!(history["revisions"].as_array().unwrap().len(), 1);
+    assert_eq!(history["revisions"][0]["revision"], 1);
+
+    let legacy = get_json(app.clone(), &format!("/api/announcements/{}/revisions", announcement_id)).await;
+    assert_eq!(legacy, history);
+
+    let (status, _) = get(app.clone(), "/api/v1/announcements/unknown/revisions").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = get(app, &format!("/detail/{}", announcement_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("版本紀錄"));
+}
+
+#[tokio::test]
+async fn v1_announcements_are_paginated() {
+    let app = create_app(fixture_store());
+
+    let first = get_json(app.clone(), "/api/v1/announcements?limit=2").await;
+    assert_eq!(company_codes(&first["items"]), ["6488", "2330"]);
+    assert_eq!(first["total"], 5);
+    assert_eq!(first["page"], 1);
+    assert_eq!(first["limit"], 2);
+    assert_eq!(first["total_pages"], 3);
+
+    let last = get_json(app.clone(), "/api/v1/announcements?limit=2&page=3").await;
+    assert_eq!(company_codes(&last["items"]), ["1240"]);
+
+    // 日期範圍優先於單一日期，總數只計算符合條件的公告
+    let range = get_json(
+        app.clone(),
+        "/api/v1/announcements?date=2025-08-15&start_date=2025-08-13&end_date=2025-08-14",
+    )
+    .await;
+    assert_eq!(company_codes(&range["items"]), ["2330", "1240"]);
+    assert_eq!(range["total"], 2);
+
+    // 表單送出的空字串視為未指定
+    let blank = get_json(app.clone(), "/api/v1/announcements?company=&search=&market=").await;
+    assert_eq!(blank["total"], 5);
+
+    let query = AnnouncementQuery { companies: vec!["6488".into()], ..Default::default() };
+    let announcement_id = fixture_store().query(&query).await.unwrap()[0].stable_id();
+    let single = get_json(app.clone(), &format!("/api/v1/announcements/{}", announcement_id)).await;
+    assert_eq!(single["company_code"], "6488");
+    assert_eq!(single["announcement_id"], announcement_id.as_str());
+
+    let (status, _) = get(app, "/api/v1/announcements/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn v1_announcements_walk_with_cursor() {
+    let app = create_app(fixture_store());
+
+    let mut codes = Vec::new();
+    let mut uri = "/api/v1/announcements?limit=2&with_total=false".to_string();
+    loop {
+        let page = get_json(app.clone(), &uri).await;
+        assert!(page.get("total").is_none());
+        assert!(page.get("total_pages").is_none());
+        codes.extend(company_codes(&page["items"]).into_iter().map(str::to_string));
+        let Some(cursor) = page["next_cursor"].as_str() else { break };
+        uri = format!("/api/v1/announcements?limit=2&with_total=false&cursor={}", cursor);
+    }
+    let all = get_json(app.clone(), "/api/v1/announcements").await;
+    assert_eq!(codes, company_codes(&all["items"]));
+    assert!(all.get("next_cursor").is_none());
+
+    // 游標查詢保留篩選條件但沒有頁碼；總數預設只在第一頁計算
+    let first = get_json(app.clone(), "/api/v1/announcements?limit=1&start_date=2025-08-13").await;
+    assert_eq!(first["total"], 5);
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let second = get_json(app.clone(), &format!("/api/v1/announcements?limit=1&start_date=2025-08-13&cursor={}", cursor)).await;
+    assert!(second.get("total").is_none());
+    assert!(second.get("page").is_none());
+    assert_ne!(company_codes(&second["items"]), company_codes(&first["items"]));
+    let counted = get_json(app.clone(), &format!("/api/v1/announcements?limit=1&start_date=2025-08-13&with_total=true&cursor={}", cursor)).await;
+    assert_eq!(counted["total"], first["total"]);
+
+    let (status, body) = get(app.clone(), "/api/v1/announcements?cursor=garbage").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], "cursor");
+
+    let (status, _) = get(app, &format!("/api/v1/announcements?page=2&cursor={}", cursor)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn index_pager_keeps_filters() {
+    let app = create_app(fixture_store());
+
+    let (status, body) = get(app.clone(), "/?limit=1&page=2&search=%E6%9C%AC%E5%85%AC%E5%8F%B8").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("/?search=%E6%9C%AC%E5%85%AC%E5%8F%B8&amp;page=1&amp;limit=1"), "{}", body);
+
+    let (_, body) = get(app, "/?market=sii&limit=2").await;
+    assert!(body.contains("/?market=sii&amp;page=2&amp;limit=2"));
+    assert!(body.contains(r#"<option value="sii" selected>"#));
+}
+
+#[tokio::test]
+async fn openapi_document_describes_v1() {
+    let spec = get_json(create_app(fixture_store()), "/api/openapi.json").await;
+
+    let list = &spec["paths"]["/api/v1/announcements"]["get"];
+    let parameters: Vec<&str> = list["parameters"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert!(["company", "start_date", "end_date", "page", "limit"].iter().all(|name| parameters.contains(name)));
+    assert_eq!(list["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/AnnouncementPage");
+    assert_eq!(list["responses"]["400"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ApiError");
+    assert!(spec["paths"]["/api/v1/announcements/{id}/revisions"]["get"]["responses"]["404"].is_object());
+
+    let schemas = &spec["components"]["schemas"];
+    for name in ["Announcement", "AnnouncementPage", "ClauseCode", "Stats", "ApiError"] {
+        assert!(schemas[name].is_object(), "缺少 schema {}", name);
+    }
+    assert_eq!(schemas["Announcement"]["properties"]["date"]["type"], "string");
+}
+
+#[tokio::test]
+async fn invalid_requests_return_json_errors() {
+    let app = create_app(fixture_store());
+
+    let (status, body) = get(app.clone(), "/api/v1/announcements?start_date=2025-13-45").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(error["code"], "invalid_parameter");
+    assert_eq!(error["parameter"], "start_date");
+    assert!(error["message"].as_str().unwrap().contains("2025-13-45"));
+
+    let (status, body) = get(app.clone(), "/api/v1/announcements?start_date=2025-08-15&end_date=2025-08-13").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], "start_date");
+
+    let (status, body) = get(app.clone(), "/api/v1/announcements?page=abc").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(error["code"], "invalid_parameter");
+    assert_eq!(error["parameter"], "page");
+
+    // 無法解析的查詢參數回報參數名稱
+    for (uri, parameter) in [
+        ("/api/v1/announcements?limit=abc", "limit"),
+        ("/api/v1/search?q=台積電&limit=-1", "limit"),
+        ("/api/announcements?page=1.5", "page"),
+    ] {
+        let (status, body) = get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], parameter, "{}", uri);
+    }
+
+    // 舊版路徑不再把無法辨識的日期當成未指定
+    let (status, body) = get(app.clone(), "/api/announcements?date=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], "date");
+
+    let (status, body) = get(app.clone(), "/api/v1/announcements/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["code"], "not_found");
+
+    let (status, body) = get(app, "/?date=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("yesterday"));
+}
+
+#[tokio::test]
+async fn store_failures_return_service_unavailable() {
+    let path = std::env::temp_dir().join(format!("twse_web_api_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = open_store(&format!("sqlite://{}", path.display()), "", "").await.unwrap();
+    rusqlite::Connection::open(&path).unwrap().execute_batch("DROP TABLE announcements").unwrap();
+
+    let app = create_app(store);
+    for uri in ["/api/v1/announcements", "/api/v1/stats", "/api/announcements"] {
+        let (status, body) = get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", uri);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["code"], "store_unavailable");
+    }
+
+    let (status, _) = get(app, "/").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn only_store_errors_are_service_unavailable() {
+    let store_error = anyhow::Error::new(rusqlite::Error::InvalidQuery).context("查詢公告失敗");
+    assert_eq!(ApiError::from(store_error).code, ErrorCode::StoreUnavailable);
+
+    // 全文檢索索引與其他內部錯誤不是儲存後端的問題
+    let index_error = anyhow::anyhow!("索引檔案損毀");
+    let error = ApiError::from(index_error);
+    assert_eq!(error.code, ErrorCode::Internal);
+    assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+// 載入測試資料的 SQLite 資料庫，回傳資料庫路徑供測試結束時刪除
+async fn sqlite_fixture_store(name: &str) -> (Arc<dyn AnnouncementStore>, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!("twse_web_api_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = open_store(&format!("sqlite://{}", path.display()), "", "").await.unwrap();
+    let fixture = fixture_store().query(&AnnouncementQuery::default()).await.unwrap();
+    let scope = ReplaceScope::day(fixture[0].date, "all");
+    store.save(&fixture, &scope, DuplicateMode::Upsert).await.unwrap();
+    (store, path)
+}
+
+#[tokio::test]
+async fn sqlite_cursor_matches_page_order() {
+    let (store, path) = sqlite_fixture_store("cursor").await;
+    let app = create_app(store);
+    let mut codes = Vec::new();
+    let mut uri = "/api/v1/announcements?limit=2".to_string();
+    loop {
+        let page = get_json(app.clone(), &uri).await;
+        // 總數只在第一頁計算
+        assert_eq!(page.get("total").cloned(), (!uri.contains("cursor=")).then(|| Value::from(5)));
+        codes.extend(company_codes(&page["items"]).into_iter().map(str::to_string));
+        let Some(cursor) = page["next_cursor"].as_str() else { break };
+        uri = format!("/api/v1/announcements?limit=2&cursor={}", cursor);
+    }
+    assert_eq!(codes, company_codes(&get_json(app, "/api/v1/announcements").await["items"]));
+    assert_eq!(codes.len(), 5);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn filters_by_clause_code_time_and_companies() {
+    let (sqlite, path) = sqlite_fixture_store("filters").await;
+    let memory: Arc<dyn AnnouncementStore> = fixture_store();
+
+    for store in [memory, sqlite] {
+        let app = create_app(store);
+        let codes = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let page = get_json(app, uri).await;
+                company_codes(&page["items"]).into_iter().map(str::to_string).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(codes("/api/v1/announcements?clause_code=11").await, ["2330"]);
+        assert_eq!(codes("/api/v1/announcements?clause_code=11,20").await, ["2330", "2330"]);
+        // 盤後公告
+        assert_eq!(codes("/api/v1/announcements?time_from=17:00").await, ["6488", "2330"]);
+        assert_eq!(codes("/api/v1/announcements?company=2330&time_to=16:30").await, ["2330"]);
+        assert_eq!(codes("/api/v1/announcements?company=2317,%201240").await, ["2317", "1240"]);
+        assert_eq!(codes("/api/v1/announcements?clause_code=11&time_from=17:00&start_date=2025-08-01").await, ["2330"]);
+        assert!(codes("/api/v1/announcements?occurrence_start_date=2025-01-01").await.is_empty());
+
+        for (uri, parameter) in [
+            ("/api/v1/announcements?clause_code=11,99", "clause_code"),
+            ("/api/v1/announcements?time_from=25:00", "time_from"),
+            ("/api/v1/announcements?time_from=18:00&time_to=09:00", "time_from"),
+            ("/api/v1/announcements?occurrence_start_date=2025-08-15&occurrence_end_date=2025-08-01", "occurrence_start_date"),
+        ] {
+            let (status, body) = get(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], parameter, "{}", uri);
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn full_text_search_ranks_and_highlights() {
+    let app = create_app(fixture_store());
+
+    let results = get_json(app.clone(), "/api/v1/search?q=董事會").await;
+    assert_eq!(results["total"], 2);
+    assert_eq!(results["page"], 1);
+    let titles: Vec<&str> =
+        results["items"].as_array().unwrap().iter().map(|hit| hit["highlights"]["title"].as_str().unwrap()).collect();
+    assert!(titles.contains(&"本公司<mark>董事會</mark>決議股利分派"), "{:?}", titles);
+    assert!(results["items"][0]["score"].as_f64().unwrap() > 0.0);
+    assert!(results["items"][0]["announcement"]["announcement_id"].is_string());
+
+    // 篩選參數與公告列表相同
+    let filtered = get_json(app.clone(), "/api/v1/search?q=董事會&company=6488").await;
+    assert_eq!(filtered["total"], 1);
+    assert_eq!(filtered["items"][0]["announcement"]["company_code"], "6488");
+    assert_eq!(get_json(app.clone(), "/api/v1/search?q=esg%20-report").await["total"], 0);
+
+    for (uri, parameter) in [
+        ("/api/v1/search", "q"),
+        ("/api/v1/search?q=title:(董事會", "q"),
+        ("/api/v1/search?q=董事會&search=股利", "search"),
+        ("/api/v1/search?q=董事會&cursor=abc", "cursor"),
+        ("/api/v1/search?q=董事會&clause_code=99", "clause_code"),
+    ] {
+        let (status, body) = get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["parameter"], parameter, "{}", uri);
+    }
+
+    let spec = get_json(app, "/api/openapi.json").await;
+    let search = &spec["paths"]["/api/v1/search"]["get"];
+    assert_eq!(search["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/SearchPage");
+    assert!(search["parameters"].as_array().unwrap().iter().any(|p| p["name"] == "q"));
+}